@@ -1,25 +1,21 @@
-use traffic_core::conditions::Parser;
-use traffic_core::conditions::gen_condition::generate_condition;
+use traffic_core::conditions::{parse_ddr_expression, to_ddr_string};
 use traffic_core::converters::gen_scn_from_chars;
 
 fn view_flow_and_ast() {
 
         let examples = vec![
-        "1",
-        "1|3",
-        "1&3",
-        "1|3&5",
-        "(1|3)",
-        "(1|3)&5",
-        "1|(3&5)",
+        "1-3",
+        "or 1-3",
+        "and 1-3",
+        "(or 1-3) and (or 4-6)",
+        "(1-3) and (4-6) or (7-9)",
     ];
 
     for example in examples {
-        let mut parser = Parser::new(example);
-        match parser.parse_with_visual() {
+        match parse_ddr_expression(example) {
             Ok(expr) => {
                 println!("УСПЕХ! Получили AST: {:?}", expr);
-                println!("В виде ddr: {}", expr.to_ddr_string());
+                println!("В виде ddr: {}", to_ddr_string(&expr));
                 println!();
             }
             Err(e) => {
@@ -31,16 +27,6 @@ fn view_flow_and_ast() {
 }
 
 
-fn simple_gen_condotion() {
-    
-
-    let test_cases = vec!["1|3", "1|3, 24", "1&3, 14"];
-    for input in test_cases {
-        println!("{} -> {}", input, generate_condition(input));
-    }
-}
-
-
 fn covert_scn() {
     let test_cases = vec!["CO4554", "C1111",];
     for test_case in test_cases {
@@ -54,7 +40,7 @@ fn covert_scn() {
 fn main() {
 
 
-    // view_flow_and_ast();
+    view_flow_and_ast();
     covert_scn();
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,48 @@
+//! Симулятор SNMP-агента контроллера на localhost
+//!
+//! Запуск: cargo run --example snmp_simulator -- [адрес] [SCN]
+//! По умолчанию слушает 127.0.0.1:16100 и отвечает за SCN "CO4554".
+//!
+//! Проверить можно утилитами net-snmp:
+//!   snmpwalk -v2c -c private 127.0.0.1:16100 .1.3.6.1.4.1.99999
+//!   snmpset  -v2c -c private 127.0.0.1:16100 <OID детектора> i 1
+
+use std::net::UdpSocket;
+
+use traffic_core::conditions::parse_ddr_expression;
+use traffic_core::snmp::{Access, Oid, Simulator, Value};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:16100".to_string());
+    let scn = args.next().unwrap_or_else(|| "CO4554".to_string());
+
+    // Условные колонки для примера, не привязанные к конкретной MIB
+    let base: Oid = "1.3.6.1.4.1.99999".parse()?;
+    let simulator = Simulator::new("private");
+
+    let stage = simulator.add_object(&base.child(1), &scn, Value::Integer(1), Access::ReadWrite)?;
+    let name = simulator.add_object(&base.child(2), &scn, Value::string(&scn), Access::ReadOnly)?;
+    println!("🔹 Фаза:        {}", stage);
+    println!("🔹 Имя:         {}", name);
+
+    for detector in 1..=8 {
+        let oid = simulator.add_detector(&base.child(3), &scn, detector)?;
+        println!("🔹 Детектор {}:  {}", detector, oid);
+    }
+
+    let conditions = [
+        "(or 1-3) and (or 4-6)",
+        "and 7-8",
+    ];
+    for (i, source) in conditions.iter().enumerate() {
+        let expr = parse_ddr_expression(source)?;
+        let oid = simulator.add_condition(&base.child(10 + i as u32), &scn, expr)?;
+        println!("🔹 Условие '{}': {}", source, oid);
+    }
+
+    let socket = UdpSocket::bind(&addr)?;
+    println!("\n🔹 Симулятор слушает {} (community 'private')", socket.local_addr()?);
+    simulator.serve(&socket)?;
+    Ok(())
+}
@@ -0,0 +1,363 @@
+//! ddr-lsp — языковой сервер (LSP) для файлов-библиотек DDR-условий
+//!
+//! Работает через stdin/stdout и умеет:
+//! - показывать ошибки разбора с подчёркиванием проблемного фрагмента;
+//! - по наведению показывать условие, развёрнутое в `ddr(Dn)`;
+//! - форматировать файл в едином виде (как `traffic-tools cond fmt`);
+//! - дополнять операторы и имена `$ИМЯ`, определённые в файле.
+//!
+//! Подключение в редакторе — как у любого LSP-сервера, команда `ddr-lsp`
+//! для файлов `*.ddr`.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::process::ExitCode;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, Formatting, HoverRequest, Request as _};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, Diagnostic, DiagnosticSeverity,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentFormattingParams,
+    Hover, HoverContents, HoverParams, HoverProviderCapability, MarkupContent, MarkupKind, OneOf, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextEdit, Uri,
+};
+
+use traffic_core::conditions::{
+    parse_ddr_expression_with_symbols, to_ddr_string, FormatOptions, Library, LibraryError, SymbolTable,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/// Операторы для дополнения: текст и пояснение
+const KEYWORDS: [(&str, &str); 10] = [
+    ("and", "оба условия"),
+    ("or", "хотя бы одно условие"),
+    ("not", "отрицание"),
+    ("of", "счёт: 2of 1-4 — не меньше двух детекторов из диапазона"),
+    ("delay", "delay(условие, с) — условие держится не меньше заданного времени"),
+    ("hold", "hold(условие, с) — условие продлевается на заданное время"),
+    ("rise", "rise(условие) — условие только что стало истинным"),
+    ("fall", "fall(условие) — условие только что стало ложным"),
+    ("occ", "occ(Dn) — занятость детектора, %"),
+    ("cnt", "cnt(Dn) — число машин"),
+];
+
+fn main() -> ExitCode {
+    let (connection, io_threads) = Connection::stdio();
+    let result = run(&connection);
+    drop(connection);
+
+    match result.and_then(|()| io_threads.join().map_err(Into::into)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ddr-lsp: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(connection: &Connection) -> Result<()> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["$".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    // Ключ — текст URI: `Uri` кэширует разбор внутри и ключом быть не может
+    let mut documents: HashMap<String, String> = HashMap::new();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = handle_request(&documents, request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(uri) = handle_notification(&mut documents, notification)? {
+                    let params = PublishDiagnosticsParams {
+                        diagnostics: documents.get(uri.as_str()).map(|text| diagnostics(text)).unwrap_or_default(),
+                        uri,
+                        version: None,
+                    };
+                    let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+                    connection.sender.send(Message::Notification(notification))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Обновляет открытые документы; возвращает документ, для которого
+/// нужно заново отправить ошибки
+fn handle_notification(documents: &mut HashMap<String, String>, notification: Notification) -> Result<Option<Uri>> {
+    let uri = match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params: DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+            documents.insert(params.text_document.uri.as_str().to_string(), params.text_document.text);
+            params.text_document.uri
+        }
+        DidChangeTextDocument::METHOD => {
+            let params: DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+            // Синхронизация полным текстом: важно только последнее изменение
+            if let Some(change) = params.content_changes.into_iter().last() {
+                documents.insert(params.text_document.uri.as_str().to_string(), change.text);
+            }
+            params.text_document.uri
+        }
+        DidCloseTextDocument::METHOD => {
+            let params: DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+            documents.remove(params.text_document.uri.as_str());
+            params.text_document.uri
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(uri))
+}
+
+fn handle_request(documents: &HashMap<String, String>, request: Request) -> Response {
+    let id = request.id.clone();
+    let text = |uri: &Uri| documents.get(uri.as_str()).map(String::as_str).unwrap_or_default();
+
+    let result = match request.method.as_str() {
+        HoverRequest::METHOD => serde_json::from_value::<HoverParams>(request.params).map(|params| {
+            let position = params.text_document_position_params;
+            serde_json::to_value(hover(text(&position.text_document.uri), position.position))
+        }),
+        Completion::METHOD => serde_json::from_value::<CompletionParams>(request.params).map(|params| {
+            let position = params.text_document_position;
+            serde_json::to_value(completion(text(&position.text_document.uri), position.position))
+        }),
+        Formatting::METHOD => serde_json::from_value::<DocumentFormattingParams>(request.params)
+            .map(|params| serde_json::to_value(formatting(text(&params.text_document.uri)))),
+        method => {
+            return Response::new_err(id, ErrorCode::MethodNotFound as i32, format!("неизвестный метод {}", method));
+        }
+    };
+
+    match result.and_then(|value| value) {
+        Ok(value) => Response::new_ok(id, value),
+        Err(e) => Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+/// Позиция LSP (символы UTF-16) → смещение в байтах внутри строки
+fn byte_offset(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (offset, c) in line.char_indices() {
+        if units >= character as usize {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+/// Смещение в байтах внутри строки → позиция LSP (символы UTF-16)
+fn character(line: &str, offset: usize) -> u32 {
+    line.get(..offset).unwrap_or(line).encode_utf16().count() as u32
+}
+
+/// Строка библиотеки: имя, смещение условия в строке и его текст
+struct Definition<'a> {
+    name: &'a str,
+    offset: usize,
+    source: &'a str,
+}
+
+/// Разбивает строку так же, как `Library::parse`; `None` — пустая строка,
+/// комментарий или строка без `:`/`=`
+fn definition(line: &str) -> Option<Definition<'_>> {
+    let content = line.split('#').next().unwrap_or_default();
+    let separator = content.find([':', '='])?;
+    Some(Definition {
+        name: content[..separator].trim(),
+        offset: separator + 1,
+        source: &content[separator + 1..],
+    })
+}
+
+/// Все имена документа — даже если в нём есть ошибки
+fn symbols(text: &str) -> SymbolTable {
+    let mut symbols = SymbolTable::new();
+    for definition in text.lines().filter_map(definition) {
+        // Неверные имена покажет диагностика
+        let _ = symbols.define(definition.name, definition.source);
+    }
+    symbols
+}
+
+fn diagnostics(text: &str) -> Vec<Diagnostic> {
+    let Err(errors) = Library::parse("", text) else {
+        return Vec::new();
+    };
+
+    errors
+        .iter()
+        .filter_map(|error| {
+            let location = error.location()?;
+            let line = text.lines().nth(location.line - 1).unwrap_or_default();
+            let start = line.char_indices().nth(location.column - 1).map_or(line.len(), |(offset, _)| offset);
+
+            let (span, message) = match error {
+                LibraryError::Syntax { error, .. } => (
+                    error.span().unwrap_or(start..line.split('#').next().unwrap_or_default().trim_end().len()),
+                    error.to_string(),
+                ),
+                LibraryError::Duplicate { name, first_line, .. } => (
+                    start..start + name.len(),
+                    format!("имя '{}' уже определено в строке {}", name, first_line),
+                ),
+                LibraryError::Io { .. } => return None,
+            };
+
+            let row = (location.line - 1) as u32;
+            let range = Range::new(
+                Position::new(row, character(line, span.start)),
+                Position::new(row, character(line, span.end.max(span.start + 1))),
+            );
+            Some(Diagnostic {
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("ddr".to_string()),
+                ..Diagnostic::new_simple(range, message)
+            })
+        })
+        .collect()
+}
+
+/// Над `$ИМЯ` — определение имени, в остальной части строки — её условие;
+/// оба развёрнуты в `ddr(Dn)`
+fn hover(text: &str, position: Position) -> Option<Hover> {
+    let line = text.lines().nth(position.line as usize)?;
+    let offset = byte_offset(line, position.character);
+    let symbols = symbols(text);
+
+    let is_name = |c: char| c.is_alphanumeric() || c == '_';
+    let start = line[..offset]
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !is_name(c))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let end = line[offset..].find(|c: char| !is_name(c)).map_or(line.len(), |i| offset + i);
+
+    let value = if line[..start].ends_with('$') {
+        let name = &line[start..end];
+        let expr = symbols.resolve(name).ok()?;
+        format!("**${}** = `{}`\n\n```\n{}\n```", name, symbols.get(name)?, to_ddr_string(&expr))
+    } else {
+        let definition = definition(line)?;
+        if offset > definition.offset + definition.source.len() {
+            return None;
+        }
+        let expr = parse_ddr_expression_with_symbols(definition.source, &symbols).ok()?;
+        format!("**{}**\n\n```\n{}\n```", definition.name, to_ddr_string(&expr))
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+        range: None,
+    })
+}
+
+/// После `$` — имена документа, иначе — операторы и имена
+fn completion(text: &str, position: Position) -> Vec<CompletionItem> {
+    let line = text.lines().nth(position.line as usize).unwrap_or_default();
+    let before = &line[..byte_offset(line, position.character)];
+    let word = before.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_');
+    let after_dollar = word.ends_with('$');
+
+    let symbols = symbols(text);
+    let names = symbols.iter().map(|(name, source)| CompletionItem {
+        label: if after_dollar { name.to_string() } else { format!("${}", name) },
+        kind: Some(CompletionItemKind::VARIABLE),
+        detail: Some(source.to_string()),
+        ..CompletionItem::default()
+    });
+    if after_dollar {
+        return names.collect();
+    }
+
+    KEYWORDS
+        .iter()
+        .map(|(keyword, detail)| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            detail: Some(detail.to_string()),
+            ..CompletionItem::default()
+        })
+        .chain(names)
+        .collect()
+}
+
+/// Весь документ одной правкой; `None`, если в нём есть ошибки
+fn formatting(text: &str) -> Option<Vec<TextEdit>> {
+    let formatted = Library::format("", text, &FormatOptions::default()).ok()?;
+    if formatted == text {
+        return Some(Vec::new());
+    }
+    let last = text.split('\n').next_back().unwrap_or_default();
+    let end = Position::new(text.split('\n').count() as u32 - 1, character(last, last.len()));
+    Some(vec![TextEdit::new(Range::new(Position::new(0, 0), end), formatted)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "NB = 1-2\nфаза1: $NB and not 9 # вызов\nphase2: 1 xor 2\n";
+
+    #[test]
+    fn test_diagnostics() {
+        let diagnostics = diagnostics(TEXT);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range, Range::new(Position::new(2, 10), Position::new(2, 13)));
+        assert!(diagnostics[0].message.contains("xor"));
+
+        // Позиции — в символах UTF-16, а не в байтах
+        let diagnostics = super::diagnostics("фаза1: 1 xor 2");
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 9));
+    }
+
+    #[test]
+    fn test_hover_and_completion() {
+        let markup = |hover: Option<Hover>| match hover.unwrap().contents {
+            HoverContents::Markup(content) => content.value,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            markup(hover(TEXT, Position::new(1, 9))),
+            "**$NB** = `1-2`\n\n```\nddr(D1) or ddr(D2)\n```"
+        );
+        assert!(markup(hover(TEXT, Position::new(1, 17))).contains("(ddr(D1) or ddr(D2)) and (not ddr(D9))"));
+        assert!(hover(TEXT, Position::new(1, 24)).is_none());
+        // Разделитель перед словом — многобайтовый символ
+        assert!(markup(hover("phase1: 1–3", Position::new(0, 11))).contains("ddr(D1) or ddr(D2) or ddr(D3)"));
+        assert!(hover("NB = 1-2\nphase2: «$NB»", Position::new(1, 9)).is_none());
+
+        let labels = |items: Vec<CompletionItem>| items.into_iter().map(|item| item.label).collect::<Vec<_>>();
+        assert_eq!(labels(completion(TEXT, Position::new(1, 8))), ["NB", "phase2", "фаза1"]);
+        assert!(labels(completion(TEXT, Position::new(1, 12))).contains(&"delay".to_string()));
+    }
+
+    #[test]
+    fn test_formatting() {
+        assert_eq!(formatting(TEXT), None);
+        let edits = formatting("NB=1 - 2\nphase1: $NB&!9").unwrap();
+        assert_eq!(edits[0].range.end, Position::new(1, 14));
+        assert_eq!(edits[0].new_text, "NB = 1-2\nphase1: $NB and not 9");
+        assert_eq!(formatting("NB = 1-2\n"), Some(Vec::new()));
+    }
+}
@@ -0,0 +1,553 @@
+//! traffic-tools — командная строка для инструментов traffic_core
+//!
+//! Примеры:
+//!   traffic-tools cond expand "(or 1-3) and (or 4-6)"
+//!   traffic-tools cond eval --active 1,5 "(or 1-3) and (or 4-6)"
+//!   traffic-tools cond library --dialect plain junction.ddr
+//!   traffic-tools cond xref --detectors 1-16 --format md junction.ddr
+//!   traffic-tools cond fmt --check junction.ddr
+//!   traffic-tools cond highlight --expand "(or 1-3) and not 9"
+//!   echo "CO4554" | traffic-tools --json scn encode
+//!
+//! Если аргументы не заданы, входные данные читаются из stdin
+//! построчно. Код выхода: 0 — всё успешно, 1 — хотя бы одна строка
+//! с ошибкой, 2 — неверные аргументы командной строки.
+
+use std::collections::BTreeSet;
+use std::io::{self, BufRead};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{json, Map, Value};
+
+use traffic_core::conditions::{
+    highlight_ansi, highlight_html, parse_ddr_expression, to_ddr_string,
+    to_ddr_string_with_options, try_evaluate, CrossReference, Dialect, FormatOptions,
+    GenerateOptions, KeywordStyle, Library, LibraryError, Measure, Measurements, Notation,
+    ParenStyle, MAX_RANGE_SIZE,
+};
+use traffic_core::converters::{find_scn, gen_scn_from_chars, parse_components};
+
+#[derive(Parser)]
+#[command(name = "traffic-tools", version, about = "Инструменты для светофорных объектов")]
+struct Cli {
+    /// Машиночитаемый вывод: один JSON-объект на каждую входную строку
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Работа с DDR-условиями
+    #[command(subcommand)]
+    Cond(CondCommand),
+
+    /// Кодирование SCN в OID-индекс и обратно
+    #[command(subcommand)]
+    Scn(ScnCommand),
+}
+
+#[derive(Subcommand)]
+enum CondCommand {
+    /// Развернуть условие в формат DDR: "1-3" → "ddr(D1) or ddr(D2) or ddr(D3)"
+    Expand {
+        /// Использовать символы &/| вместо and/or
+        #[arg(long)]
+        symbols: bool,
+
+        /// Префикс перед номером детектора
+        #[arg(long)]
+        prefix: Option<String>,
+
+        /// Суффикс после номера детектора
+        #[arg(long)]
+        suffix: Option<String>,
+
+        /// Условие (слова склеиваются через пробел); без него — stdin
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        expr: Vec<String>,
+    },
+
+    /// Проверить синтаксис условия
+    Check {
+        /// Условие; без него — stdin
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        expr: Vec<String>,
+    },
+
+    /// Вычислить условие при заданных занятых детекторах
+    Eval {
+        /// Занятые детекторы через запятую: --active 1,4,5
+        #[arg(long, short, value_delimiter = ',')]
+        active: Vec<u32>,
+
+        /// Измерение для сравнений: --measure occ:3=75 (можно повторять)
+        #[arg(long, short, value_parser = parse_measure)]
+        measure: Vec<(Measure, u32, f64)>,
+
+        /// Условие; без него — stdin
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        expr: Vec<String>,
+    },
+
+    /// Подсветить условие цветами терминала или в HTML
+    Highlight {
+        /// HTML с CSS-классами ddr-* вместо цветов терминала
+        #[arg(long)]
+        html: bool,
+
+        /// Подсветить развёрнутую запись ddr(Dn) вместо исходной
+        #[arg(long)]
+        expand: bool,
+
+        /// Условие; без него — stdin
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        expr: Vec<String>,
+    },
+
+    /// Развернуть все условия файла-библиотеки (строки `имя: условие`)
+    Library {
+        /// Диалект вывода: ddr, ddr-symbols, plain, channel
+        #[arg(long, default_value = "ddr")]
+        dialect: String,
+
+        /// Файл библиотеки
+        file: String,
+    },
+
+    /// Привести файл-библиотеку к единому виду
+    Fmt {
+        /// Операторы символами &, |, ! вместо and, or, not
+        #[arg(long)]
+        symbols: bool,
+
+        /// Скобки вокруг каждого операнда and/or
+        #[arg(long)]
+        explicit: bool,
+
+        /// Упорядочить операнды and/or по номерам детекторов
+        #[arg(long)]
+        sort: bool,
+
+        /// Только проверить: код выхода 1, если файл не в едином виде
+        #[arg(long)]
+        check: bool,
+
+        /// Записать результат в файл вместо вывода
+        #[arg(long, conflicts_with = "check")]
+        write: bool,
+
+        /// Файл библиотеки
+        file: String,
+    },
+
+    /// Перекрёстные ссылки детекторов по файлу-библиотеке
+    Xref {
+        /// Настроенные детекторы: --detectors 1-16,20
+        #[arg(long, value_delimiter = ',')]
+        detectors: Vec<String>,
+
+        /// Формат отчёта (с --json всегда JSON)
+        #[arg(long, value_enum, default_value_t = XrefFormat::Csv)]
+        format: XrefFormat,
+
+        /// Файл библиотеки
+        file: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum XrefFormat {
+    Csv,
+    Md,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum ScnCommand {
+    /// SCN → OID-индекс: "CO4554" → ".1.6.67.79.52.53.53.52"
+    Encode {
+        /// SCN; без них — stdin
+        scn: Vec<String>,
+    },
+
+    /// OID (или его часть с SCN) → SCN
+    Decode {
+        /// OID; без них — stdin
+        oid: Vec<String>,
+    },
+}
+
+/// Результат обработки одной входной строки
+type Outcome = Result<Map<String, Value>, String>;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Cond(command) => run_cond(command, cli.json),
+        Command::Scn(command) => run_scn(command, cli.json),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("traffic-tools: ошибка чтения stdin: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_cond(command: CondCommand, json: bool) -> io::Result<bool> {
+    match command {
+        CondCommand::Expand { symbols, prefix, suffix, expr } => {
+            let defaults = GenerateOptions::default();
+            let options = GenerateOptions {
+                prefix: prefix.unwrap_or(defaults.prefix),
+                suffix: suffix.unwrap_or(defaults.suffix),
+                use_symbols: symbols,
+                ..defaults
+            };
+            process(joined(expr)?, json, |input| {
+                let expr = parse_ddr_expression(input).map_err(|e| e.to_string())?;
+                Ok(output(to_ddr_string_with_options(&expr, &options)))
+            })
+        }
+        CondCommand::Check { expr } => process(joined(expr)?, json, |input| {
+            parse_ddr_expression(input).map_err(|e| e.to_string())?;
+            Ok(output("ok".to_string()))
+        }),
+        CondCommand::Eval { active, measure, expr } => {
+            let inputs = Measurements {
+                active: active.into_iter().collect(),
+                values: measure.into_iter().map(|(measure, detector, value)| ((measure, detector), value)).collect(),
+            };
+            process(joined(expr)?, json, |input| {
+                let expr = parse_ddr_expression(input).map_err(|e| e.to_string())?;
+                let mut fields = Map::new();
+                let result = try_evaluate(&expr, &inputs).map_err(|e| e.to_string())?;
+                fields.insert("result".to_string(), Value::Bool(result));
+                Ok(fields)
+            })
+        }
+        CondCommand::Highlight { html, expand, expr } => process(joined(expr)?, json, |input| {
+            let (text, notation) = if expand {
+                let expr = parse_ddr_expression(input).map_err(|e| e.to_string())?;
+                (to_ddr_string(&expr), Notation::Expanded)
+            } else {
+                (input.to_string(), Notation::Shorthand)
+            };
+            let highlighted = if html { highlight_html(&text, notation) } else { highlight_ansi(&text, notation) };
+            Ok(output(highlighted))
+        }),
+        CondCommand::Library { dialect, file } => Ok(run_library(&dialect, &file, json)),
+        CondCommand::Fmt { symbols, explicit, sort, check, write, file } => {
+            let options = FormatOptions {
+                keywords: if symbols { KeywordStyle::Symbols } else { KeywordStyle::Words },
+                parens: if explicit { ParenStyle::Explicit } else { ParenStyle::Minimal },
+                sort_operands: sort,
+                ..FormatOptions::default()
+            };
+            Ok(run_fmt(&options, check, write, &file, json))
+        }
+        CondCommand::Xref { detectors, format, file } => {
+            let format = if json { XrefFormat::Json } else { format };
+            Ok(run_xref(&detectors, format, &file))
+        }
+    }
+}
+
+/// Отчёт по детекторам; код выхода 1, если есть ссылки на ненастроенные детекторы
+fn run_xref(detectors: &[String], format: XrefFormat, file: &str) -> bool {
+    let configured = match parse_detector_list(detectors) {
+        Ok(configured) => configured,
+        Err(message) => {
+            eprintln!("traffic-tools: {}", message);
+            return false;
+        }
+    };
+    let library = match Library::load(file) {
+        Ok(library) => library,
+        Err(errors) => {
+            print_library_errors(&errors, matches!(format, XrefFormat::Json));
+            return false;
+        }
+    };
+
+    let xref = CrossReference::from_library(&library, configured);
+    match format {
+        XrefFormat::Csv => print!("{}", xref.to_csv()),
+        XrefFormat::Md => print!("{}", xref.to_markdown()),
+        XrefFormat::Json => println!("{}", xref.to_json()),
+    }
+    xref.unknown().is_empty()
+}
+
+/// "occ:3=75", "cnt(D1)=4" → измерение детектора
+fn parse_measure(text: &str) -> Result<(Measure, u32, f64), String> {
+    Measurements::parse_value(text).ok_or_else(|| format!("ожидалось измерение вида occ:3=75, получено '{}'", text))
+}
+
+/// "1-16", "20" → номера детекторов; пустой список — конфигурация не задана
+fn parse_detector_list(items: &[String]) -> Result<Option<BTreeSet<u32>>, String> {
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    let mut detectors = BTreeSet::new();
+    for item in items {
+        let number = |s: &str| {
+            s.trim()
+                .parse::<u32>()
+                .map_err(|_| format!("неверный номер детектора '{}'", item))
+        };
+        match item.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (number(start)?, number(end)?);
+                if start > end {
+                    return Err(format!("неверный диапазон детекторов '{}': начало больше конца", item));
+                }
+                if u64::from(end) - u64::from(start) >= u64::from(MAX_RANGE_SIZE) {
+                    return Err(format!(
+                        "неверный диапазон детекторов '{}': допустимо не больше {} детекторов",
+                        item, MAX_RANGE_SIZE
+                    ));
+                }
+                detectors.extend(start..=end);
+            }
+            None => {
+                detectors.insert(number(item)?);
+            }
+        }
+    }
+    Ok(Some(detectors))
+}
+
+/// Печатает все условия библиотеки; ошибки — в формате файл:строка:колонка
+fn run_library(dialect: &str, file: &str, json: bool) -> bool {
+    let Some(dialect) = Dialect::by_name(dialect) else {
+        eprintln!("traffic-tools: неизвестный диалект '{}'", dialect);
+        return false;
+    };
+
+    let library = match Library::load(file) {
+        Ok(library) => library,
+        Err(errors) => {
+            print_library_errors(&errors, json);
+            return false;
+        }
+    };
+
+    // Условия, которые контроллер не примет, не выводим вовсе
+    let mut ok = true;
+    for entry in library.entries() {
+        if let Err(errors) = dialect.check(&entry.expr) {
+            for error in errors {
+                eprintln!("{}:{}: {}: {}", library.file(), entry.line, entry.name, error);
+            }
+            ok = false;
+        }
+    }
+    if !ok {
+        return false;
+    }
+
+    if !json {
+        print!("{}", library.generate(&dialect.options));
+        return true;
+    }
+    for entry in library.entries() {
+        let object = json!({
+            "name": entry.name,
+            "line": entry.line,
+            "input": entry.source,
+            "ok": true,
+            "output": to_ddr_string_with_options(&entry.expr, &dialect.options),
+        });
+        println!("{}", object);
+    }
+    true
+}
+
+/// Ошибки библиотеки: в stderr или JSON-объектами в stdout
+fn print_library_errors(errors: &[LibraryError], json: bool) {
+    for error in errors {
+        if json {
+            let mut object = Map::new();
+            object.insert("ok".to_string(), json!(false));
+            if let Some(location) = error.location() {
+                object.insert("file".to_string(), json!(location.file));
+                object.insert("line".to_string(), json!(location.line));
+                object.insert("column".to_string(), json!(location.column));
+            }
+            object.insert("error".to_string(), json!(error.to_string()));
+            println!("{}", Value::Object(object));
+        } else {
+            eprintln!("{}", error);
+        }
+    }
+}
+
+/// Форматирует библиотеку; с `check` код выхода 1, если файл нужно переформатировать
+fn run_fmt(options: &FormatOptions, check: bool, write: bool, file: &str, json: bool) -> bool {
+    let text = match std::fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return false;
+        }
+    };
+    let formatted = match Library::format(file, &text, options) {
+        Ok(formatted) => formatted,
+        Err(errors) => {
+            print_library_errors(&errors, json);
+            return false;
+        }
+    };
+
+    let changed = formatted != text;
+    if check {
+        if json {
+            println!("{}", json!({ "file": file, "ok": !changed }));
+        } else if changed {
+            eprintln!("{}: файл не в едином виде, выполните traffic-tools cond fmt --write", file);
+        }
+        return !changed;
+    }
+    if write {
+        if changed && let Err(e) = std::fs::write(file, &formatted) {
+            eprintln!("{}: {}", file, e);
+            return false;
+        }
+        return true;
+    }
+    if json {
+        println!("{}", json!({ "file": file, "ok": true, "output": formatted }));
+    } else {
+        print!("{}", formatted);
+    }
+    true
+}
+
+fn run_scn(command: ScnCommand, json: bool) -> io::Result<bool> {
+    match command {
+        ScnCommand::Encode { scn } => process(separate(scn)?, json, |input| {
+            if input.trim().is_empty() {
+                return Err("пустой SCN".to_string());
+            }
+            Ok(output(gen_scn_from_chars(input)))
+        }),
+        ScnCommand::Decode { oid } => process(separate(oid)?, json, |input| {
+            let components = parse_components(input).map_err(|e| e.to_string())?;
+            find_scn(&components)
+                .map(|found| output(found.scn))
+                .ok_or_else(|| format!("в '{}' не найден SCN", input))
+        }),
+    }
+}
+
+/// Условие из аргументов склеивается в одну строку, из stdin — по строке на условие
+fn joined(args: Vec<String>) -> io::Result<Vec<String>> {
+    if args.is_empty() {
+        stdin_lines()
+    } else {
+        Ok(vec![args.join(" ")])
+    }
+}
+
+/// Каждый аргумент — отдельный элемент; без аргументов — строки stdin
+fn separate(args: Vec<String>) -> io::Result<Vec<String>> {
+    if args.is_empty() {
+        stdin_lines()
+    } else {
+        Ok(args)
+    }
+}
+
+fn stdin_lines() -> io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            lines.push(line.trim().to_string());
+        }
+    }
+    Ok(lines)
+}
+
+fn output(text: String) -> Map<String, Value> {
+    let mut fields = Map::new();
+    fields.insert("output".to_string(), Value::String(text));
+    fields
+}
+
+/// Обрабатывает входные строки и печатает результат.
+/// Возвращает `true`, если все строки обработаны без ошибок.
+fn process<F>(inputs: Vec<String>, json: bool, handle: F) -> io::Result<bool>
+where
+    F: Fn(&str) -> Outcome,
+{
+    let mut all_ok = true;
+
+    for input in &inputs {
+        let outcome = handle(input);
+        all_ok &= outcome.is_ok();
+
+        if json {
+            let mut object = Map::new();
+            object.insert("input".to_string(), json!(input));
+            object.insert("ok".to_string(), json!(outcome.is_ok()));
+            match outcome {
+                Ok(fields) => object.extend(fields),
+                Err(message) => {
+                    object.insert("error".to_string(), json!(message));
+                }
+            }
+            println!("{}", Value::Object(object));
+            continue;
+        }
+
+        match outcome {
+            Ok(fields) => {
+                let text = fields
+                    .values()
+                    .map(|value| match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                println!("{}", text);
+            }
+            Err(message) => eprintln!("ошибка: {}: {}", input, message),
+        }
+    }
+
+    Ok(all_ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_detector_list() {
+        let items = |items: &[&str]| items.iter().map(|item| item.to_string()).collect::<Vec<_>>();
+        assert_eq!(parse_detector_list(&[]), Ok(None));
+        assert_eq!(parse_detector_list(&items(&["1-3", "7", "5-5"])), Ok(Some(BTreeSet::from([1, 2, 3, 5, 7]))));
+        assert_eq!(
+            parse_detector_list(&items(&["16-1"])),
+            Err("неверный диапазон детекторов '16-1': начало больше конца".to_string())
+        );
+        assert!(parse_detector_list(&items(&["1-x"])).is_err());
+        assert!(parse_detector_list(&items(&["1-1024"])).is_ok());
+        assert_eq!(
+            parse_detector_list(&items(&["1-4000000000"])),
+            Err("неверный диапазон детекторов '1-4000000000': допустимо не больше 1024 детекторов".to_string())
+        );
+    }
+}
@@ -3,15 +3,40 @@
 //! Этот модуль содержит структуры данных, которые представляют
 //! разобранное выражение пользователя.
 
+use std::collections::BTreeSet;
+use std::fmt;
+
 /// Основное выражение.
 ///
 /// Выражением может быть:
 /// - Простой диапазон: "or 1-3", "and 4-6", "1-3" (OR по умолчанию)
 /// - Комбинация выражений: "(or 1-3) and (or 4-6)"
+/// - Отрицание: "not 9", "not (1-3)"
+/// - Ссылка на именованное условие: "$NB"
+/// - Условие во времени: "delay(3, 5)", "rise(7)"
+/// - Сравнение измерения детектора: "occ(D3) > 60", "cnt(D1) >= 10"
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     /// Диапазон DDR номеров (самый простой случай)
     Range(Range),
+
+    /// Отрицание выражения
+    Not(Box<Expr>),
+
+    /// Ссылка на именованное условие; `body` — его разобранный текст
+    Ref {
+        name: String,
+        body: Box<Expr>,
+    },
+
+    /// Сравнение числового измерения детектора с порогом
+    Compare(Comparison),
+
+    /// Временной оператор над выражением: задержка, удержание, фронт
+    Temporal {
+        op: TemporalOp,
+        inner: Box<Expr>,
+    },
     
     /// Бинарная операция: левое выражение, оператор, правое выражение
     Binary {
@@ -21,6 +46,10 @@ pub enum Expr {
     },
 }
 
+/// Наибольшее число детекторов в одном диапазоне: `1-1024` ещё можно,
+/// `1-4000000000` разбор не примет
+pub const MAX_RANGE_SIZE: u32 = 1024;
+
 /// Диапазон DDR номеров.
 ///
 /// Хранит начальный и конечный номер, а также оператор внутри диапазона.
@@ -37,10 +66,14 @@ pub struct Range {
 /// Определяет, как соединяются элементы внутри одного диапазона:
 /// - Or: ddr(D1) or ddr(D2) or ddr(D3)  (по умолчанию)
 /// - And: ddr(D1) and ddr(D2) and ddr(D3)
-#[derive(Debug, Clone, PartialEq)]
+/// - AtLeast(n): занято не меньше n детекторов диапазона, `2of 1-4`
+/// - Exactly(n): занято ровно n детекторов диапазона, `=1of 1-3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RangeOp {
     Or,  // значение по умолчанию, если оператор не указан
     And,
+    AtLeast(u32),
+    Exactly(u32),
 }
 
 /// Оператор между выражениями.
@@ -48,21 +81,252 @@ pub enum RangeOp {
 /// Используется для соединения целых выражений:
 /// - (or 1-3) and (or 4-6) → BinaryOp::And
 /// - (or 1-3) or (and 4-6)  → BinaryOp::Or
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinaryOp {
     And,
     Or,
 }
 
+/// Числовое измерение детектора
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Measure {
+    /// Занятость, %: `occ(D3)`
+    Occupancy,
+    /// Счёт машин: `cnt(D1)`
+    Count,
+}
+
+impl Measure {
+    /// Имя в тексте условия
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Measure::Occupancy => "occ",
+            Measure::Count => "cnt",
+        }
+    }
+
+    /// Измерение по имени "occ"/"cnt" (без учёта регистра)
+    pub fn from_keyword(word: &str) -> Option<Measure> {
+        [Measure::Occupancy, Measure::Count]
+            .into_iter()
+            .find(|measure| measure.keyword().eq_ignore_ascii_case(word))
+    }
+}
+
+/// Оператор сравнения: > >= < <= = !=
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    /// Выполняется ли `left op right`
+    pub fn apply(&self, left: f64, right: f64) -> bool {
+        match self {
+            CompareOp::Gt => left > right,
+            CompareOp::Ge => left >= right,
+            CompareOp::Lt => left < right,
+            CompareOp::Le => left <= right,
+            CompareOp::Eq => left == right,
+            CompareOp::Ne => left != right,
+        }
+    }
+}
+
+/// Сравнение: `occ(D3) > 60`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub measure: Measure,
+    pub detector: u32,
+    pub op: CompareOp,
+    pub value: f64,
+}
+
+/// Временной оператор.
+///
+/// Длительности хранятся в миллисекундах, в тексте записываются в секундах:
+/// - Delay: `delay(3, 5)` — условие выполняется не меньше 5 с подряд
+/// - Hold: `hold(3, 2.5)` — условие выполнялось, и с тех пор прошло меньше 2.5 с
+/// - Rise: `rise(7)` — условие только что стало истинным (один шаг)
+/// - Fall: `fall(7)` — условие только что стало ложным (один шаг)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemporalOp {
+    Delay { ms: u64 },
+    Hold { ms: u64 },
+    Rise,
+    Fall,
+}
+
+impl TemporalOp {
+    /// Имя оператора в тексте условия
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            TemporalOp::Delay { .. } => "delay",
+            TemporalOp::Hold { .. } => "hold",
+            TemporalOp::Rise => "rise",
+            TemporalOp::Fall => "fall",
+        }
+    }
+
+    /// Длительность, мс (для задержки и удержания)
+    pub fn duration_ms(&self) -> Option<u64> {
+        match self {
+            TemporalOp::Delay { ms } | TemporalOp::Hold { ms } => Some(*ms),
+            TemporalOp::Rise | TemporalOp::Fall => None,
+        }
+    }
+}
+
+/// Миллисекунды в секундах без лишних нулей: 5000 → "5", 2500 → "2.5"
+pub(crate) fn format_seconds(ms: u64) -> String {
+    if ms.is_multiple_of(1000) {
+        return (ms / 1000).to_string();
+    }
+    format!("{}.{:03}", ms / 1000, ms % 1000).trim_end_matches('0').to_string()
+}
+
 /// Конструкторы для удобства создания структур.
 impl Range {
     /// Создаёт новый диапазон.
     ///
     /// # Пример
     /// ```
+    /// use traffic_core::conditions::{Range, RangeOp};
+    ///
     /// let range = Range::new(1, 3, RangeOp::Or);
     /// ```
     pub fn new(start: u32, end: u32, operator: RangeOp) -> Self {
         Self { start, end, operator }
     }
-}
\ No newline at end of file
+
+    /// Число детекторов в диапазоне
+    pub fn detector_count(&self) -> u32 {
+        if self.start <= self.end { self.end - self.start + 1 } else { 0 }
+    }
+
+    /// Выполняется ли диапазон, если занято `active` его детекторов
+    pub fn accepts(&self, active: u32) -> bool {
+        match self.operator {
+            RangeOp::Or => active > 0,
+            RangeOp::And => active == self.detector_count(),
+            RangeOp::AtLeast(n) => active >= n,
+            RangeOp::Exactly(n) => active == n,
+        }
+    }
+}
+
+impl Expr {
+    /// Номера всех детекторов, которые встречаются в выражении
+    pub fn detectors(&self) -> BTreeSet<u32> {
+        let mut detectors = BTreeSet::new();
+        self.collect_detectors(&mut detectors);
+        detectors
+    }
+
+    /// Есть ли в выражении временные операторы (в том числе внутри ссылок)
+    pub fn is_temporal(&self) -> bool {
+        match self {
+            Expr::Range(_) | Expr::Compare(_) => false,
+            Expr::Not(inner) => inner.is_temporal(),
+            Expr::Ref { body, .. } => body.is_temporal(),
+            Expr::Temporal { .. } => true,
+            Expr::Binary { left, right, .. } => left.is_temporal() || right.is_temporal(),
+        }
+    }
+
+    fn collect_detectors(&self, detectors: &mut BTreeSet<u32>) {
+        match self {
+            Expr::Range(range) => detectors.extend(range.start..=range.end),
+            Expr::Not(inner) => inner.collect_detectors(detectors),
+            Expr::Ref { body, .. } => body.collect_detectors(detectors),
+            Expr::Compare(comparison) => {
+                detectors.insert(comparison.detector);
+            }
+            Expr::Temporal { inner, .. } => inner.collect_detectors(detectors),
+            Expr::Binary { left, right, .. } => {
+                left.collect_detectors(detectors);
+                right.collect_detectors(detectors);
+            }
+        }
+    }
+}
+
+/// Печать в краткой записи: "(or 1-3) and (and 4-6)".
+///
+/// Результат снова разбирается `parse_ddr_expression` в то же выражение
+/// (для ссылок `$ИМЯ` нужна та же таблица имён).
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Range(range) => write!(f, "{}", range),
+            Expr::Not(inner) => match inner.as_ref() {
+                Expr::Range(range) if range.start == range.end && range.operator == RangeOp::Or => {
+                    write!(f, "not {}", inner)
+                }
+                Expr::Ref { .. } | Expr::Not(_) | Expr::Temporal { .. } => write!(f, "not {}", inner),
+                _ => write!(f, "not ({})", inner),
+            },
+            Expr::Ref { name, .. } => write!(f, "${}", name),
+            Expr::Compare(comparison) => write!(f, "{}", comparison),
+            Expr::Temporal { op, inner } => match op.duration_ms() {
+                Some(ms) => write!(f, "{}({}, {})", op.keyword(), inner, format_seconds(ms)),
+                None => write!(f, "{}({})", op.keyword(), inner),
+            },
+            Expr::Binary { op, left, right } => write!(f, "({}) {} ({})", left, op, right),
+        }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end && matches!(self.operator, RangeOp::Or | RangeOp::And) {
+            return write!(f, "{}", self.start);
+        }
+        write!(f, "{} {}-{}", self.operator, self.start, self.end)
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(D{}) {} {}", self.measure.keyword(), self.detector, self.op, self.value)
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl fmt::Display for RangeOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeOp::Or => write!(f, "or"),
+            RangeOp::And => write!(f, "and"),
+            RangeOp::AtLeast(n) => write!(f, "{}of", n),
+            RangeOp::Exactly(n) => write!(f, "={}of", n),
+        }
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryOp::And => write!(f, "and"),
+            BinaryOp::Or => write!(f, "or"),
+        }
+    }
+}
@@ -0,0 +1,362 @@
+//! Конкретное синтаксическое дерево (CST)
+//!
+//! В отличие от `Expr`, дерево хранит всё, что было в тексте: пробелы,
+//! комментарии `# ...`, написание операторов (`and` или `&`), скобки.
+//! Из него текст печатается обратно байт в байт или в едином виде
+//! (`reformat`). Дерево строит тот же разбор, что и `parse_ddr_expression`,
+//! поэтому `to_expr` берёт выражение из того же прохода, а не разбирает
+//! текст заново.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::{SyntaxTree, NodeKind};
+//!
+//! let source = "(1-3)  &  not 9   # вызов с левого ряда";
+//! let tree = SyntaxTree::parse(source);
+//! assert_eq!(tree.to_string(), source);
+//! assert_eq!(tree.root().nodes().next().unwrap().kind, NodeKind::Binary);
+//! assert_eq!(tree.reformat(), "(1-3) & not 9 # вызов с левого ряда");
+//! assert_eq!(tree.to_expr().unwrap().to_string(), "(or 1-3) and (not 9)");
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::conditions::ast::Expr;
+use crate::conditions::error::ParseError;
+use crate::conditions::keywords::{Keywords, DEFAULT_KEYWORDS};
+use crate::conditions::lexer::{Token, TokenKind};
+use crate::conditions::parser::{Context, Event, Syntax};
+use crate::conditions::symbols::SymbolTable;
+
+/// Вид узла дерева
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Корень: выражение и пробелы/комментарии после него
+    Root,
+    /// `левое and правое`
+    Binary,
+    /// `not атом`, `!атом`
+    Not,
+    /// `( выражение )`
+    Paren,
+    /// `or 1-3`, `2of 1-4`, `9`
+    Range,
+    /// `$ИМЯ`
+    Reference,
+    /// `delay(выражение, 5)`, `rise(выражение)`
+    Temporal,
+    /// `occ(D3) > 60`
+    Comparison,
+    /// Текст, который не удалось разобрать
+    Error,
+}
+
+/// Элемент дерева: узел или токен
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    Node(Node),
+    Token(Token),
+}
+
+/// Узел дерева; пробелы и комментарии лежат в нём как токены
+/// перед следующими за ними значимыми токенами
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub children: Vec<Element>,
+}
+
+impl Node {
+    /// Байтовый диапазон узла в исходном тексте
+    pub fn span(&self) -> Range<usize> {
+        let tokens = self.tokens();
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => first.span.start..last.span.end,
+            _ => 0..0,
+        }
+    }
+
+    /// Все токены узла по порядку, включая вложенные
+    pub fn tokens(&self) -> Vec<&Token> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    /// Дочерние узлы
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.children.iter().filter_map(|element| match element {
+            Element::Node(node) => Some(node),
+            Element::Token(_) => None,
+        })
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a Token>) {
+        for element in &self.children {
+            match element {
+                Element::Node(node) => node.collect_tokens(tokens),
+                Element::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// Значимые токены вместе с видом узла, которому они принадлежат
+    fn collect_with_parent<'a>(&'a self, out: &mut Vec<(&'a Token, NodeKind)>) {
+        for element in &self.children {
+            match element {
+                Element::Node(node) => node.collect_with_parent(out),
+                Element::Token(token) => out.push((token, self.kind)),
+            }
+        }
+    }
+
+    fn contains_error(&self) -> bool {
+        self.kind == NodeKind::Error || self.nodes().any(Node::contains_error)
+    }
+}
+
+/// Дерево разбора условия вместе с исходным текстом
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxTree {
+    source: String,
+    root: Node,
+    syntax: Syntax,
+    keywords: Keywords,
+}
+
+impl SyntaxTree {
+    /// Строит дерево; разбор не падает: то, что не удалось разобрать,
+    /// попадает в узел `Error`
+    pub fn parse(source: &str) -> Self {
+        Self::parse_with_keywords(source, &DEFAULT_KEYWORDS)
+    }
+
+    /// Строит дерево с заданным написанием ключевых слов
+    pub fn parse_with_keywords(source: &str, keywords: &Keywords) -> Self {
+        let syntax = Syntax::parse(source, keywords);
+        let root = match syntax.result {
+            Ok(_) => build(&syntax.events, &syntax.tokens),
+            // Дерево для ошибочного текста не строим: весь текст — один узел Error
+            Err(_) => {
+                let children = syntax.tokens.iter().cloned().map(Element::Token).collect();
+                let error = Node { kind: NodeKind::Error, children };
+                Node { kind: NodeKind::Root, children: vec![Element::Node(error)] }
+            }
+        };
+
+        Self { source: source.to_string(), root, syntax, keywords: keywords.clone() }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// Есть ли в тексте синтаксические ошибки
+    pub fn has_errors(&self) -> bool {
+        self.root.contains_error()
+    }
+
+    /// Ошибка разбора; неизвестные имена `$ИМЯ` сюда не входят — они
+    /// проверяются в `to_expr_with_symbols`
+    pub fn error(&self) -> Option<&ParseError> {
+        self.syntax.result.as_ref().err()
+    }
+
+    /// Выражение без ссылок на именованные условия
+    pub fn to_expr(&self) -> Result<Expr, ParseError> {
+        self.syntax.resolve(&Context::new(None).with_keywords(&self.keywords))
+    }
+
+    /// Выражение со ссылками `$ИМЯ` из таблицы имён
+    pub fn to_expr_with_symbols(&self, symbols: &SymbolTable) -> Result<Expr, ParseError> {
+        self.syntax.resolve(&Context::new(Some(symbols)).with_keywords(&self.keywords))
+    }
+
+    /// Текст в едином виде: по одному пробелу между токенами, без пробелов
+    /// внутри скобок, диапазонов и вызовов; написание операторов и
+    /// комментарии сохраняются. Ошибочный текст возвращается как есть.
+    pub fn reformat(&self) -> String {
+        if self.has_errors() {
+            return self.source.clone();
+        }
+
+        let mut tokens = Vec::new();
+        self.root.collect_with_parent(&mut tokens);
+
+        let mut out = String::new();
+        let mut previous: Option<(&Token, NodeKind)> = None;
+        for (token, parent) in tokens {
+            match token.kind {
+                TokenKind::Whitespace => continue,
+                TokenKind::Comment => {
+                    if !out.is_empty() && !out.ends_with('\n') {
+                        out.push(' ');
+                    }
+                    out.push_str(token.text(&self.source));
+                    out.push('\n');
+                    previous = None;
+                    continue;
+                }
+                _ => {}
+            }
+
+            if let Some((before, before_parent)) = previous
+                && spaced(&self.source, (before, before_parent), (token, parent))
+            {
+                out.push(' ');
+            }
+            out.push_str(token.text(&self.source));
+            previous = Some((token, parent));
+        }
+
+        // Комментарий в конце не требует перевода строки
+        if out.ends_with('\n') {
+            out.pop();
+        }
+        out
+    }
+}
+
+/// Исходный текст, собранный из токенов дерева
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.root.tokens() {
+            write!(f, "{}", token.text(&self.source))?;
+        }
+        Ok(())
+    }
+}
+
+/// Нужен ли пробел между соседними значимыми токенами
+pub(crate) fn spaced(source: &str, (left, left_parent): (&Token, NodeKind), (right, right_parent): (&Token, NodeKind)) -> bool {
+    let (left_text, right_text) = (left.word(source), right.word(source));
+    let counting = left_parent == NodeKind::Range && right_parent == NodeKind::Range;
+
+    !(left.kind == TokenKind::LParen
+        || right.kind == TokenKind::RParen
+        || right_text == ","
+        || left_text == "$"
+        || left_text == "!"
+        || left.kind == TokenKind::Dash
+        || right.kind == TokenKind::Dash
+        || (right.kind == TokenKind::LParen && left.kind == TokenKind::Keyword)
+        || (counting && left_text == "=")
+        || (counting && right_text == "of"))
+}
+
+/// Собирает дерево по шагам разбора; пробелы и комментарии попадают в
+/// узел следующего за ними значимого токена, хвостовые — в корень
+fn build(events: &[Event], tokens: &[Token]) -> Node {
+    let mut tokens = tokens.iter().cloned().peekable();
+    let mut stack = vec![Node { kind: NodeKind::Root, children: Vec::new() }];
+
+    for event in events {
+        match *event {
+            Event::Start(kind) => stack.push(Node { kind, children: Vec::new() }),
+            Event::Token => {
+                let node = stack.last_mut().expect("корень не закрывается");
+                while let Some(trivia) = tokens.next_if(|token| token.kind.is_trivia()) {
+                    node.children.push(Element::Token(trivia));
+                }
+                node.children.extend(tokens.next().map(Element::Token));
+            }
+            Event::Finish => {
+                let node = stack.pop().expect("у Finish есть Start");
+                stack.last_mut().expect("корень не закрывается").children.push(Element::Node(node));
+            }
+        }
+    }
+
+    let mut root = stack.pop().expect("корень не закрывается");
+    root.children.extend(tokens.map(Element::Token));
+    root
+}
+
+/// "D3", "d3"
+pub(crate) fn is_detector_name(name: &str) -> bool {
+    name.strip_prefix(['D', 'd']).is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::{parse_ddr_expression, parse_ddr_expression_with_symbols};
+
+    #[test]
+    fn test_lossless() {
+        for source in [
+            "  or 1-3 and(4 - 6)  ",
+            "# начало\n$NB &\n  not 2of 1-4 # хвост",
+            "delay( occ(D3)>=60 , 2.5 ) | =1of 5-7",
+            "1-3 and (",
+            "",
+        ] {
+            let tree = SyntaxTree::parse(source);
+            assert_eq!(tree.to_string(), source);
+            assert_eq!(tree.root().span().end, source.len());
+        }
+        assert!(SyntaxTree::parse("1-3 and (").has_errors());
+        assert!(SyntaxTree::parse("").has_errors());
+    }
+
+    #[test]
+    fn test_structure() {
+        let tree = SyntaxTree::parse("not (1-2) or $NB");
+        let binary = tree.root().nodes().next().unwrap();
+        assert_eq!(binary.kind, NodeKind::Binary);
+
+        let kinds: Vec<NodeKind> = binary.nodes().map(|node| node.kind).collect();
+        assert_eq!(kinds, [NodeKind::Not, NodeKind::Reference]);
+        assert_eq!(binary.nodes().next().unwrap().span(), 0..9);
+    }
+
+    #[test]
+    fn test_reformat_and_expr() {
+        let tree = SyntaxTree::parse("# вызов\n  ( or 1 - 3 )&!9 or delay( occ( D3 )>=60,2 )  or =2 of 4-6");
+        assert_eq!(
+            tree.reformat(),
+            "# вызов\n(or 1-3) & !9 or delay(occ(D3) >= 60, 2) or =2of 4-6"
+        );
+        assert_eq!(
+            tree.to_expr().unwrap(),
+            parse_ddr_expression(tree.reformat().split_once('\n').unwrap().1).unwrap()
+        );
+
+        // Позиции ошибок — в исходном тексте, с комментариями
+        let tree = SyntaxTree::parse("# c\n1 xor 2");
+        assert_eq!(
+            tree.to_expr(),
+            Err(ParseError::UnknownOperator { word: "xor".to_string(), position: 6 })
+        );
+        assert_eq!(tree.error(), tree.to_expr().err().as_ref());
+    }
+
+    #[test]
+    fn test_keywords_and_symbols() {
+        // Дерево строит тот же разбор, что и parse_ddr_expression: с его
+        // написанием ключевых слов и проверками
+        let strict = Keywords::english();
+        assert!(SyntaxTree::parse_with_keywords("1 AND 2", &strict).has_errors());
+        assert!(!SyntaxTree::parse("1 AND 2").has_errors());
+        assert!(SyntaxTree::parse("5of 1-4").has_errors());
+
+        let russian = SyntaxTree::parse_with_keywords("$NB или не 9", &Keywords::russian());
+        assert!(!russian.has_errors());
+        assert_eq!(russian.error(), None);
+        assert_eq!(
+            russian.to_expr(),
+            Err(ParseError::UnknownSymbol { name: "NB".to_string(), position: 0 })
+        );
+        let mut symbols = SymbolTable::new();
+        symbols.define("NB", "1-2").unwrap();
+        assert_eq!(
+            russian.to_expr_with_symbols(&symbols),
+            parse_ddr_expression_with_symbols("$NB or not 9", &symbols)
+        );
+    }
+}
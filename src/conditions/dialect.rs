@@ -0,0 +1,184 @@
+//! Диалекты генерации
+//!
+//! Разные контроллеры ждут условия в немного разном виде: `ddr(D1)`,
+//! `D1`, `CH1`, слова или символы. Диалект — именованный набор
+//! `GenerateOptions` под конкретный формат и ограничения контроллера.
+
+use crate::conditions::ast::Expr;
+use crate::conditions::error::DialectError;
+use crate::conditions::generator::{to_ddr_string_with_options, CountFormat, GenerateOptions, TemporalFormat};
+use crate::conditions::keywords::Keywords;
+use crate::conditions::metrics::{Limits, Metrics};
+
+/// Именованный формат вывода условий
+#[derive(Debug, Clone)]
+pub struct Dialect {
+    /// Имя для выбора: "ddr", "plain"...
+    pub name: String,
+
+    /// Короткое описание для справки
+    pub description: String,
+
+    /// Опции генерации
+    pub options: GenerateOptions,
+
+    /// Ограничения контроллера на сложность условия
+    pub limits: Limits,
+}
+
+impl Dialect {
+    /// Создаёт диалект с произвольными опциями
+    pub fn new(name: &str, description: &str, options: GenerateOptions) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            options,
+            limits: Limits::none(),
+        }
+    }
+
+    /// Задаёт ограничения контроллера
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Встроенные диалекты
+    pub fn builtin() -> Vec<Dialect> {
+        let words = GenerateOptions::default();
+        let symbols = GenerateOptions {
+            use_symbols: true,
+            ..GenerateOptions::default()
+        };
+
+        vec![
+            Dialect::new("ddr", "ddr(D1) or ddr(D2) — формат по умолчанию", words.clone()),
+            Dialect::new("ddr-symbols", "ddr(D1) | ddr(D2)", symbols),
+            Dialect::new(
+                "ddr-ru",
+                "ddr(D1) или ddr(D2) — русские и/или/не",
+                GenerateOptions {
+                    keywords: Keywords::russian(),
+                    ..words.clone()
+                },
+            ),
+            Dialect::new(
+                "ddr-count",
+                "ddr(D1) or ddr(D2), счёт как count(ddr(D1), ddr(D2)) >= 2",
+                GenerateOptions {
+                    counting: Some(CountFormat::default()),
+                    ..words.clone()
+                },
+            ),
+            Dialect::new(
+                "plain",
+                "D1 or D2, таймеры TON/TOF/R_TRIG/F_TRIG",
+                GenerateOptions {
+                    prefix: "D".to_string(),
+                    suffix: "".to_string(),
+                    temporal: Some(TemporalFormat::iec()),
+                    ..words.clone()
+                },
+            ),
+            Dialect::new(
+                "channel",
+                "CH1 or CH2, без временных операторов, не длиннее 255 символов",
+                GenerateOptions {
+                    prefix: "CH".to_string(),
+                    suffix: "".to_string(),
+                    temporal: None,
+                    ..words
+                },
+            )
+            .with_limits(Limits {
+                max_terms: Some(32),
+                max_depth: Some(8),
+                max_length: Some(255),
+                max_detectors: None,
+            }),
+        ]
+    }
+
+    /// Ищет встроенный диалект по имени (без учёта регистра)
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::conditions::{parse_ddr_expression, to_ddr_string_with_options, Dialect};
+    ///
+    /// let dialect = Dialect::by_name("plain").unwrap();
+    /// let expr = parse_ddr_expression("and 1-2").unwrap();
+    /// assert_eq!(to_ddr_string_with_options(&expr, &dialect.options), "D1 and D2");
+    /// ```
+    pub fn by_name(name: &str) -> Option<Dialect> {
+        Self::builtin()
+            .into_iter()
+            .find(|dialect| dialect.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Можно ли записать выражение в этом диалекте: временные операторы
+    /// есть не во всех форматах
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::conditions::{parse_ddr_expression, Dialect};
+    ///
+    /// let expr = parse_ddr_expression("delay(3, 5)").unwrap();
+    /// assert!(Dialect::by_name("plain").unwrap().supports(&expr));
+    /// assert!(!Dialect::by_name("channel").unwrap().supports(&expr));
+    /// ```
+    pub fn supports(&self, expr: &Expr) -> bool {
+        self.options.temporal.is_some() || !expr.is_temporal()
+    }
+
+    /// Проверяет, что условие можно записать в диалекте: операторы
+    /// поддерживаются, ограничения контроллера соблюдены
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::conditions::{parse_ddr_expression, Dialect, DialectError};
+    ///
+    /// // 2 × 924 группы по 6 детекторов — строку не выводим
+    /// let expr = parse_ddr_expression("6of 1-12 or 6of 13-24").unwrap();
+    /// assert_eq!(
+    ///     Dialect::by_name("channel").unwrap().check(&expr),
+    ///     Err(vec![DialectError::TooManyTerms { terms: 11088, max: 32 }])
+    /// );
+    /// ```
+    pub fn check(&self, expr: &Expr) -> Result<Metrics, Vec<DialectError>> {
+        // Строку выводим, только если слагаемые и глубина в пределах
+        let mut metrics = Metrics::without_length(expr, &self.options);
+        let mut errors = self.limits.check(&metrics);
+        if errors.is_empty() {
+            metrics = Metrics::of(expr, &self.options);
+            errors = self.limits.check(&metrics);
+        }
+        if !self.supports(expr) {
+            errors.insert(0, DialectError::TemporalUnsupported(self.name.clone()));
+        }
+        if errors.is_empty() { Ok(metrics) } else { Err(errors) }
+    }
+
+    /// Записывает условие в диалекте, если оно проходит `check`
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::conditions::{parse_ddr_expression, Dialect};
+    ///
+    /// let channel = Dialect::by_name("channel").unwrap();
+    /// let expr = parse_ddr_expression("and 1-3").unwrap();
+    /// assert_eq!(channel.generate(&expr).unwrap(), "CH1 and CH2 and CH3");
+    ///
+    /// let errors = channel.generate(&parse_ddr_expression("or 1-40").unwrap()).unwrap_err();
+    /// assert_eq!(errors[0].to_string(), "Слишком много слагаемых: 40, допустимо не больше 32");
+    /// ```
+    pub fn generate(&self, expr: &Expr) -> Result<String, Vec<DialectError>> {
+        self.check(expr)?;
+        Ok(to_ddr_string_with_options(expr, &self.options))
+    }
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self::by_name("ddr").expect("встроенный диалект ddr")
+    }
+}
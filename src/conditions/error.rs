@@ -1,43 +1,262 @@
 //! Типы ошибок для парсера DDR-выражений
 
+use crate::conditions::ast::Measure;
+use crate::conditions::keywords::Keywords;
 use thiserror::Error;
 
-/// Ошибки, которые могут возникнуть при парсинге
+/// Ошибки, которые могут возникнуть при парсинге.
+///
+/// Позиции — смещения в байтах от начала разбираемой строки.
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum ParseError {
     /// Ошибка: неожиданный символ
     #[error("Неожиданный символ '{0}' на позиции {1}")]
     UnexpectedChar(char, usize),
-    
+
     /// Ошибка: ожидалось число
-    #[error("Ожидалось число, получено '{0}'")]
-    ExpectedNumber(String),
-    
+    #[error("Ожидалось число на позиции {position}, получено '{found}'")]
+    ExpectedNumber { found: String, position: usize },
+
     /// Ошибка: неизвестный оператор
-    #[error("Неизвестный оператор '{0}'. Используйте and/or или &/|")]
-    UnknownOperator(String),
-    
+    #[error("Неизвестный оператор '{word}' на позиции {position}. Используйте and/or или &/|")]
+    UnknownOperator { word: String, position: usize },
+
     /// Ошибка: незакрытая скобка
-    #[error("Незакрытая скобка")]
-    UnclosedParen,
-    
+    #[error("Незакрытая скобка на позиции {0}")]
+    UnclosedParen(usize),
+
     /// Ошибка: лишние символы после выражения
-    #[error("Лишние символы после выражения: '{0}'")]
-    ExtraInput(String),
-    
+    #[error("Лишние символы после выражения на позиции {position}: '{rest}'")]
+    ExtraInput { rest: String, position: usize },
+
     /// Ошибка: не хватает операнда
-    #[error("После оператора '{0}' должно быть выражение")]
-    MissingOperand(String),
-    
-    /// Ошибка: внутренняя ошибка парсера
-    #[error("Внутренняя ошибка парсера")]
-    InternalError,
+    #[error("После оператора '{op}' на позиции {position} должно быть выражение")]
+    MissingOperand { op: String, position: usize },
+
+    /// Ошибка: ссылка на неопределённое условие
+    #[error("Неизвестное имя '${name}' на позиции {position}")]
+    UnknownSymbol { name: String, position: usize },
+
+    /// Ошибка: условия ссылаются друг на друга по кругу
+    #[error("Циклическая ссылка: {}", format_chain(.0))]
+    CyclicReference(Vec<String>),
+
+    /// Ошибка в тексте именованного условия; позиции внутри `error` — от начала его текста
+    #[error("В условии ${name}: {error}")]
+    InDefinition { name: String, error: Box<ParseError> },
+
+    /// Ошибка: недопустимое имя условия
+    #[error("Некорректное имя условия '{0}': допустимы буквы, цифры и '_', первой не может быть цифра")]
+    InvalidName(String),
+
+    /// Ошибка: строка не похожа на определение
+    #[error("Ожидалось определение вида ИМЯ = условие, получено '{0}'")]
+    InvalidDefinition(String),
+
+    /// Ошибка: счёт детекторов не имеет смысла для диапазона
+    #[error("Нельзя требовать {count} из {size} детекторов на позиции {position}")]
+    InvalidCount { count: u32, size: u32, position: usize },
+
+    /// Ошибка: начало диапазона больше конца
+    #[error("Диапазон {start}-{end} на позиции {position}: начало больше конца")]
+    ReversedRange { start: u32, end: u32, position: usize },
+
+    /// Ошибка: в диапазоне больше детекторов, чем допустимо
+    #[error("Диапазон {start}-{end} на позиции {position} слишком большой: допустимо не больше {max} детекторов")]
+    RangeTooLarge { start: u32, end: u32, max: u32, position: usize },
+
+    /// Ошибка: числовое измерение там, где нужно условие
+    #[error("{measure} на позиции {position} — число, а не условие: сравните его, например '{measure} > 0'")]
+    MissingComparison { measure: String, position: usize },
+
+    /// Ошибка: с числом сравнивается логическое условие
+    #[error("Сравнение на позиции {position}: с числом сравниваются только occ(Dn) и cnt(Dn), а не состояние детектора или условие")]
+    BooleanComparison { position: usize },
+
+    /// Ошибка: длительность delay/hold нулевая или не помещается в миллисекунды
+    #[error("Длительность {value} с на позиции {position} вне допустимого диапазона: нужно от 0.001 до 1.8e16 с")]
+    InvalidDuration { value: String, position: usize },
 }
 
 impl ParseError {
-    /// Создать ошибку из ошибки nom
-    pub fn from_nom<E>(_err: E, _input: &str) -> Self {
-        // TODO: нормальное преобразование ошибок nom
-        ParseError::InternalError
+    /// Ошибка на месте `position`, где разбор не нашёл ожидаемого:
+    /// числа (`number`) или знака.
+    ///
+    /// По этому месту определяем, что именно пошло не так:
+    /// конец строки после оператора, незакрытая скобка, незнакомое слово...
+    pub(crate) fn expected_at(input: &str, position: usize, number: bool, keywords: &Keywords) -> Self {
+        let consumed = input[..position].trim_end();
+        let rest = input[position..].trim_start();
+        let position = input.len() - rest.len();
+
+        let Some(next) = rest.chars().next() else {
+            if let Some(open) = unclosed_paren(consumed) {
+                return ParseError::UnclosedParen(open);
+            }
+            if let Some(op) = trailing_operator(consumed, keywords) {
+                return ParseError::MissingOperand {
+                    op: op.to_string(),
+                    position: consumed.len() - op.len(),
+                };
+            }
+            return ParseError::ExpectedNumber {
+                found: "конец строки".to_string(),
+                position,
+            };
+        };
+
+        if starts_with_comparison(rest) {
+            return ParseError::BooleanComparison { position };
+        }
+
+        if next.is_alphabetic() {
+            let word: String = rest.chars().take_while(|c| c.is_alphanumeric()).collect();
+            return match keywords.canonical(&word) {
+                Some("and" | "or") => match trailing_operator(consumed, keywords) {
+                    Some(op) => ParseError::MissingOperand {
+                        op: op.to_string(),
+                        position: consumed.len() - op.len(),
+                    },
+                    None => ParseError::ExpectedNumber { found: word, position },
+                },
+                _ => ParseError::UnknownOperator { word, position },
+            };
+        }
+
+        if number {
+            ParseError::ExpectedNumber {
+                found: rest.chars().take_while(|c| !c.is_whitespace()).collect(),
+                position,
+            }
+        } else {
+            ParseError::UnexpectedChar(next, position)
+        }
+    }
+
+    /// Позиция ошибки (смещение в байтах), если она известна
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            ParseError::UnexpectedChar(_, position)
+            | ParseError::ExpectedNumber { position, .. }
+            | ParseError::UnknownOperator { position, .. }
+            | ParseError::UnclosedParen(position)
+            | ParseError::ExtraInput { position, .. }
+            | ParseError::MissingOperand { position, .. }
+            | ParseError::UnknownSymbol { position, .. }
+            | ParseError::InvalidCount { position, .. }
+            | ParseError::ReversedRange { position, .. }
+            | ParseError::RangeTooLarge { position, .. }
+            | ParseError::MissingComparison { position, .. }
+            | ParseError::BooleanComparison { position }
+            | ParseError::InvalidDuration { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// Та же ошибка с позицией, сдвинутой на `offset` байт —
+    /// когда разобранная строка была частью более длинной
+    pub fn shifted(mut self, offset: usize) -> Self {
+        match &mut self {
+            ParseError::UnexpectedChar(_, position)
+            | ParseError::ExpectedNumber { position, .. }
+            | ParseError::UnknownOperator { position, .. }
+            | ParseError::UnclosedParen(position)
+            | ParseError::ExtraInput { position, .. }
+            | ParseError::MissingOperand { position, .. }
+            | ParseError::UnknownSymbol { position, .. }
+            | ParseError::InvalidCount { position, .. }
+            | ParseError::ReversedRange { position, .. }
+            | ParseError::RangeTooLarge { position, .. }
+            | ParseError::MissingComparison { position, .. }
+            | ParseError::BooleanComparison { position }
+            | ParseError::InvalidDuration { position, .. } => *position += offset,
+            _ => {}
+        }
+        self
     }
-}
\ No newline at end of file
+
+    /// Проблемный фрагмент строки (байтовый диапазон) — для подчёркивания
+    pub fn span(&self) -> Option<std::ops::Range<usize>> {
+        let len = match self {
+            ParseError::UnexpectedChar(c, _) => c.len_utf8(),
+            ParseError::UnknownOperator { word, .. } => word.len(),
+            ParseError::ExtraInput { rest, .. } => rest.len(),
+            ParseError::MissingOperand { op, .. } => op.len(),
+            ParseError::UnknownSymbol { name, .. } => name.len() + 1,
+            ParseError::MissingComparison { measure, .. } => measure.len(),
+            ParseError::InvalidDuration { value, .. } => value.len(),
+            _ => 1,
+        };
+        self.position().map(|start| start..start + len.max(1))
+    }
+}
+
+/// Ошибки вычисления условия
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// Ошибка: для сравнения нет числового измерения детектора
+    #[error("Нет измерения {}(D{detector})", .measure.keyword())]
+    MissingMeasurement { measure: Measure, detector: u32 },
+}
+
+/// Условие нельзя записать в диалекте
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DialectError {
+    /// Ошибка: формат не знает временных операторов
+    #[error("Диалект '{0}' не поддерживает временные операторы")]
+    TemporalUnsupported(String),
+
+    /// Ошибка: слишком много слагаемых
+    #[error("Слишком много слагаемых: {terms}, допустимо не больше {max}")]
+    TooManyTerms { terms: usize, max: usize },
+
+    /// Ошибка: слишком глубокая вложенность
+    #[error("Слишком глубокая вложенность: {depth}, допустимо не больше {max}")]
+    TooDeep { depth: usize, max: usize },
+
+    /// Ошибка: слишком длинная строка
+    #[error("Слишком длинное условие: {length} символов, допустимо не больше {max}")]
+    TooLong { length: usize, max: usize },
+
+    /// Ошибка: слишком много детекторов
+    #[error("Слишком много детекторов: {detectors}, допустимо не больше {max}")]
+    TooManyDetectors { detectors: usize, max: usize },
+}
+
+/// "$A → $B → $A"
+fn format_chain(chain: &[String]) -> String {
+    chain
+        .iter()
+        .map(|name| format!("${}", name))
+        .collect::<Vec<_>>()
+        .join(" → ")
+}
+
+/// Начинается ли строка с оператора сравнения
+pub(crate) fn starts_with_comparison(rest: &str) -> bool {
+    rest.starts_with(['<', '>', '=']) || rest.starts_with("!=")
+}
+
+/// Позиция последней незакрытой '('
+fn unclosed_paren(consumed: &str) -> Option<usize> {
+    let mut open = Vec::new();
+    for (i, c) in consumed.char_indices() {
+        match c {
+            '(' => open.push(i),
+            ')' => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    open.pop()
+}
+
+/// Оператор, которым заканчивается разобранная часть строки
+fn trailing_operator<'a>(consumed: &'a str, keywords: &Keywords) -> Option<&'a str> {
+    if consumed.ends_with(['&', '|', '!']) {
+        return Some(&consumed[consumed.len() - 1..]);
+    }
+    let word = &consumed[consumed.trim_end_matches(char::is_alphanumeric).len()..];
+    matches!(keywords.canonical(word), Some("and" | "or" | "not")).then_some(word)
+}
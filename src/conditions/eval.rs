@@ -0,0 +1,282 @@
+//! Вычисление DDR-выражений
+//!
+//! Подставляет в выражение текущие состояния детекторов
+//! и возвращает результат условия.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::conditions::ast::*;
+use crate::conditions::error::EvalError;
+
+/// Источник состояний детекторов.
+///
+/// Реализован для множеств активных детекторов (`HashSet<u32>`,
+/// `BTreeSet<u32>`, срез `[u32]`), для словарей `номер → состояние`
+/// и для `Measurements` с числовыми измерениями.
+pub trait DetectorInputs {
+    /// Занят ли детектор с номером `detector`
+    fn is_active(&self, detector: u32) -> bool;
+
+    /// Числовое измерение детектора, если оно есть
+    fn measurement(&self, _measure: Measure, _detector: u32) -> Option<f64> {
+        None
+    }
+}
+
+/// Состояния и числовые измерения детекторов
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Measurements {
+    pub active: BTreeSet<u32>,
+    pub values: BTreeMap<(Measure, u32), f64>,
+}
+
+impl Measurements {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Отмечает детектор занятым
+    pub fn with_active(mut self, detector: u32) -> Self {
+        self.active.insert(detector);
+        self
+    }
+
+    /// Задаёт измерение детектора
+    pub fn with(mut self, measure: Measure, detector: u32, value: f64) -> Self {
+        self.values.insert((measure, detector), value);
+        self
+    }
+
+    /// Разбирает измерение из записи `occ(D3)=75` или `occ:3=75`
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::conditions::{Measure, Measurements};
+    ///
+    /// assert_eq!(Measurements::parse_value("occ(D3)=75"), Some((Measure::Occupancy, 3, 75.0)));
+    /// assert_eq!(Measurements::parse_value("cnt:1=4"), Some((Measure::Count, 1, 4.0)));
+    /// assert_eq!(Measurements::parse_value("occ(D3)"), None);
+    /// ```
+    pub fn parse_value(text: &str) -> Option<(Measure, u32, f64)> {
+        let (name, value) = text.split_once('=')?;
+        let value = value.trim().parse::<f64>().ok().filter(|value| value.is_finite())?;
+        let (keyword, detector) = match name.split_once(':') {
+            Some(parts) => parts,
+            None => {
+                let (keyword, detector) = name.trim().split_once('(')?;
+                (keyword, detector.strip_suffix(')')?.strip_prefix(['D', 'd'])?)
+            }
+        };
+        let measure = Measure::from_keyword(keyword.trim())?;
+        Some((measure, detector.trim().parse().ok()?, value))
+    }
+}
+
+impl DetectorInputs for Measurements {
+    fn is_active(&self, detector: u32) -> bool {
+        self.active.contains(&detector)
+    }
+
+    fn measurement(&self, measure: Measure, detector: u32) -> Option<f64> {
+        self.values.get(&(measure, detector)).copied()
+    }
+}
+
+impl DetectorInputs for HashSet<u32> {
+    fn is_active(&self, detector: u32) -> bool {
+        self.contains(&detector)
+    }
+}
+
+impl DetectorInputs for BTreeSet<u32> {
+    fn is_active(&self, detector: u32) -> bool {
+        self.contains(&detector)
+    }
+}
+
+impl DetectorInputs for [u32] {
+    fn is_active(&self, detector: u32) -> bool {
+        self.contains(&detector)
+    }
+}
+
+impl DetectorInputs for HashMap<u32, bool> {
+    fn is_active(&self, detector: u32) -> bool {
+        self.get(&detector).copied().unwrap_or(false)
+    }
+}
+
+impl DetectorInputs for BTreeMap<u32, bool> {
+    fn is_active(&self, detector: u32) -> bool {
+        self.get(&detector).copied().unwrap_or(false)
+    }
+}
+
+/// Вычисляет выражение для заданных состояний детекторов.
+///
+/// Истории здесь нет: `delay` и `hold` считаются равными своему условию,
+/// фронты `rise`/`fall` — ложными. Для вычисления во времени есть
+/// `TemporalEvaluator`. Сравнение без измерения ложно; чтобы получить
+/// ошибку, есть `try_evaluate`.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conditions::{evaluate, parse_ddr_expression};
+///
+/// let expr = parse_ddr_expression("(or 1-3) and (or 4-6)").unwrap();
+/// assert!(evaluate(&expr, &[2, 5][..]));
+/// assert!(!evaluate(&expr, &[2, 3][..]));
+/// ```
+pub fn evaluate<I>(expr: &Expr, inputs: &I) -> bool
+where
+    I: DetectorInputs + ?Sized,
+{
+    match expr {
+        Expr::Range(range) => evaluate_range(range, inputs),
+        Expr::Not(inner) => !evaluate(inner, inputs),
+        Expr::Ref { body, .. } => evaluate(body, inputs),
+        Expr::Compare(comparison) => evaluate_comparison(comparison, inputs).unwrap_or(false),
+        Expr::Temporal { op, inner } => match op {
+            TemporalOp::Delay { .. } | TemporalOp::Hold { .. } => evaluate(inner, inputs),
+            TemporalOp::Rise | TemporalOp::Fall => false,
+        },
+        Expr::Binary { op, left, right } => match op {
+            BinaryOp::And => evaluate(left, inputs) && evaluate(right, inputs),
+            BinaryOp::Or => evaluate(left, inputs) || evaluate(right, inputs),
+        },
+    }
+}
+
+/// Вычисляет выражение, требуя измерения для всех сравнений.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conditions::{parse_ddr_expression, try_evaluate, EvalError, Measure, Measurements};
+///
+/// let expr = parse_ddr_expression("occ(D3) >= 60 or cnt(D1) > 10").unwrap();
+/// let inputs = Measurements::new().with(Measure::Occupancy, 3, 75.0);
+/// assert_eq!(
+///     try_evaluate(&expr, &inputs),
+///     Err(EvalError::MissingMeasurement { measure: Measure::Count, detector: 1 })
+/// );
+/// assert_eq!(try_evaluate(&expr, &inputs.with(Measure::Count, 1, 4.0)), Ok(true));
+/// ```
+pub fn try_evaluate<I>(expr: &Expr, inputs: &I) -> Result<bool, EvalError>
+where
+    I: DetectorInputs + ?Sized,
+{
+    // Проверяем все измерения заранее: `evaluate` сокращает вычисление
+    // и до части сравнений может не дойти
+    check_measurements(expr, inputs)?;
+    Ok(evaluate(expr, inputs))
+}
+
+fn check_measurements<I>(expr: &Expr, inputs: &I) -> Result<(), EvalError>
+where
+    I: DetectorInputs + ?Sized,
+{
+    match expr {
+        Expr::Range(_) => Ok(()),
+        Expr::Compare(comparison) => evaluate_comparison(comparison, inputs).map(|_| ()),
+        Expr::Not(inner) | Expr::Temporal { inner, .. } | Expr::Ref { body: inner, .. } => {
+            check_measurements(inner, inputs)
+        }
+        Expr::Binary { left, right, .. } => {
+            check_measurements(left, inputs)?;
+            check_measurements(right, inputs)
+        }
+    }
+}
+
+/// Сравнение измерения с порогом; ошибка, если измерения нет
+pub(crate) fn evaluate_comparison<I>(comparison: &Comparison, inputs: &I) -> Result<bool, EvalError>
+where
+    I: DetectorInputs + ?Sized,
+{
+    let Comparison { measure, detector, op, value } = *comparison;
+    inputs
+        .measurement(measure, detector)
+        .map(|actual| op.apply(actual, value))
+        .ok_or(EvalError::MissingMeasurement { measure, detector })
+}
+
+/// Вычисление диапазона: все (And), хотя бы один (Or) или сколько-то
+/// детекторов (AtLeast, Exactly)
+pub(crate) fn evaluate_range<I>(range: &Range, inputs: &I) -> bool
+where
+    I: DetectorInputs + ?Sized,
+{
+    let mut detectors = range.start..=range.end;
+    match range.operator {
+        RangeOp::Or => detectors.any(|d| inputs.is_active(d)),
+        RangeOp::And => detectors.all(|d| inputs.is_active(d)),
+        RangeOp::AtLeast(_) | RangeOp::Exactly(_) => {
+            range.accepts(detectors.filter(|&d| inputs.is_active(d)).count() as u32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_range() {
+        let or = Expr::Range(Range::new(1, 3, RangeOp::Or));
+        let and = Expr::Range(Range::new(1, 3, RangeOp::And));
+
+        assert!(evaluate(&or, &[2][..]));
+        assert!(!evaluate(&or, &[4][..]));
+        assert!(!evaluate(&and, &[1, 2][..]));
+        assert!(evaluate(&and, &[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_evaluate_counting() {
+        let at_least = Expr::Range(Range::new(1, 4, RangeOp::AtLeast(2)));
+        let exactly = Expr::Range(Range::new(1, 4, RangeOp::Exactly(1)));
+
+        assert!(!evaluate(&at_least, &[1, 5][..]));
+        assert!(evaluate(&at_least, &[1, 3, 4][..]));
+        assert!(evaluate(&exactly, &[2][..]));
+        assert!(!evaluate(&exactly, &[2, 3][..]));
+        assert!(!evaluate(&exactly, &[][..]));
+    }
+
+    #[test]
+    fn test_evaluate_comparison() {
+        let expr = Expr::Compare(Comparison {
+            measure: Measure::Occupancy,
+            detector: 3,
+            op: CompareOp::Ge,
+            value: 60.0,
+        });
+        let inputs = Measurements::new().with_active(1).with(Measure::Occupancy, 3, 60.0);
+        assert!(evaluate(&expr, &inputs));
+        assert!(!evaluate(&expr, &Measurements::new().with(Measure::Occupancy, 3, 59.5)));
+
+        // Без измерения: evaluate — ложь, try_evaluate — ошибка
+        assert!(!evaluate(&expr, &[3][..]));
+        assert_eq!(
+            try_evaluate(&expr, &[3][..]).unwrap_err().to_string(),
+            "Нет измерения occ(D3)"
+        );
+    }
+
+    #[test]
+    fn test_evaluate_binary() {
+        let expr = Expr::Binary {
+            op: BinaryOp::Or,
+            left: Box::new(Expr::Range(Range::new(1, 2, RangeOp::And))),
+            right: Box::new(Expr::Range(Range::new(5, 6, RangeOp::Or))),
+        };
+
+        let mut states = BTreeMap::new();
+        states.insert(1, true);
+        states.insert(2, false);
+        assert!(!evaluate(&expr, &states));
+
+        states.insert(6, true);
+        assert!(evaluate(&expr, &states));
+    }
+}
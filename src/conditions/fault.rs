@@ -0,0 +1,340 @@
+//! Неисправные детекторы: трёхзначная логика и политика неисправности
+//!
+//! Контроллер не знает состояния неисправного детектора. Обычно такой
+//! детектор считается постоянно занятым (фаза вызывается каждый цикл) или
+//! игнорируется; можно и оставить его состояние неизвестным и посмотреть,
+//! зависит ли от него результат.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use crate::conditions::ast::*;
+
+/// Значение в трёхзначной логике Клини
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tristate {
+    False,
+    True,
+    /// Значение неизвестно (зависит от неисправного детектора)
+    Unknown,
+}
+
+impl Tristate {
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::False, _) | (_, Self::False) => Self::False,
+            (Self::True, Self::True) => Self::True,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::True, _) | (_, Self::True) => Self::True,
+            (Self::False, Self::False) => Self::False,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Известное значение или `default`
+    pub fn unwrap_or(self, default: bool) -> bool {
+        match self {
+            Self::False => false,
+            Self::True => true,
+            Self::Unknown => default,
+        }
+    }
+}
+
+impl std::ops::Not for Tristate {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Self::False => Self::True,
+            Self::True => Self::False,
+            Self::Unknown => Self::Unknown,
+        }
+    }
+}
+
+impl From<bool> for Tristate {
+    fn from(value: bool) -> Self {
+        if value { Self::True } else { Self::False }
+    }
+}
+
+impl fmt::Display for Tristate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::False => write!(f, "ложь"),
+            Self::True => write!(f, "истина"),
+            Self::Unknown => write!(f, "неизвестно"),
+        }
+    }
+}
+
+/// Состояние детектора с учётом исправности
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DetectorState {
+    On,
+    #[default]
+    Off,
+    Faulty,
+}
+
+/// Источник состояний детекторов с признаком неисправности.
+///
+/// Реализован для словарей `номер → состояние`; отсутствующий детектор
+/// считается свободным.
+pub trait DetectorStates {
+    fn state(&self, detector: u32) -> DetectorState;
+
+    /// Числовое измерение исправного детектора, если оно есть
+    fn measurement(&self, _measure: Measure, _detector: u32) -> Option<f64> {
+        None
+    }
+}
+
+impl DetectorStates for BTreeMap<u32, DetectorState> {
+    fn state(&self, detector: u32) -> DetectorState {
+        self.get(&detector).copied().unwrap_or_default()
+    }
+}
+
+impl DetectorStates for HashMap<u32, DetectorState> {
+    fn state(&self, detector: u32) -> DetectorState {
+        self.get(&detector).copied().unwrap_or_default()
+    }
+}
+
+/// Как понимать неисправный детектор
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FaultPolicy {
+    /// Постоянно занят — вызов и продление есть всегда
+    #[default]
+    Demand,
+    /// Постоянно свободен — детектор не участвует в условиях
+    Ignore,
+    /// Состояние неизвестно — результат может стать `Unknown`
+    Unknown,
+}
+
+/// Политика неисправности по детекторам
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultPolicies {
+    /// Политика для детекторов, у которых нет своей
+    pub default: FaultPolicy,
+    pub detectors: BTreeMap<u32, FaultPolicy>,
+}
+
+impl FaultPolicies {
+    pub fn new(default: FaultPolicy) -> Self {
+        Self { default, detectors: BTreeMap::new() }
+    }
+
+    /// Задаёт политику детектора
+    pub fn with(mut self, detector: u32, policy: FaultPolicy) -> Self {
+        self.detectors.insert(detector, policy);
+        self
+    }
+
+    /// Политика детектора
+    pub fn policy(&self, detector: u32) -> FaultPolicy {
+        self.detectors.get(&detector).copied().unwrap_or(self.default)
+    }
+}
+
+/// Результат вычисления с неисправными детекторами
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultEvaluation {
+    /// Значение по политикам неисправности
+    pub value: Tristate,
+
+    /// Результат зависит от неисправного детектора: при неизвестном
+    /// состоянии неисправных детекторов значение не определяется
+    pub depends_on_fault: bool,
+
+    /// Неисправные детекторы, входящие в условие
+    pub faulty: BTreeSet<u32>,
+}
+
+/// Вычисляет выражение с учётом неисправных детекторов.
+///
+/// Значение `value` получается подстановкой политик; `depends_on_fault`
+/// показывает, определяется ли результат, если состояние всех
+/// неисправных детекторов неизвестно. Проверка консервативна: для
+/// выражений вида `3 or not 3` зависимость сообщается и там, где её нет.
+///
+/// # Пример
+/// ```
+/// use std::collections::BTreeMap;
+/// use traffic_core::conditions::{
+///     evaluate_with_faults, parse_ddr_expression, DetectorState, FaultPolicies, FaultPolicy, Tristate,
+/// };
+///
+/// let expr = parse_ddr_expression("(or 1-2) and 3").unwrap();
+/// let states = BTreeMap::from([(1, DetectorState::Faulty), (3, DetectorState::On)]);
+///
+/// let result = evaluate_with_faults(&expr, &states, &FaultPolicies::new(FaultPolicy::Demand));
+/// assert_eq!(result.value, Tristate::True);
+/// assert!(result.depends_on_fault);
+///
+/// let result = evaluate_with_faults(&expr, &states, &FaultPolicies::new(FaultPolicy::Unknown));
+/// assert_eq!(result.value, Tristate::Unknown);
+/// ```
+pub fn evaluate_with_faults<S>(expr: &Expr, states: &S, policies: &FaultPolicies) -> FaultEvaluation
+where
+    S: DetectorStates + ?Sized,
+{
+    let by_policy = |detector| match policies.policy(detector) {
+        FaultPolicy::Demand => Tristate::True,
+        FaultPolicy::Ignore => Tristate::False,
+        FaultPolicy::Unknown => Tristate::Unknown,
+    };
+    // Сравнение с неисправным детектором: по политике, как его состояние;
+    // сравнение без измерения ложно, как в `evaluate`
+    let compare = |faulty: &dyn Fn(u32) -> Tristate, comparison: &Comparison| {
+        match states.state(comparison.detector) {
+            DetectorState::Faulty => faulty(comparison.detector),
+            _ => states
+                .measurement(comparison.measure, comparison.detector)
+                .is_some_and(|actual| comparison.op.apply(actual, comparison.value))
+                .into(),
+        }
+    };
+    let input = |faulty: &dyn Fn(u32) -> Tristate, detector| match states.state(detector) {
+        DetectorState::On => Tristate::True,
+        DetectorState::Off => Tristate::False,
+        DetectorState::Faulty => faulty(detector),
+    };
+
+    let value = evaluate_tristate(
+        expr,
+        &|detector| input(&by_policy, detector),
+        &|comparison| compare(&by_policy, comparison),
+    );
+    let unknown = evaluate_tristate(
+        expr,
+        &|detector| input(&|_| Tristate::Unknown, detector),
+        &|comparison| compare(&|_| Tristate::Unknown, comparison),
+    );
+
+    FaultEvaluation {
+        value,
+        depends_on_fault: unknown == Tristate::Unknown,
+        faulty: expr
+            .detectors()
+            .into_iter()
+            .filter(|&detector| states.state(detector) == DetectorState::Faulty)
+            .collect(),
+    }
+}
+
+/// Вычисление по Клини со значениями детекторов из `input`;
+/// `compare` даёт значение сравнений
+fn evaluate_tristate(
+    expr: &Expr,
+    input: &dyn Fn(u32) -> Tristate,
+    compare: &dyn Fn(&Comparison) -> Tristate,
+) -> Tristate {
+    match expr {
+        Expr::Range(range) => {
+            let values: Vec<Tristate> = (range.start..=range.end).map(input).collect();
+            let known = values.iter().filter(|&&v| v == Tristate::True).count() as u32;
+            let unknown = values.iter().filter(|&&v| v == Tristate::Unknown).count() as u32;
+
+            // Результат известен, если он одинаков при любом числе занятых среди неизвестных
+            let results: Vec<bool> = (known..=known + unknown).map(|active| range.accepts(active)).collect();
+            if results.iter().all(|&r| r) {
+                Tristate::True
+            } else if results.iter().all(|&r| !r) {
+                Tristate::False
+            } else {
+                Tristate::Unknown
+            }
+        }
+        Expr::Compare(comparison) => compare(comparison),
+        Expr::Not(inner) => !evaluate_tristate(inner, input, compare),
+        Expr::Ref { body, .. } => evaluate_tristate(body, input, compare),
+        // Без истории — как в `evaluate`
+        Expr::Temporal { op, inner } => match op {
+            TemporalOp::Delay { .. } | TemporalOp::Hold { .. } => evaluate_tristate(inner, input, compare),
+            TemporalOp::Rise | TemporalOp::Fall => Tristate::False,
+        },
+        Expr::Binary { op, left, right } => {
+            let (left, right) = (
+                evaluate_tristate(left, input, compare),
+                evaluate_tristate(right, input, compare),
+            );
+            match op {
+                BinaryOp::And => left.and(right),
+                BinaryOp::Or => left.or(right),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::parse_ddr_expression;
+
+    #[test]
+    fn test_kleene() {
+        use Tristate::*;
+        assert_eq!(Unknown.and(False), False);
+        assert_eq!(Unknown.and(True), Unknown);
+        assert_eq!(Unknown.or(True), True);
+        assert_eq!(!Unknown, Unknown);
+        assert!(Unknown.unwrap_or(true));
+    }
+
+    #[test]
+    fn test_policies() {
+        let expr = parse_ddr_expression("(or 1-2) and not 3").unwrap();
+        let states = BTreeMap::from([(1, DetectorState::Faulty), (3, DetectorState::Faulty)]);
+        let value = |policies: &FaultPolicies| evaluate_with_faults(&expr, &states, policies).value;
+
+        assert_eq!(value(&FaultPolicies::new(FaultPolicy::Demand)), Tristate::False);
+        assert_eq!(value(&FaultPolicies::new(FaultPolicy::Ignore)), Tristate::False);
+        assert_eq!(
+            value(&FaultPolicies::new(FaultPolicy::Demand).with(3, FaultPolicy::Ignore)),
+            Tristate::True
+        );
+        assert_eq!(
+            value(&FaultPolicies::new(FaultPolicy::Unknown).with(3, FaultPolicy::Ignore)),
+            Tristate::Unknown
+        );
+    }
+
+    #[test]
+    fn test_depends_on_fault() {
+        let states = BTreeMap::from([
+            (1, DetectorState::On),
+            (2, DetectorState::Faulty),
+            (4, DetectorState::Faulty),
+        ]);
+        let policies = FaultPolicies::default();
+
+        // D1 занят — неисправный D2 на результат не влияет
+        let result = evaluate_with_faults(&parse_ddr_expression("or 1-2").unwrap(), &states, &policies);
+        assert_eq!(result.value, Tristate::True);
+        assert!(!result.depends_on_fault);
+        assert_eq!(result.faulty, BTreeSet::from([2]));
+
+        let result = evaluate_with_faults(&parse_ddr_expression("and 1-2").unwrap(), &states, &policies);
+        assert!(result.depends_on_fault);
+
+        let result = evaluate_with_faults(&parse_ddr_expression("3").unwrap(), &states, &policies);
+        assert_eq!(result.value, Tristate::False);
+        assert!(result.faulty.is_empty());
+
+        // Из 1-4 один занят и два неисправны: «не меньше 1» известно, «ровно 1» — нет
+        let result = evaluate_with_faults(&parse_ddr_expression("1of 1-4").unwrap(), &states, &policies);
+        assert!(!result.depends_on_fault);
+        let result = evaluate_with_faults(&parse_ddr_expression("=1of 1-4").unwrap(), &states, &policies);
+        assert_eq!(result.value, Tristate::False);
+        assert!(result.depends_on_fault);
+    }
+}
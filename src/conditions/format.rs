@@ -0,0 +1,513 @@
+//! Форматирование текста условий
+//!
+//! Приводит условие к единому виду: пробелы, написание операторов
+//! (`and` или `&`), скобки, перенос длинных строк и, по желанию, порядок
+//! операндов `and`/`or`. Комментарии `# ...` сохраняются. В отличие от
+//! `SyntaxTree::reformat`, форматирование перестраивает скобки и цепочки.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::{format_condition, FormatOptions, KeywordStyle};
+//!
+//! let options = FormatOptions::default();
+//! assert_eq!(format_condition("( or 1 - 3 )&!9", &options).unwrap(), "(or 1-3) and not 9");
+//!
+//! let options = FormatOptions { keywords: KeywordStyle::Symbols, sort_operands: true, ..options };
+//! assert_eq!(format_condition("$NB or 7 or (5 and 2)", &options).unwrap(), "(2 & 5) | 7 | $NB");
+//! ```
+
+use crate::conditions::ast::BinaryOp;
+use crate::conditions::cst::{spaced, Element, Node, NodeKind, SyntaxTree};
+use crate::conditions::error::ParseError;
+use crate::conditions::lexer::{Token, TokenKind};
+
+/// Отступ вложенных строк при переносе
+const INDENT: usize = 4;
+
+/// Написание операторов
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeywordStyle {
+    /// Как в исходном тексте
+    Preserve,
+    /// `and`, `or`, `not`
+    #[default]
+    Words,
+    /// `&`, `|`, `!`
+    Symbols,
+}
+
+/// Расстановка скобок
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParenStyle {
+    /// Только вокруг вложенных цепочек другого оператора и диапазонов
+    /// с оператором: `(or 1-3) and (4 or 5) and not 9`
+    #[default]
+    Minimal,
+    /// Вокруг каждого операнда `and`/`or`, как в `Expr::to_string`
+    Explicit,
+}
+
+/// Настройки форматирования
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub keywords: KeywordStyle,
+    pub parens: ParenStyle,
+    /// Ширина строки в символах; `None` — не переносить
+    pub width: Option<usize>,
+    /// Упорядочить операнды `and`/`or` по первому номеру детектора
+    pub sort_operands: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            keywords: KeywordStyle::Words,
+            parens: ParenStyle::Minimal,
+            width: Some(80),
+            sort_operands: false,
+        }
+    }
+}
+
+/// Форматирует текст условия. Смысл условия не меняется: перестановка
+/// операндов `and`/`or` и снятие лишних скобок на результат не влияют.
+pub fn format_condition(source: &str, options: &FormatOptions) -> Result<String, ParseError> {
+    let tree = SyntaxTree::parse(source);
+    if let Some(error) = tree.error() {
+        return Err(error.clone());
+    }
+    let expression = tree.root().nodes().next().expect("в дереве без ошибок есть выражение");
+    let mut term = build(expression, source, options.keywords);
+
+    // Комментарий достаётся самому внешнему операнду, который кончается
+    // перед ним; комментарии до условия остаются в начале
+    let mut header = Vec::new();
+    for comment in tree.root().tokens().into_iter().filter(|token| token.kind == TokenKind::Comment) {
+        let text = comment.text(source).trim_end().to_string();
+        let mut ends = Vec::new();
+        term.ends(&mut ends);
+        let target = ends
+            .iter()
+            .enumerate()
+            .filter(|(_, end)| **end <= comment.span.start)
+            .fold(None, |best: Option<(usize, usize)>, (index, &end)| match best {
+                Some((_, best_end)) if best_end >= end => best,
+                _ => Some((index, end)),
+            });
+        match target {
+            Some((index, _)) => {
+                term.attach(index, &mut 0, text);
+            }
+            None => header.push(text),
+        }
+    }
+
+    let printer = Printer { options };
+    if options.sort_operands {
+        printer.sort(&mut term);
+    }
+
+    let mut out: String = header.into_iter().map(|comment| comment + "\n").collect();
+    out.push_str(&printer.render(&term, 0, 0));
+    Ok(out)
+}
+
+/// Условие, подготовленное к печати: скобки сняты, цепочки одного
+/// оператора собраны в одну
+struct Term {
+    kind: TermKind,
+    /// Конец операнда в исходном тексте — для привязки комментариев
+    end: usize,
+    comments: Vec<String>,
+}
+
+enum TermKind {
+    /// Диапазон, сравнение или ссылка — печатается одной строкой
+    Leaf {
+        text: String,
+        /// Один детектор или ссылка: скобки не нужны нигде
+        simple: bool,
+        /// Начинается с оператора диапазона: `or 1-3`, `2of 1-4`
+        operator: bool,
+        detector: Option<u32>,
+    },
+    Not { keyword: String, inner: Box<Term> },
+    Temporal { keyword: String, inner: Box<Term>, seconds: Option<String> },
+    Chain { op: BinaryOp, keyword: String, operands: Vec<Term> },
+}
+
+impl Term {
+    fn children(&self) -> Vec<&Term> {
+        match &self.kind {
+            TermKind::Leaf { .. } => Vec::new(),
+            TermKind::Not { inner, .. } | TermKind::Temporal { inner, .. } => vec![inner],
+            TermKind::Chain { operands, .. } => operands.iter().collect(),
+        }
+    }
+
+    /// Концы всех операндов в прямом порядке обхода
+    fn ends(&self, out: &mut Vec<usize>) {
+        out.push(self.end);
+        for child in self.children() {
+            child.ends(out);
+        }
+    }
+
+    /// Добавляет комментарий операнду с номером `target` в прямом порядке обхода
+    fn attach(&mut self, target: usize, index: &mut usize, comment: String) -> bool {
+        if *index == target {
+            self.comments.push(comment);
+            return true;
+        }
+        *index += 1;
+        let children: Vec<&mut Term> = match &mut self.kind {
+            TermKind::Leaf { .. } => Vec::new(),
+            TermKind::Not { inner, .. } | TermKind::Temporal { inner, .. } => vec![inner],
+            TermKind::Chain { operands, .. } => operands.iter_mut().collect(),
+        };
+        children.into_iter().any(|child| child.attach(target, index, comment.clone()))
+    }
+
+    /// Есть ли комментарии у операнда или внутри него
+    fn has_comments(&self) -> bool {
+        !self.comments.is_empty() || self.inner_comments()
+    }
+
+    fn inner_comments(&self) -> bool {
+        self.children().into_iter().any(Term::has_comments)
+    }
+
+    /// Наименьший номер детектора — ключ сортировки
+    fn detector(&self) -> Option<u32> {
+        match &self.kind {
+            TermKind::Leaf { detector, .. } => *detector,
+            _ => self.children().into_iter().filter_map(Term::detector).min(),
+        }
+    }
+}
+
+/// Значимые токены узла (без вложенных узлов)
+fn own_tokens(node: &Node) -> impl Iterator<Item = &Token> {
+    node.children.iter().filter_map(|element| match element {
+        Element::Token(token) if !token.kind.is_trivia() => Some(token),
+        _ => None,
+    })
+}
+
+/// Оператор в выбранной записи; кроме `Preserve` русские слова, регистр и
+/// тире приводятся к каноническому виду (`ИЛИ` → `or`, `–` → `-`)
+fn keyword(token: &Token, source: &str, style: KeywordStyle) -> String {
+    let canonical = match token.kind {
+        TokenKind::Dash => "-",
+        _ => token.word(source),
+    };
+    let (word, symbol) = match canonical {
+        "and" | "&" => ("and", "&"),
+        "or" | "|" => ("or", "|"),
+        "not" | "!" => ("not", "!"),
+        other => (other, other),
+    };
+    match style {
+        KeywordStyle::Preserve => token.text(source).to_string(),
+        KeywordStyle::Words => word.to_string(),
+        KeywordStyle::Symbols => symbol.to_string(),
+    }
+}
+
+fn build(node: &Node, source: &str, style: KeywordStyle) -> Term {
+    let end = node.span().end;
+    let mut nodes = node.nodes();
+    let mut child = || build(nodes.next().expect("узел разобран без ошибок"), source, style);
+    let first = own_tokens(node).next();
+    let first_keyword = || first.map_or(String::new(), |token| keyword(token, source, style));
+
+    let kind = match node.kind {
+        NodeKind::Paren => {
+            let inner = child();
+            return Term { end, ..inner };
+        }
+        NodeKind::Binary => {
+            let and = first.is_some_and(|token| matches!(token.word(source), "and" | "&"));
+            let op = if and { BinaryOp::And } else { BinaryOp::Or };
+            let mut operands = Vec::new();
+            for operand in [child(), child()] {
+                match operand.kind {
+                    TermKind::Chain { op: inner, operands: chain, .. } if inner == op => operands.extend(chain),
+                    kind => operands.push(Term { kind, ..operand }),
+                }
+            }
+            TermKind::Chain { op, keyword: first_keyword(), operands }
+        }
+        NodeKind::Not => TermKind::Not { keyword: first_keyword(), inner: Box::new(child()) },
+        NodeKind::Temporal => TermKind::Temporal {
+            keyword: first_keyword(),
+            inner: Box::new(child()),
+            seconds: own_tokens(node)
+                .find(|token| token.kind == TokenKind::Number)
+                .map(|token| token.text(source).to_string()),
+        },
+        _ => leaf(node, source, style),
+    };
+    Term { kind, end, comments: Vec::new() }
+}
+
+/// Диапазон, сравнение или ссылка: токены через пробелы, как в `reformat`
+fn leaf(node: &Node, source: &str, style: KeywordStyle) -> TermKind {
+    let tokens: Vec<&Token> = own_tokens(node).collect();
+    let mut text = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && spaced(source, (tokens[i - 1], node.kind), (token, node.kind)) {
+            text.push(' ');
+        }
+        text.push_str(&keyword(token, source, style));
+    }
+
+    let detector = match node.kind {
+        // Первое число, за которым не идёт `of`
+        NodeKind::Range => tokens
+            .iter()
+            .enumerate()
+            .find(|(i, token)| {
+                token.kind == TokenKind::Number && tokens.get(i + 1).is_none_or(|next| next.word(source) != "of")
+            })
+            .and_then(|(_, token)| token.text(source).parse().ok()),
+        NodeKind::Comparison => tokens
+            .iter()
+            .find(|token| matches!(token.kind, TokenKind::Number | TokenKind::Ident))
+            .and_then(|token| token.text(source).trim_start_matches(['D', 'd']).parse().ok()),
+        _ => None,
+    };
+
+    TermKind::Leaf {
+        simple: node.kind == NodeKind::Reference || (node.kind == NodeKind::Range && tokens.len() == 1),
+        operator: node.kind == NodeKind::Range && tokens.first().is_some_and(|token| token.kind != TokenKind::Number),
+        detector,
+        text,
+    }
+}
+
+struct Printer<'a> {
+    options: &'a FormatOptions,
+}
+
+impl Printer<'_> {
+    fn fits(&self, column: usize, text: &str) -> bool {
+        self.options.width.is_none_or(|width| column + text.chars().count() <= width)
+    }
+
+    fn sort(&self, term: &mut Term) {
+        match &mut term.kind {
+            TermKind::Leaf { .. } => {}
+            TermKind::Not { inner, .. } | TermKind::Temporal { inner, .. } => self.sort(inner),
+            TermKind::Chain { operands, .. } => {
+                for operand in operands.iter_mut() {
+                    self.sort(operand);
+                }
+                operands.sort_by_cached_key(|operand| (operand.detector().unwrap_or(u32::MAX), self.flat(operand)));
+            }
+        }
+    }
+
+    /// Нужны ли скобки операнду `and`/`or`
+    fn operand_parens(&self, term: &Term) -> bool {
+        match &term.kind {
+            _ if self.options.parens == ParenStyle::Explicit => true,
+            TermKind::Chain { .. } => true,
+            TermKind::Leaf { operator, .. } => *operator,
+            _ => false,
+        }
+    }
+
+    /// Нужны ли скобки после `not`
+    fn not_parens(term: &Term) -> bool {
+        match &term.kind {
+            TermKind::Leaf { simple, .. } => !simple,
+            TermKind::Chain { .. } => true,
+            TermKind::Not { .. } | TermKind::Temporal { .. } => false,
+        }
+    }
+
+    fn not_prefix(keyword: &str) -> String {
+        if keyword == "!" { keyword.to_string() } else { format!("{keyword} ") }
+    }
+
+    /// Операнд одной строкой, без комментариев
+    fn flat(&self, term: &Term) -> String {
+        match &term.kind {
+            TermKind::Leaf { text, .. } => text.clone(),
+            TermKind::Not { keyword, inner } if Self::not_parens(inner) => {
+                format!("{}({})", Self::not_prefix(keyword), self.flat(inner))
+            }
+            TermKind::Not { keyword, inner } => format!("{}{}", Self::not_prefix(keyword), self.flat(inner)),
+            TermKind::Temporal { keyword, inner, seconds: Some(seconds) } => {
+                format!("{keyword}({}, {seconds})", self.flat(inner))
+            }
+            TermKind::Temporal { keyword, inner, seconds: None } => format!("{keyword}({})", self.flat(inner)),
+            TermKind::Chain { keyword, operands, .. } => operands
+                .iter()
+                .map(|operand| {
+                    if self.operand_parens(operand) { format!("({})", self.flat(operand)) } else { self.flat(operand) }
+                })
+                .collect::<Vec<_>>()
+                .join(&format!(" {keyword} ")),
+        }
+    }
+
+    /// Операнд с комментариями; `indent` — отступ строк переноса,
+    /// `column` — где начинается первая строка
+    fn render(&self, term: &Term, indent: usize, column: usize) -> String {
+        let mut out = self.body(term, indent, column);
+        for (i, comment) in term.comments.iter().enumerate() {
+            if i == 0 {
+                out.push(' ');
+            } else {
+                out.push('\n');
+                out.push_str(&" ".repeat(indent));
+            }
+            out.push_str(comment);
+        }
+        out
+    }
+
+    fn body(&self, term: &Term, indent: usize, column: usize) -> String {
+        let flat = self.flat(term);
+        if !term.inner_comments() && self.fits(column, &flat) {
+            return flat;
+        }
+
+        let pad = " ".repeat(indent);
+        match &term.kind {
+            TermKind::Leaf { .. } => flat,
+            TermKind::Not { keyword, inner } => {
+                let prefix = Self::not_prefix(keyword);
+                let column = column + prefix.chars().count();
+                if Self::not_parens(inner) {
+                    prefix + &self.parenthesized(inner, indent, column)
+                } else {
+                    prefix + &self.render(inner, indent, column)
+                }
+            }
+            TermKind::Temporal { keyword, inner, seconds } => {
+                let inner_pad = " ".repeat(indent + INDENT);
+                let inner = self.render(inner, indent + INDENT, indent + INDENT);
+                match seconds {
+                    // После комментария запятая переносится на новую строку
+                    Some(seconds) if inner.lines().last().is_some_and(|line| line.contains('#')) => {
+                        format!("{keyword}(\n{inner_pad}{inner}\n{inner_pad}, {seconds}\n{pad})")
+                    }
+                    Some(seconds) => format!("{keyword}(\n{inner_pad}{inner},\n{inner_pad}{seconds}\n{pad})"),
+                    None => format!("{keyword}(\n{inner_pad}{inner}\n{pad})"),
+                }
+            }
+            TermKind::Chain { keyword, operands, .. } => {
+                let mut out = self.operand(&operands[0], indent, column);
+                for operand in &operands[1..] {
+                    let column = indent + keyword.chars().count() + 1;
+                    out.push_str(&format!("\n{pad}{keyword} {}", self.operand(operand, indent, column)));
+                }
+                out
+            }
+        }
+    }
+
+    fn operand(&self, term: &Term, indent: usize, column: usize) -> String {
+        if self.operand_parens(term) {
+            self.parenthesized(term, indent, column)
+        } else {
+            self.render(term, indent, column)
+        }
+    }
+
+    /// Операнд в скобках; комментарии операнда — после `)`
+    fn parenthesized(&self, term: &Term, indent: usize, column: usize) -> String {
+        let flat = format!("({})", self.flat(term));
+        let mut out = if !term.inner_comments() && self.fits(column, &flat) {
+            flat
+        } else {
+            let inner = self.body(term, indent + INDENT, indent + INDENT);
+            format!("(\n{}{inner}\n{})", " ".repeat(indent + INDENT), " ".repeat(indent))
+        };
+        for (i, comment) in term.comments.iter().enumerate() {
+            out.push_str(if i == 0 { " " } else { "\n" });
+            if i > 0 {
+                out.push_str(&" ".repeat(indent));
+            }
+            out.push_str(comment);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    use crate::conditions::{evaluate, parse_ddr_expression, parse_ddr_expression_with_symbols};
+
+    fn format(source: &str, options: &FormatOptions) -> String {
+        format_condition(source, options).unwrap()
+    }
+
+    #[test]
+    fn test_styles() {
+        let source = "( or 1 - 3 )&!9 or (4|5) or delay( occ( D3 )>=60,2 )";
+        let words = FormatOptions::default();
+        assert_eq!(format(source, &words), "((or 1-3) and not 9) or 4 or 5 or delay(occ(D3) >= 60, 2)");
+
+        let symbols = FormatOptions { keywords: KeywordStyle::Symbols, ..words.clone() };
+        assert_eq!(format(source, &symbols), "((| 1-3) & !9) | 4 | 5 | delay(occ(D3) >= 60, 2)");
+
+        let preserve = FormatOptions { keywords: KeywordStyle::Preserve, ..words.clone() };
+        assert_eq!(format(source, &preserve), "((or 1-3) & !9) or 4 or 5 or delay(occ(D3) >= 60, 2)");
+
+        let explicit = FormatOptions { parens: ParenStyle::Explicit, ..words };
+        let expr = parse_ddr_expression("(or 1-3) and not =2of 4-6").unwrap();
+        assert_eq!(format(&expr.to_string(), &explicit), expr.to_string());
+    }
+
+    #[test]
+    fn test_meaning_preserved() {
+        let options = FormatOptions { width: Some(16), ..FormatOptions::default() };
+        let sorted = FormatOptions { sort_operands: true, ..options.clone() };
+        for source in [
+            "1 and (2 and (3 or 4)) and not (5-6)",
+            "(or 1-3) or ((4 and 5) or 6) and 7",
+            "not not 9 or delay((1 or 2) and 3, 2.5) or rise(occ(D1) > 5)",
+            "2of 1-4 and (=0of 5-6 or $NB)",
+        ] {
+            let parse = |text: &str| parse_ddr_expression_with_symbols(text, &symbols()).unwrap();
+            let expected = parse(source);
+            let formatted = format(source, &options);
+            assert_eq!(format(&formatted, &options), formatted);
+
+            // Цепочки пересобраны, а порядок операндов изменён — значение то же
+            for result in [parse(&formatted), parse(&format(source, &sorted))] {
+                for mask in 0u32..64 {
+                    let active: BTreeSet<u32> = (1..=6).filter(|d| mask & (1 << (d - 1)) != 0).collect();
+                    assert_eq!(evaluate(&result, &active), evaluate(&expected, &active), "{source}");
+                }
+            }
+        }
+    }
+
+    fn symbols() -> crate::conditions::SymbolTable {
+        let mut symbols = crate::conditions::SymbolTable::new();
+        symbols.define("NB", "1-2").unwrap();
+        symbols
+    }
+
+    #[test]
+    fn test_wrapping_and_comments() {
+        let options = FormatOptions { width: Some(20), ..FormatOptions::default() };
+        assert_eq!(
+            format("(or 1-3) and not 9 and (4 or 5 or 6 or 7 or 8)", &options),
+            "(or 1-3)\nand not 9\nand (\n    4\n    or 5\n    or 6\n    or 7\n    or 8\n)"
+        );
+        assert_eq!(
+            format("# вызов\n1 # первый\n  and (2 or 3)   # второй", &FormatOptions::default()),
+            "# вызов\n1 # первый\nand (2 or 3) # второй"
+        );
+        assert!(format_condition("1 and (", &options).is_err());
+    }
+}
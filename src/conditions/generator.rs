@@ -3,6 +3,11 @@
 //! Превращает выражение обратно в формат DDR, который ожидает пользователь.
 
 use crate::conditions::ast::*;
+use crate::conditions::keywords::Keywords;
+
+/// Больше групп счёт не раскрывает: `12of 1-40` дал бы C(40, 12) ≈ 5,6 млрд
+/// групп. Такой счёт записывается шаблоном `CountFormat` по умолчанию
+pub(crate) const MAX_COUNT_GROUPS: usize = 1024;
 
 /// Опции генерации
 #[derive(Debug, Clone)]
@@ -16,8 +21,81 @@ pub struct GenerateOptions {
     /// Разделитель между элементами: по умолчанию " "
     pub separator: String,
     
-    /// Использовать слова (and/or) или символы (&/|)
+    /// Использовать слова (and/or/not) или символы (&/|/!)
     pub use_symbols: bool,
+
+    /// Написание слов: `Keywords::russian()` — и/или/не
+    pub keywords: Keywords,
+
+    /// Раскрывать ссылки `$ИМЯ` в их условия (по умолчанию) или оставлять как есть
+    pub expand_refs: bool,
+
+    /// Запись счёта детекторов (`2of 1-4`); `None` — формат счёта не знает,
+    /// и диапазон раскрывается в равносильную запись через and/or
+    pub counting: Option<CountFormat>,
+
+    /// Запись временных операторов; `None` — формат их не поддерживает
+    /// (выводится запись по умолчанию, см. `Dialect::supports`)
+    pub temporal: Option<TemporalFormat>,
+}
+
+/// Шаблоны счёта: `{n}` — число, `{list}` — детекторы через запятую
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountFormat {
+    pub at_least: String,
+    pub exactly: String,
+}
+
+impl Default for CountFormat {
+    fn default() -> Self {
+        Self {
+            at_least: "count({list}) >= {n}".to_string(),
+            exactly: "count({list}) = {n}".to_string(),
+        }
+    }
+}
+
+/// Шаблоны временных операторов: `{expr}` — условие, `{secs}` — секунды
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemporalFormat {
+    pub delay: String,
+    pub hold: String,
+    pub rise: String,
+    pub fall: String,
+}
+
+impl TemporalFormat {
+    /// Функциональные блоки МЭК 61131-3: TON, TOF, R_TRIG, F_TRIG
+    pub fn iec() -> Self {
+        Self {
+            delay: "TON({expr}, T#{secs}s)".to_string(),
+            hold: "TOF({expr}, T#{secs}s)".to_string(),
+            rise: "R_TRIG({expr})".to_string(),
+            fall: "F_TRIG({expr})".to_string(),
+        }
+    }
+
+    /// Шаблон оператора
+    pub fn template(&self, op: &TemporalOp) -> &str {
+        match op {
+            TemporalOp::Delay { .. } => &self.delay,
+            TemporalOp::Hold { .. } => &self.hold,
+            TemporalOp::Rise => &self.rise,
+            TemporalOp::Fall => &self.fall,
+        }
+    }
+}
+
+/// Запись как во входном языке: `delay({expr}, {secs})`
+impl Default for TemporalFormat {
+    fn default() -> Self {
+        Self {
+            delay: "delay({expr}, {secs})".to_string(),
+            hold: "hold({expr}, {secs})".to_string(),
+            rise: "rise({expr})".to_string(),
+            fall: "fall({expr})".to_string(),
+        }
+    }
 }
 
 impl Default for GenerateOptions {
@@ -27,6 +105,10 @@ impl Default for GenerateOptions {
             suffix: ")".to_string(),
             separator: " ".to_string(),
             use_symbols: false,
+            keywords: Keywords::default(),
+            expand_refs: true,
+            counting: None,
+            temporal: Some(TemporalFormat::default()),
         }
     }
 }
@@ -40,15 +122,51 @@ pub fn to_ddr_string(expr: &Expr) -> String {
 pub fn to_ddr_string_with_options(expr: &Expr, options: &GenerateOptions) -> String {
     match expr {
         Expr::Range(range) => generate_range(range, options),
+        Expr::Not(inner) => {
+            let (_, _, not) = operators(options);
+            match inner.as_ref() {
+                Expr::Range(range) if range.start == range.end && range.operator == RangeOp::Or => {
+                    format!("{}{}", not, generate_range(range, options))
+                }
+                Expr::Temporal { .. } => format!("{}{}", not, to_ddr_string_with_options(inner, options)),
+                Expr::Ref { .. } if !options.expand_refs => {
+                    format!("{}{}", not, to_ddr_string_with_options(inner, options))
+                }
+                _ => format!("{}({})", not, to_ddr_string_with_options(inner, options)),
+            }
+        }
+        Expr::Ref { name, body } => {
+            if options.expand_refs {
+                to_ddr_string_with_options(body, options)
+            } else {
+                format!("${}", name)
+            }
+        }
+        Expr::Compare(comparison) => format!(
+            "{}({}{}{}) {} {}",
+            comparison.measure.keyword(),
+            options.prefix,
+            comparison.detector,
+            options.suffix,
+            comparison.op,
+            comparison.value
+        ),
+        Expr::Temporal { op, inner } => {
+            let default = TemporalFormat::default();
+            let format = options.temporal.as_ref().unwrap_or(&default);
+            format
+                .template(op)
+                .replace("{secs}", &op.duration_ms().map(format_seconds).unwrap_or_default())
+                .replace("{expr}", &to_ddr_string_with_options(inner, options))
+        }
         Expr::Binary { op, left, right } => {
+            let (and, or, _) = operators(options);
             format!(
                 "({}) {} ({})",
                 to_ddr_string_with_options(left, options),
-                match (op, options.use_symbols) {
-                    (BinaryOp::And, false) => "and",
-                    (BinaryOp::And, true) => "&",
-                    (BinaryOp::Or, false) => "or",
-                    (BinaryOp::Or, true) => "|",
+                match op {
+                    BinaryOp::And => and,
+                    BinaryOp::Or => or,
                 },
                 to_ddr_string_with_options(right, options)
             )
@@ -56,20 +174,111 @@ pub fn to_ddr_string_with_options(expr: &Expr, options: &GenerateOptions) -> Str
     }
 }
 
+/// Записи and, or и not (с пробелом после слова)
+fn operators(options: &GenerateOptions) -> (String, String, String) {
+    if options.use_symbols {
+        return ("&".to_string(), "|".to_string(), "!".to_string());
+    }
+    let keywords = &options.keywords;
+    (keywords.and.clone(), keywords.or.clone(), format!("{} ", keywords.not))
+}
+
 /// Генерация строки для диапазона
 fn generate_range(range: &Range, options: &GenerateOptions) -> String {
     let numbers: Vec<String> = (range.start..=range.end)
         .map(|n| format!("{}{}{}", options.prefix, n, options.suffix))
         .collect();
-    
-    let operator_str = match (&range.operator, options.use_symbols) {
-        (RangeOp::And, false) => "and",
-        (RangeOp::And, true) => "&",
-        (RangeOp::Or, false) => "or",
-        (RangeOp::Or, true) => "|",
+    let (and, or, not) = operators(options);
+
+    let (count, exact) = match range.operator {
+        RangeOp::And => return numbers.join(&format!(" {} ", and)),
+        RangeOp::Or => return numbers.join(&format!(" {} ", or)),
+        RangeOp::AtLeast(count) => (count, false),
+        RangeOp::Exactly(count) => (count, true),
     };
-    
-    numbers.join(&format!(" {} ", operator_str))
+
+    let counted = |format: &CountFormat| {
+        let template = if exact { &format.exactly } else { &format.at_least };
+        template
+            .replace("{n}", &count.to_string())
+            .replace("{list}", &numbers.join(", "))
+    };
+    if let Some(format) = &options.counting {
+        return counted(format);
+    }
+
+    // Раскрытие: «не меньше n» — хотя бы одна группа из n занята;
+    // «ровно n» — группа из n занята, а остальные свободны
+    let size = numbers.len();
+    match (count as usize, exact) {
+        (1, false) => return numbers.join(&format!(" {} ", or)),
+        (n, false) if n == size => return numbers.join(&format!(" {} ", and)),
+        _ => {}
+    }
+    if binomial(size, count as usize) > MAX_COUNT_GROUPS {
+        return counted(&CountFormat::default());
+    }
+    let groups: Vec<String> = combinations(size, count as usize)
+        .into_iter()
+        .map(|chosen| {
+            let terms: Vec<String> = (0..size)
+                .filter(|i| exact || chosen.contains(i))
+                .map(|i| match chosen.contains(&i) {
+                    true => numbers[i].clone(),
+                    false => format!("{}{}", not, numbers[i]),
+                })
+                .collect();
+            terms.join(&format!(" {} ", and))
+        })
+        .collect();
+
+    if groups.len() == 1 {
+        return groups.into_iter().next().unwrap_or_default();
+    }
+    groups
+        .iter()
+        .map(|group| format!("({})", group))
+        .collect::<Vec<_>>()
+        .join(&format!(" {} ", or))
+}
+
+/// Число сочетаний из `n` по `k`; больше `usize::MAX` — `usize::MAX`
+pub(crate) fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    // Промежуточные C(n, i) растут, поэтому после насыщения можно остановиться
+    let mut acc: u128 = 1;
+    for i in 0..k.min(n - k) {
+        acc = acc * (n - i) as u128 / (i + 1) as u128;
+        if acc > usize::MAX as u128 {
+            return usize::MAX;
+        }
+    }
+    acc as usize
+}
+
+/// Все сочетания из `n` по `k` (номера по возрастанию), в лексикографическом порядке
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    if k > n {
+        return result;
+    }
+    let mut current = Vec::with_capacity(k);
+    fn walk(start: usize, n: usize, k: usize, current: &mut Vec<usize>, result: &mut Vec<Vec<usize>>) {
+        if current.len() == k {
+            result.push(current.clone());
+            return;
+        }
+        // Оставляем место для недостающих номеров, иначе перебор экспоненциальный
+        for i in start..=n - (k - current.len()) {
+            current.push(i);
+            walk(i + 1, n, k, current, result);
+            current.pop();
+        }
+    }
+    walk(0, n, k, &mut current, &mut result);
+    result
 }
 
 #[cfg(test)]
@@ -94,6 +303,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_generate_counting() {
+        let at_least = Range::new(1, 3, RangeOp::AtLeast(2));
+        assert_eq!(
+            generate_range(&at_least, &GenerateOptions::default()),
+            "(ddr(D1) and ddr(D2)) or (ddr(D1) and ddr(D3)) or (ddr(D2) and ddr(D3))"
+        );
+
+        let options = GenerateOptions {
+            prefix: "D".to_string(),
+            suffix: "".to_string(),
+            use_symbols: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_range(&Range::new(1, 2, RangeOp::Exactly(1)), &options),
+            "(D1 & !D2) | (!D1 & D2)"
+        );
+        assert_eq!(generate_range(&Range::new(1, 2, RangeOp::Exactly(0)), &options), "!D1 & !D2");
+        assert_eq!(generate_range(&Range::new(1, 3, RangeOp::AtLeast(3)), &options), "D1 & D2 & D3");
+
+        let counting = GenerateOptions { counting: Some(CountFormat::default()), ..options };
+        assert_eq!(
+            generate_range(&Range::new(1, 3, RangeOp::AtLeast(2)), &counting),
+            "count(D1, D2, D3) >= 2"
+        );
+    }
+
     #[test]
     fn test_generate_binary() {
         let left = Expr::Range(Range::new(1, 3, RangeOp::Or));
@@ -159,4 +396,97 @@ mod tests {
             "(ddr(D1) | ddr(D2) | ddr(D3)) & (ddr(D4) | ddr(D5) | ddr(D6))"
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_not_and_refs() {
+        let nb = Expr::Ref {
+            name: "NB".to_string(),
+            body: Box::new(Expr::Range(Range::new(1, 2, RangeOp::Or))),
+        };
+        let expr = Expr::Binary {
+            op: BinaryOp::And,
+            left: Box::new(nb.clone()),
+            right: Box::new(Expr::Not(Box::new(Expr::Range(Range::new(9, 9, RangeOp::Or))))),
+        };
+        assert_eq!(to_ddr_string(&expr), "(ddr(D1) or ddr(D2)) and (not ddr(D9))");
+
+        let options = GenerateOptions {
+            expand_refs: false,
+            use_symbols: true,
+            ..Default::default()
+        };
+        assert_eq!(to_ddr_string_with_options(&expr, &options), "($NB) & (!ddr(D9))");
+        assert_eq!(
+            to_ddr_string(&Expr::Not(Box::new(nb))),
+            "not (ddr(D1) or ddr(D2))"
+        );
+    }
+
+    #[test]
+    fn test_comparison() {
+        let expr = Expr::Not(Box::new(Expr::Compare(Comparison {
+            measure: Measure::Occupancy,
+            detector: 3,
+            op: CompareOp::Ge,
+            value: 62.5,
+        })));
+        assert_eq!(to_ddr_string(&expr), "not (occ(ddr(D3)) >= 62.5)");
+    }
+
+    #[test]
+    fn test_temporal() {
+        let expr = Expr::Temporal {
+            op: TemporalOp::Delay { ms: 2500 },
+            inner: Box::new(Expr::Range(Range::new(3, 4, RangeOp::And))),
+        };
+        assert_eq!(to_ddr_string(&expr), "delay(ddr(D3) and ddr(D4), 2.5)");
+
+        let options = GenerateOptions {
+            prefix: "D".to_string(),
+            suffix: "".to_string(),
+            temporal: Some(TemporalFormat::iec()),
+            ..Default::default()
+        };
+        let not_rise = Expr::Not(Box::new(Expr::Temporal {
+            op: TemporalOp::Rise,
+            inner: Box::new(Expr::Range(Range::new(7, 7, RangeOp::Or))),
+        }));
+        assert_eq!(to_ddr_string_with_options(&expr, &options), "TON(D3 and D4, T#2.5s)");
+        assert_eq!(to_ddr_string_with_options(&not_rise, &options), "not R_TRIG(D7)");
+    }
+
+    #[test]
+    fn test_count_limit() {
+        let expr = Expr::Range(Range::new(1, 40, RangeOp::AtLeast(12)));
+        let list: Vec<String> = (1..=40).map(|n| format!("ddr(D{})", n)).collect();
+        assert_eq!(to_ddr_string(&expr), format!("count({}) >= 12", list.join(", ")));
+        assert_eq!(
+            to_ddr_string(&Expr::Range(Range::new(1, 40, RangeOp::Exactly(20)))),
+            format!("count({}) = 20", list.join(", "))
+        );
+        // 40 групп раскрываются
+        let groups = to_ddr_string(&Expr::Range(Range::new(1, 40, RangeOp::Exactly(39))));
+        assert_eq!(groups.matches(") or (").count(), 39);
+        assert_eq!(binomial(40, 12), 5_586_853_480);
+        assert_eq!(binomial(200, 100), usize::MAX);
+    }
+
+    #[test]
+    fn test_keywords() {
+        let options = GenerateOptions { keywords: Keywords::russian(), ..Default::default() };
+        let expr = Expr::Binary {
+            op: BinaryOp::Or,
+            left: Box::new(Expr::Range(Range::new(1, 3, RangeOp::Exactly(2)))),
+            right: Box::new(Expr::Not(Box::new(Expr::Range(Range::new(9, 9, RangeOp::Or))))),
+        };
+        assert_eq!(
+            to_ddr_string_with_options(&expr, &options),
+            "((ddr(D1) и ddr(D2) и не ddr(D3)) или (ddr(D1) и не ddr(D2) и ddr(D3)) или \
+             (не ddr(D1) и ddr(D2) и ddr(D3))) или (не ddr(D9))"
+        );
+
+        // Символы важнее слов
+        let symbols = GenerateOptions { use_symbols: true, ..options };
+        assert_eq!(to_ddr_string_with_options(&expr, &symbols).matches('|').count(), 3);
+    }
+}
@@ -0,0 +1,341 @@
+//! Подсветка синтаксиса условий
+//!
+//! Раскрашивает условие по токенам: операторы, функции (`delay`, `ddr`),
+//! номера детекторов, прочие числа, ссылки `$ИМЯ`, скобки по уровням
+//! вложенности, комментарии и ошибки. Подходит и для сокращённой записи
+//! (`(or 1-3) and not 9`), и для развёрнутой (`ddr(D1) or ddr(D2)`).
+//! Вывод — ANSI-цвета для терминала или HTML с CSS-классами.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::{highlight_html, Notation};
+//!
+//! assert_eq!(
+//!     highlight_html("1-3 & $NB", Notation::Shorthand),
+//!     "<code class=\"ddr\"><span class=\"ddr-det\">1</span><span class=\"ddr-op\">-</span>\
+//!      <span class=\"ddr-det\">3</span> <span class=\"ddr-op\">&amp;</span> \
+//!      <span class=\"ddr-ref\">$NB</span></code>"
+//! );
+//! ```
+
+use std::ops::Range;
+
+use crate::conditions::cst::{is_detector_name, SyntaxTree};
+use crate::conditions::lexer::{tokenize, Token, TokenKind};
+
+/// Какая запись подсвечивается
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// Сокращённая запись: `(or 1-3) and not 9`; ошибки разбора выделяются
+    Shorthand,
+    /// Развёрнутый вывод генератора: `ddr(D1) or ddr(D2)`; `#` — не
+    /// комментарий (`T#5s`), незнакомые символы не считаются ошибкой
+    Expanded,
+}
+
+/// Класс фрагмента
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightClass {
+    /// `and or not & | ! of`, сравнения, `-` в диапазоне
+    Operator,
+    /// `delay hold rise fall occ cnt`, в развёрнутой записи — `ddr(`, `TON(`
+    Function,
+    /// Номер детектора: `3` в `1-3`, `D3` в `ddr(D3)`
+    Detector,
+    /// Прочие числа: счёт, порог, секунды
+    Number,
+    /// `$ИМЯ`
+    Reference,
+    /// Скобка; уровень вложенности с 0
+    Paren(usize),
+    Comment,
+    /// Непарная скобка, незнакомый символ, место ошибки разбора
+    Error,
+    /// Пробелы и всё остальное
+    Plain,
+}
+
+impl HighlightClass {
+    /// CSS-класс для HTML
+    pub fn css_class(&self) -> String {
+        match self {
+            HighlightClass::Operator => "ddr-op".to_string(),
+            HighlightClass::Function => "ddr-fn".to_string(),
+            HighlightClass::Detector => "ddr-det".to_string(),
+            HighlightClass::Number => "ddr-num".to_string(),
+            HighlightClass::Reference => "ddr-ref".to_string(),
+            HighlightClass::Paren(level) => format!("ddr-paren ddr-paren-{}", level % PAREN_COLORS.len()),
+            HighlightClass::Comment => "ddr-comment".to_string(),
+            HighlightClass::Error => "ddr-error".to_string(),
+            HighlightClass::Plain => String::new(),
+        }
+    }
+
+    /// Escape-последовательность ANSI; `None` — без цвета
+    pub fn ansi_code(&self) -> Option<&'static str> {
+        match self {
+            HighlightClass::Operator => Some("1;35"),
+            HighlightClass::Function => Some("34"),
+            HighlightClass::Detector => Some("36"),
+            HighlightClass::Number => Some("33"),
+            HighlightClass::Reference => Some("32"),
+            HighlightClass::Paren(level) => Some(PAREN_COLORS[level % PAREN_COLORS.len()]),
+            HighlightClass::Comment => Some("90"),
+            HighlightClass::Error => Some("4;31"),
+            HighlightClass::Plain => None,
+        }
+    }
+}
+
+/// Цвета скобок по уровням, по кругу
+const PAREN_COLORS: [&str; 3] = ["93", "95", "96"];
+
+/// Таблица стилей для `highlight_html` — те же цвета, что в терминале
+pub const HIGHLIGHT_CSS: &str = "\
+.ddr-op { color: #a626a4; font-weight: bold; }
+.ddr-fn { color: #4078f2; }
+.ddr-det { color: #0184bc; }
+.ddr-num { color: #986801; }
+.ddr-ref { color: #50a14f; }
+.ddr-paren-0 { color: #c18401; }
+.ddr-paren-1 { color: #c678dd; }
+.ddr-paren-2 { color: #56b6c2; }
+.ddr-comment { color: #a0a1a7; font-style: italic; }
+.ddr-error { color: #e45649; text-decoration: underline wavy; }
+";
+
+/// Фрагмент текста и его класс
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    pub span: Range<usize>,
+    pub class: HighlightClass,
+}
+
+/// Разбивает текст на подсвеченные фрагменты, покрывающие его целиком
+pub fn highlight(source: &str, notation: Notation) -> Vec<Highlight> {
+    let tokens = tokens(source, notation);
+    let text = |i: usize| tokens[i].text(source);
+
+    let mut highlights = Vec::with_capacity(tokens.len());
+    let mut open: Vec<usize> = Vec::new();
+    let mut previous: Option<usize> = None;
+    for (i, token) in tokens.iter().enumerate() {
+        let next = (i + 1..tokens.len()).find(|&j| !tokens[j].kind.is_trivia());
+
+        let class = match token.kind {
+            TokenKind::Whitespace => HighlightClass::Plain,
+            TokenKind::Comment => HighlightClass::Comment,
+            TokenKind::Number => {
+                // Счёт `2of`, порог после сравнения, секунды после запятой, `T#5s`
+                let counted = next.is_some_and(|next| tokens[next].word(source) == "of");
+                let measured = previous.is_some_and(|previous| match tokens[previous].kind {
+                    TokenKind::Symbol => !matches!(text(previous), "&" | "|" | "!" | "$"),
+                    TokenKind::Unknown => true,
+                    _ => false,
+                });
+                if counted || measured { HighlightClass::Number } else { HighlightClass::Detector }
+            }
+            TokenKind::Dash => HighlightClass::Operator,
+            TokenKind::Keyword if matches!(token.word(source), "and" | "or" | "not" | "of") => HighlightClass::Operator,
+            TokenKind::Keyword => HighlightClass::Function,
+            TokenKind::Symbol if text(i) == "$" => HighlightClass::Reference,
+            TokenKind::Symbol if text(i) == "," => HighlightClass::Plain,
+            TokenKind::Symbol => HighlightClass::Operator,
+            TokenKind::Ident if previous.is_some_and(|previous| text(previous) == "$") => HighlightClass::Reference,
+            TokenKind::Ident if next.is_some_and(|next| tokens[next].kind == TokenKind::LParen) => {
+                HighlightClass::Function
+            }
+            TokenKind::Ident if is_detector_name(text(i)) => HighlightClass::Detector,
+            TokenKind::Ident => HighlightClass::Plain,
+            TokenKind::LParen => {
+                open.push(i);
+                HighlightClass::Paren(open.len() - 1)
+            }
+            TokenKind::RParen => match open.pop() {
+                Some(_) => HighlightClass::Paren(open.len()),
+                None => HighlightClass::Error,
+            },
+            TokenKind::Unknown if notation == Notation::Shorthand => HighlightClass::Error,
+            TokenKind::Unknown => HighlightClass::Plain,
+        };
+        if !token.kind.is_trivia() {
+            previous = Some(i);
+        }
+        highlights.push(Highlight { span: token.span.clone(), class });
+    }
+
+    // Незакрытые скобки
+    for i in open {
+        highlights[i].class = HighlightClass::Error;
+    }
+    if notation == Notation::Shorthand {
+        mark_parse_error(source, &mut highlights);
+    }
+
+    // Соседние фрагменты одного класса склеиваем: `$` и `NB` — одна ссылка
+    let mut merged: Vec<Highlight> = Vec::with_capacity(highlights.len());
+    for highlight in highlights {
+        match merged.last_mut() {
+            Some(last) if last.class == highlight.class && !matches!(highlight.class, HighlightClass::Paren(_)) => {
+                last.span.end = highlight.span.end
+            }
+            _ => merged.push(highlight),
+        }
+    }
+    merged
+}
+
+/// Токены; в развёрнутой записи `#` не начинает комментарий
+fn tokens(source: &str, notation: Notation) -> Vec<Token> {
+    let mut tokens = tokenize(source);
+    if notation == Notation::Shorthand {
+        return tokens;
+    }
+    while let Some(index) = tokens.iter().position(|token| token.kind == TokenKind::Comment) {
+        let start = tokens[index].span.start;
+        tokens.truncate(index);
+        tokens.push(Token { kind: TokenKind::Unknown, span: start..start + 1 });
+        let rest = tokenize(&source[start + 1..]).into_iter().map(|token| Token {
+            span: token.span.start + start + 1..token.span.end + start + 1,
+            ..token
+        });
+        tokens.extend(rest);
+    }
+    tokens
+}
+
+/// Выделяет место ошибки разбора; неизвестные имена `$ИМЯ` ошибкой
+/// не считаются — таблица имён здесь не известна
+fn mark_parse_error(source: &str, highlights: &mut [Highlight]) {
+    let tree = SyntaxTree::parse(source);
+    let Some(error) = tree.error() else {
+        return;
+    };
+    let visible = |highlight: &Highlight| {
+        highlight.class != HighlightClass::Comment && !source[highlight.span.clone()].trim().is_empty()
+    };
+
+    let span = error.span().unwrap_or(source.len()..source.len() + 1);
+    let mut marked = false;
+    for highlight in highlights.iter_mut() {
+        if visible(highlight) && highlight.span.start < span.end && span.start < highlight.span.end {
+            highlight.class = HighlightClass::Error;
+            marked = true;
+        }
+    }
+    // Ошибка в конце текста (`1 and`) — выделяем последний токен
+    if !marked && let Some(last) = highlights.iter_mut().rev().find(|highlight| visible(highlight)) {
+        last.class = HighlightClass::Error;
+    }
+}
+
+/// Текст с цветами ANSI для терминала
+pub fn highlight_ansi(source: &str, notation: Notation) -> String {
+    let mut out = String::new();
+    for Highlight { span, class } in highlight(source, notation) {
+        match class.ansi_code() {
+            Some(code) => out.push_str(&format!("\x1b[{}m{}\x1b[0m", code, &source[span])),
+            None => out.push_str(&source[span]),
+        }
+    }
+    out
+}
+
+/// HTML: `<code class="ddr">` с фрагментами `<span class="ddr-...">`;
+/// стили — `HIGHLIGHT_CSS`
+pub fn highlight_html(source: &str, notation: Notation) -> String {
+    let mut out = String::from("<code class=\"ddr\">");
+    for Highlight { span, class } in highlight(source, notation) {
+        let text = escape_html(&source[span]);
+        match class.css_class().as_str() {
+            "" => out.push_str(&text),
+            css => out.push_str(&format!("<span class=\"{}\">{}</span>", css, text)),
+        }
+    }
+    out.push_str("</code>");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes(source: &str, notation: Notation) -> Vec<(&str, HighlightClass)> {
+        highlight(source, notation)
+            .into_iter()
+            .filter(|highlight| highlight.class != HighlightClass::Plain)
+            .map(|highlight| (&source[highlight.span], highlight.class))
+            .collect()
+    }
+
+    #[test]
+    fn test_shorthand() {
+        use HighlightClass::*;
+        assert_eq!(
+            classes("(2of 1-4) or delay(occ(D3) > 60, 5) # вызов", Notation::Shorthand),
+            [
+                ("(", Paren(0)),
+                ("2", Number),
+                ("of", Operator),
+                ("1", Detector),
+                ("-", Operator),
+                ("4", Detector),
+                (")", Paren(0)),
+                ("or", Operator),
+                ("delay", Function),
+                ("(", Paren(0)),
+                ("occ", Function),
+                ("(", Paren(1)),
+                ("D3", Detector),
+                (")", Paren(1)),
+                (">", Operator),
+                ("60", Number),
+                ("5", Number),
+                (")", Paren(0)),
+                ("# вызов", Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        use HighlightClass::*;
+        assert_eq!(classes("1 xor 2", Notation::Shorthand), [("1", Detector), ("xor", Error), ("2", Detector)]);
+        assert_eq!(classes("(1 or 2", Notation::Shorthand)[0], ("(", Error));
+        assert_eq!(classes("1) or 2", Notation::Shorthand)[1], (")", Error));
+        assert_eq!(classes("1 and", Notation::Shorthand)[1], ("and", Error));
+        assert!(classes("$NB or 1", Notation::Shorthand).iter().all(|(_, class)| *class != Error));
+    }
+
+    #[test]
+    fn test_expanded() {
+        use HighlightClass::*;
+        let source = "ddr(D1) or TON(ddr(D2), T#5s)";
+        assert_eq!(
+            classes(source, Notation::Expanded),
+            [
+                ("ddr", Function),
+                ("(", Paren(0)),
+                ("D1", Detector),
+                (")", Paren(0)),
+                ("or", Operator),
+                ("TON", Function),
+                ("(", Paren(0)),
+                ("ddr", Function),
+                ("(", Paren(1)),
+                ("D2", Detector),
+                (")", Paren(1)),
+                ("5", Number),
+                (")", Paren(0)),
+            ]
+        );
+        assert_eq!(
+            highlight_ansi("not D9", Notation::Expanded),
+            "\x1b[1;35mnot\x1b[0m \x1b[36mD9\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Написание ключевых слов условий
+//!
+//! Кроме английских `and`/`or`/`not`/`of` разбор принимает русские
+//! `и`/`или`/`не`/`из`, слова в любом регистре (`AND`, `ИЛИ`) и тире из
+//! документов (`1–3`) вместо дефиса. Генератор выводит слова из
+//! `GenerateOptions::keywords`.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::{
+//!     parse_ddr_expression, parse_ddr_expression_with_keywords, to_ddr_string_with_options,
+//!     GenerateOptions, Keywords,
+//! };
+//!
+//! let expr = parse_ddr_expression("(1–3) И НЕ 9").unwrap();
+//! assert_eq!(expr, parse_ddr_expression("(1-3) and not 9").unwrap());
+//! assert!(parse_ddr_expression_with_keywords("1 AND 2", &Keywords::english()).is_err());
+//!
+//! let options = GenerateOptions { keywords: Keywords::russian(), ..GenerateOptions::default() };
+//! assert_eq!(
+//!     to_ddr_string_with_options(&expr, &options),
+//!     "(ddr(D1) или ddr(D2) или ddr(D3)) и (не ddr(D9))"
+//! );
+//! ```
+
+use std::sync::LazyLock;
+
+/// Ключевые слова в каноническом (английском) написании; они принимаются всегда
+const CANONICAL: [&str; 10] = ["and", "or", "not", "of", "delay", "hold", "rise", "fall", "occ", "cnt"];
+
+/// Дефис и тире, которые вставляются из документов вместо него
+const DASHES: [char; 7] = ['-', '‐', '‑', '‒', '–', '—', '−'];
+
+/// Написание ключевых слов: что выводит генератор и что принимает разбор
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keywords {
+    /// Написание `and`, которое выводит генератор
+    pub and: String,
+    /// Написание `or`
+    pub or: String,
+    /// Написание `not`
+    pub not: String,
+    /// Написание `of` в счёте `2of 1-4`
+    pub of: String,
+    /// Другие написания, которые принимает разбор: `("и", "and")`
+    pub aliases: Vec<(String, String)>,
+    /// Знаки между номерами диапазона `1-3`
+    pub dashes: Vec<char>,
+    /// Слова в любом регистре: `AND`, `Or`, `ИЛИ`
+    pub ignore_case: bool,
+}
+
+impl Keywords {
+    /// Только английские слова в нижнем регистре и дефис — строгая запись
+    pub fn english() -> Self {
+        Self {
+            and: "and".to_string(),
+            or: "or".to_string(),
+            not: "not".to_string(),
+            of: "of".to_string(),
+            aliases: Vec::new(),
+            dashes: vec!['-'],
+            ignore_case: false,
+        }
+    }
+
+    /// Русские слова и/или/не/из; английские тоже принимаются
+    pub fn russian() -> Self {
+        Self {
+            and: "и".to_string(),
+            or: "или".to_string(),
+            not: "не".to_string(),
+            of: "из".to_string(),
+            aliases: Vec::new(),
+            dashes: DASHES.to_vec(),
+            ignore_case: true,
+        }
+    }
+
+    /// Каноническое ключевое слово ("and", "delay", ...) для написания `word`
+    pub fn canonical(&self, word: &str) -> Option<&'static str> {
+        let same = |spelling: &str| match self.ignore_case {
+            true => spelling.to_lowercase() == word.to_lowercase(),
+            false => spelling == word,
+        };
+        let spellings = [
+            (self.and.as_str(), "and"),
+            (self.or.as_str(), "or"),
+            (self.not.as_str(), "not"),
+            (self.of.as_str(), "of"),
+        ];
+
+        CANONICAL
+            .into_iter()
+            .find(|keyword| same(keyword))
+            .or_else(|| spellings.into_iter().find(|(spelling, _)| same(spelling)).map(|(_, keyword)| keyword))
+            .or_else(|| {
+                let (_, keyword) = self.aliases.iter().find(|(alias, _)| same(alias))?;
+                CANONICAL.into_iter().find(|canonical| canonical == keyword)
+            })
+    }
+
+    /// Знак диапазона ли `c`
+    pub fn is_dash(&self, c: char) -> bool {
+        self.dashes.contains(&c)
+    }
+}
+
+/// Английские слова для вывода; при разборе — ещё русские, любой регистр и тире
+impl Default for Keywords {
+    fn default() -> Self {
+        Self {
+            aliases: [("и", "and"), ("или", "or"), ("не", "not"), ("из", "of")]
+                .into_iter()
+                .map(|(alias, keyword)| (alias.to_string(), keyword.to_string()))
+                .collect(),
+            dashes: DASHES.to_vec(),
+            ignore_case: true,
+            ..Self::english()
+        }
+    }
+}
+
+/// Написание по умолчанию — для дерева разбора, форматирования и подсветки
+pub(crate) static DEFAULT_KEYWORDS: LazyLock<Keywords> = LazyLock::new(Keywords::default);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical() {
+        let keywords = Keywords::default();
+        assert_eq!(keywords.canonical("AND"), Some("and"));
+        assert_eq!(keywords.canonical("Или"), Some("or"));
+        assert_eq!(keywords.canonical("Delay"), Some("delay"));
+        assert_eq!(keywords.canonical("xor"), None);
+        assert!(keywords.is_dash('–'));
+
+        let strict = Keywords::english();
+        assert_eq!(strict.canonical("and"), Some("and"));
+        assert_eq!(strict.canonical("AND"), None);
+        assert_eq!(strict.canonical("и"), None);
+        assert!(!strict.is_dash('–'));
+
+        assert_eq!(Keywords::russian().canonical("ИЗ"), Some("of"));
+        assert_eq!(Keywords::russian().canonical("of"), Some("of"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Разбиение текста условия на токены
+//!
+//! Токены покрывают весь текст без пропусков, включая пробелы и
+//! комментарии, поэтому из них текст собирается обратно байт в байт.
+//! На этих токенах работают разбор условий, дерево разбора, форматирование
+//! и подсветка. Какие слова считать ключевыми и какие знаки — дефисом
+//! диапазона, задаёт `Keywords` (`tokenize_with`).
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::{tokenize, TokenKind};
+//!
+//! let source = "2of 1-4 & !$NB";
+//! let tokens: Vec<_> = tokenize(source)
+//!     .into_iter()
+//!     .filter(|token| !token.kind.is_trivia())
+//!     .map(|token| (token.kind, token.text(source)))
+//!     .collect();
+//! assert_eq!(tokens[..4], [
+//!     (TokenKind::Number, "2"),
+//!     (TokenKind::Keyword, "of"),
+//!     (TokenKind::Number, "1"),
+//!     (TokenKind::Dash, "-"),
+//! ]);
+//! assert_eq!(tokens.len(), 9);
+//! ```
+
+use std::ops::Range;
+
+use crate::conditions::keywords::{Keywords, DEFAULT_KEYWORDS};
+
+/// Вид токена
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Число: `12`, `2.5`
+    Number,
+    /// Дефис или тире в диапазоне: `1-3`, `1–3`
+    Dash,
+    /// Ключевое слово: and, or, not, of, delay, hold, rise, fall, occ, cnt
+    /// и их написания из `Keywords` (`AND`, `или`)
+    Keyword,
+    /// Знак: `& | ! $ ,` и сравнения `> >= < <= = == != <>`
+    Symbol,
+    /// Имя: `NB` в `$NB`, `D3` в `occ(D3)`
+    Ident,
+    LParen,
+    RParen,
+    /// Пробелы и переводы строк
+    Whitespace,
+    /// Комментарий от `#` до конца строки
+    Comment,
+    /// Символ, которого нет в языке
+    Unknown,
+}
+
+impl TokenKind {
+    /// Пробелы и комментарии — на смысл условия не влияют
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, TokenKind::Whitespace | TokenKind::Comment)
+    }
+}
+
+/// Токен: вид и байтовый диапазон в исходном тексте
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+impl Token {
+    /// Текст токена
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.span.clone()]
+    }
+
+    /// Текст токена; ключевое слово — в каноническом написании
+    /// (`ИЛИ` → `or`) по `Keywords` по умолчанию
+    pub(crate) fn word<'a>(&self, source: &'a str) -> &'a str {
+        let text = self.text(source);
+        match self.kind {
+            TokenKind::Keyword => DEFAULT_KEYWORDS.canonical(text).unwrap_or(text),
+            _ => text,
+        }
+    }
+}
+
+/// Двухсимвольные знаки проверяются раньше односимвольных
+const SYMBOLS: [&str; 13] = [">=", "<=", "!=", "<>", "==", ">", "<", "=", "&", "|", "!", "$", ","];
+
+/// Разбивает текст на токены с ключевыми словами `Keywords` по умолчанию
+pub fn tokenize(source: &str) -> Vec<Token> {
+    tokenize_with(source, &DEFAULT_KEYWORDS)
+}
+
+/// Разбивает текст на токены с заданным написанием ключевых слов
+pub fn tokenize_with(source: &str, keywords: &Keywords) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while let Some(c) = source[position..].chars().next() {
+        let rest = &source[position..];
+        let (kind, len) = if c.is_whitespace() {
+            (TokenKind::Whitespace, prefix_len(rest, char::is_whitespace))
+        } else if c == '#' {
+            (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if c.is_ascii_digit() {
+            let digits = prefix_len(rest, |c| c.is_ascii_digit());
+            let fraction = match rest[digits..].strip_prefix('.') {
+                Some(after) if after.starts_with(|c: char| c.is_ascii_digit()) => {
+                    1 + prefix_len(after, |c| c.is_ascii_digit())
+                }
+                _ => 0,
+            };
+            (TokenKind::Number, digits + fraction)
+        } else if c.is_alphabetic() || c == '_' {
+            let len = prefix_len(rest, |c| c.is_alphanumeric() || c == '_');
+            let kind = match keywords.canonical(&rest[..len]) {
+                Some(_) => TokenKind::Keyword,
+                None => TokenKind::Ident,
+            };
+            (kind, len)
+        } else if keywords.is_dash(c) {
+            (TokenKind::Dash, c.len_utf8())
+        } else if c == '(' {
+            (TokenKind::LParen, 1)
+        } else if c == ')' {
+            (TokenKind::RParen, 1)
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) {
+            (TokenKind::Symbol, symbol.len())
+        } else {
+            (TokenKind::Unknown, c.len_utf8())
+        };
+
+        tokens.push(Token { kind, span: position..position + len });
+        position += len;
+    }
+    tokens
+}
+
+/// Длина в байтах начала строки из символов, подходящих под `accept`
+fn prefix_len(text: &str, accept: impl Fn(char) -> bool) -> usize {
+    text.find(|c: char| !accept(c)).unwrap_or(text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(source).iter().map(|token| (token.kind, token.text(source))).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("(2of 1-4)&!$NB # вызов"),
+            [
+                (LParen, "("),
+                (Number, "2"),
+                (Keyword, "of"),
+                (Whitespace, " "),
+                (Number, "1"),
+                (Dash, "-"),
+                (Number, "4"),
+                (RParen, ")"),
+                (Symbol, "&"),
+                (Symbol, "!"),
+                (Symbol, "$"),
+                (Ident, "NB"),
+                (Whitespace, " "),
+                (Comment, "# вызов"),
+            ]
+        );
+        assert_eq!(
+            kinds("occ(D3)>=60.5 ~"),
+            [
+                (Keyword, "occ"),
+                (LParen, "("),
+                (Ident, "D3"),
+                (RParen, ")"),
+                (Symbol, ">="),
+                (Number, "60.5"),
+                (Whitespace, " "),
+                (Unknown, "~"),
+            ]
+        );
+        assert_eq!(
+            kinds("1–3 ИЛИ xor"),
+            [
+                (Number, "1"),
+                (Dash, "–"),
+                (Number, "3"),
+                (Whitespace, " "),
+                (Keyword, "ИЛИ"),
+                (Whitespace, " "),
+                (Ident, "xor"),
+            ]
+        );
+        let strict = tokenize_with("1–3 OR", &Keywords::english());
+        assert_eq!(strict.iter().map(|token| token.kind).collect::<Vec<_>>(), [Number, Unknown, Number, Whitespace, Ident]);
+    }
+}
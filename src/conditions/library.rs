@@ -0,0 +1,389 @@
+//! Файлы-библиотеки условий
+//!
+//! Полный набор условий перекрёстка в одном файле:
+//!
+//! ```text
+//! # СО 4554, ул. Ленина
+//! NB = 1-4                    # вспомогательное условие, в вывод не попадает
+//! SB = 5-8
+//!
+//! phase1: $NB and not 9       # условие вызова фазы 1
+//! phase2: ($SB) or (and 10-11)
+//! ```
+//!
+//! Строка `имя: условие` — условие для контроллера, `ИМЯ = условие` —
+//! вспомогательное определение. На любое имя можно сослаться через `$ИМЯ`,
+//! порядок строк не важен. Всё после `#` — комментарий.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::{GenerateOptions, Library};
+//!
+//! let text = "NB = 1-2\nphase1: $NB and not 9\n";
+//! let library = Library::parse("junction.ddr", text).unwrap();
+//! assert_eq!(
+//!     library.generate(&GenerateOptions::default()),
+//!     "phase1: (ddr(D1) or ddr(D2)) and (not ddr(D9))\n"
+//! );
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::conditions::ast::Expr;
+use crate::conditions::error::ParseError;
+use crate::conditions::format::{format_condition, FormatOptions};
+use crate::conditions::generator::{to_ddr_string_with_options, GenerateOptions};
+use crate::conditions::parser::parse_ddr_expression_with_symbols;
+use crate::conditions::symbols::{is_valid_name, SymbolTable};
+
+/// Место в файле библиотеки (строка и колонка с 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Ошибки загрузки библиотеки
+#[derive(Error, Debug)]
+pub enum LibraryError {
+    /// Ошибка: файл не читается
+    #[error("{file}: {source}")]
+    Io {
+        file: String,
+        source: std::io::Error,
+    },
+
+    /// Ошибка: строка не разбирается
+    #[error("{location}: {error}")]
+    Syntax { location: Location, error: ParseError },
+
+    /// Ошибка: имя уже определено выше
+    #[error("{location}: имя '{name}' уже определено в строке {first_line}")]
+    Duplicate {
+        location: Location,
+        name: String,
+        first_line: usize,
+    },
+}
+
+impl LibraryError {
+    /// Место ошибки в файле
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            LibraryError::Io { .. } => None,
+            LibraryError::Syntax { location, .. } | LibraryError::Duplicate { location, .. } => {
+                Some(location)
+            }
+        }
+    }
+}
+
+/// Условие библиотеки, которое попадает в вывод
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    /// Текст условия, как он записан в файле
+    pub source: String,
+    pub expr: Expr,
+    /// Номер строки в файле, начиная с 1
+    pub line: usize,
+}
+
+/// Набор условий одного перекрёстка
+#[derive(Debug, Clone)]
+pub struct Library {
+    file: String,
+    symbols: SymbolTable,
+    entries: Vec<Entry>,
+}
+
+/// Строка файла до разбора условия
+struct RawLine<'a> {
+    line: usize,
+    text: &'a str,
+    name: &'a str,
+    /// Смещение условия (в байтах) от начала строки
+    offset: usize,
+    source: &'a str,
+    exported: bool,
+}
+
+impl Library {
+    /// Читает библиотеку из файла
+    pub fn load(path: impl AsRef<Path>) -> Result<Library, Vec<LibraryError>> {
+        let path = path.as_ref();
+        let file = path.display().to_string();
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&file, &text),
+            Err(source) => Err(vec![LibraryError::Io { file, source }]),
+        }
+    }
+
+    /// Разбирает текст библиотеки. `file` — имя для сообщений об ошибках.
+    ///
+    /// Возвращает все найденные ошибки, а не только первую.
+    pub fn parse(file: &str, text: &str) -> Result<Library, Vec<LibraryError>> {
+        let location = |line: usize, text: &str, offset: usize| Location {
+            file: file.to_string(),
+            line,
+            column: text[..offset.min(text.len())].chars().count() + 1,
+        };
+
+        let mut errors = Vec::new();
+        let mut symbols = SymbolTable::new();
+        let mut lines: Vec<RawLine> = Vec::new();
+
+        // Первый проход: имена, чтобы ссылки работали в любом порядке
+        for (i, text) in text.lines().enumerate() {
+            let raw = match split_line(i + 1, text) {
+                Ok(Some(raw)) => raw,
+                Ok(None) => continue,
+                Err((offset, error)) => {
+                    errors.push(LibraryError::Syntax { location: location(i + 1, text, offset), error });
+                    continue;
+                }
+            };
+
+            if let Some(first) = lines.iter().find(|other| other.name == raw.name) {
+                errors.push(LibraryError::Duplicate {
+                    location: location(raw.line, text, raw.text.len() - raw.text.trim_start().len()),
+                    name: raw.name.to_string(),
+                    first_line: first.line,
+                });
+                continue;
+            }
+
+            symbols
+                .define(raw.name, raw.source)
+                .expect("имя проверено в split_line");
+            lines.push(raw);
+        }
+
+        // Второй проход: разбор условий
+        let mut entries = Vec::new();
+        for raw in &lines {
+            match parse_ddr_expression_with_symbols(raw.source, &symbols) {
+                Ok(expr) if raw.exported => entries.push(Entry {
+                    name: raw.name.to_string(),
+                    source: raw.source.trim().to_string(),
+                    expr,
+                    line: raw.line,
+                }),
+                Ok(_) => {}
+                // Ошибка внутри другого условия будет показана на его строке
+                Err(ParseError::InDefinition { .. }) => {}
+                Err(error) => {
+                    // Позиции в ошибке — от начала строки файла, а не условия
+                    let error = error.shifted(raw.offset);
+                    let offset = error
+                        .position()
+                        .unwrap_or(raw.offset + raw.source.len() - raw.source.trim_start().len());
+                    errors.push(LibraryError::Syntax {
+                        location: location(raw.line, raw.text, offset),
+                        error,
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Library { file: file.to_string(), symbols, entries })
+        } else {
+            errors.sort_by_key(|error| error.location().map(|l| (l.line, l.column)));
+            Err(errors)
+        }
+    }
+
+    /// Текст библиотеки в едином виде: условия форматируются
+    /// `format_condition` (без переносов — условие занимает одну строку),
+    /// комментарии остаются в своих колонках, если хватает места.
+    ///
+    /// Текст с ошибками не форматируется — возвращаются ошибки, как у `parse`.
+    pub fn format(file: &str, text: &str, options: &FormatOptions) -> Result<String, Vec<LibraryError>> {
+        Self::parse(file, text)?;
+        let options = FormatOptions { width: None, ..options.clone() };
+
+        let mut out = String::new();
+        for (i, line) in text.lines().enumerate() {
+            let formatted = match split_line(i + 1, line) {
+                Ok(Some(raw)) => match format_condition(raw.source, &options) {
+                    Ok(condition) => {
+                        let indent = &line[..line.len() - line.trim_start().len()];
+                        let separator = if raw.exported { ":" } else { " =" };
+                        let mut formatted = format!("{indent}{}{separator} {condition}", raw.name);
+
+                        let comment = &line[raw.offset + raw.source.len()..];
+                        if !comment.is_empty() {
+                            let column = line[..raw.offset + raw.source.len()].chars().count();
+                            let padding = column.saturating_sub(formatted.chars().count()).max(1);
+                            formatted.push_str(&" ".repeat(padding));
+                            formatted.push_str(comment);
+                        }
+                        formatted
+                    }
+                    Err(_) => line.trim_end().to_string(),
+                },
+                _ => line.trim_end().to_string(),
+            };
+            out.push_str(&formatted);
+            out.push('\n');
+        }
+        if !text.ends_with('\n') {
+            out.pop();
+        }
+        Ok(out)
+    }
+
+    /// Имя файла, из которого загружена библиотека
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Все имена библиотеки, включая вспомогательные
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// Условия для вывода в порядке файла
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Условие по имени
+    pub fn get(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Вывод для контроллера: по строке `имя: условие` на каждое условие
+    pub fn generate(&self, options: &GenerateOptions) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}: {}\n", entry.name, to_ddr_string_with_options(&entry.expr, options)))
+            .collect()
+    }
+}
+
+/// Делит строку на имя и условие. `Ok(None)` — пустая строка или комментарий,
+/// ошибка несёт смещение в строке.
+fn split_line(line: usize, text: &str) -> Result<Option<RawLine<'_>>, (usize, ParseError)> {
+    let content = text.split('#').next().unwrap_or_default();
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let indent = content.len() - content.trim_start().len();
+    let Some(separator) = content.find([':', '=']) else {
+        return Err((indent, ParseError::InvalidDefinition(content.trim().to_string())));
+    };
+
+    let name = content[..separator].trim();
+    if !is_valid_name(name) {
+        return Err((indent, ParseError::InvalidName(name.to_string())));
+    }
+
+    Ok(Some(RawLine {
+        line,
+        text,
+        name,
+        offset: separator + 1,
+        source: &content[separator + 1..],
+        exported: content[separator..].starts_with(':'),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JUNCTION: &str = "\
+# СО 4554
+phase1: $NB and not 9   # вызов фазы 1
+NB = 1-2
+
+phase2: (and 5-6) or 7
+";
+
+    fn messages(text: &str) -> Vec<String> {
+        Library::parse("test.ddr", text)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_and_generate() {
+        let library = Library::parse("test.ddr", JUNCTION).unwrap();
+        assert_eq!(library.entries().len(), 2);
+        assert_eq!(library.get("phase1").unwrap().line, 2);
+        assert_eq!(library.get("phase1").unwrap().source, "$NB and not 9");
+        assert!(library.symbols().contains("NB"));
+
+        let options = GenerateOptions {
+            prefix: "D".to_string(),
+            suffix: "".to_string(),
+            ..GenerateOptions::default()
+        };
+        assert_eq!(
+            library.generate(&options),
+            "phase1: (D1 or D2) and (not D9)\nphase2: (D5 and D6) or (D7)\n"
+        );
+    }
+
+    #[test]
+    fn test_error_locations() {
+        let text = "\
+phase1: 1-3 xor 4
+phase2 1-3
+  phase3: $NB
+NB = (1-2
+phase1: 5
+";
+        assert_eq!(
+            messages(text),
+            vec![
+                "test.ddr:1:13: Неизвестный оператор 'xor' на позиции 12. Используйте and/or или &/|",
+                "test.ddr:2:1: Ожидалось определение вида ИМЯ = условие, получено 'phase2 1-3'",
+                "test.ddr:4:6: Незакрытая скобка на позиции 5",
+                "test.ddr:5:1: имя 'phase1' уже определено в строке 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_and_cycle() {
+        assert_eq!(
+            messages("a: $b\nb: 1 or $zz\n"),
+            vec!["test.ddr:2:9: Неизвестное имя '$zz' на позиции 8"]
+        );
+        assert_eq!(
+            messages("a: $b\nb: $a\n"),
+            vec![
+                "test.ddr:1:4: Циклическая ссылка: $b → $a → $b",
+                "test.ddr:2:4: Циклическая ссылка: $a → $b → $a",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let text = "# СО 4554\nphase1:$NB &  !9   # вызов фазы 1\nNB=1 - 2           # левый ряд\n\nphase2: ((and 5-6) or 7)\n";
+        let formatted = Library::format("test.ddr", text, &FormatOptions::default()).unwrap();
+        assert_eq!(
+            formatted,
+            "# СО 4554\nphase1: $NB and not 9 # вызов фазы 1\nNB = 1-2           # левый ряд\n\nphase2: (and 5-6) or 7\n"
+        );
+        assert_eq!(Library::format("test.ddr", &formatted, &FormatOptions::default()).unwrap(), formatted);
+        assert!(Library::format("test.ddr", "phase1: 1 xor 2", &FormatOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Сложность условий и ограничения контроллеров
+//!
+//! Контроллер может не принять условие, если оно слишком длинное, слишком
+//! глубоко вложено или содержит слишком много слагаемых. Метрики считаются
+//! для того вида, в котором условие будет записано (`GenerateOptions`):
+//! раскрытые ссылки и счёт дают больше слагаемых, чем исходный текст.
+
+use crate::conditions::ast::*;
+use crate::conditions::error::DialectError;
+use crate::conditions::generator::{binomial, to_ddr_string_with_options, GenerateOptions, MAX_COUNT_GROUPS};
+
+/// Метрики условия в заданном формате вывода
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// Число слагаемых: детекторов и сравнений в выводе (с повторами)
+    pub terms: usize,
+    /// Глубина вложенности операторов; один диапазон или сравнение — 1
+    pub depth: usize,
+    /// Длина выведенной строки в символах
+    pub length: usize,
+    /// Число разных детекторов
+    pub detectors: usize,
+}
+
+impl Metrics {
+    /// Метрики выражения в записи с опциями `options`
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::conditions::{parse_ddr_expression, GenerateOptions, Metrics};
+    ///
+    /// let expr = parse_ddr_expression("(or 1-3) and not 2of 4-6").unwrap();
+    /// let metrics = Metrics::of(&expr, &GenerateOptions::default());
+    /// assert_eq!(metrics.terms, 3 + 6);  // 2 из 3 раскрываются в 3 пары
+    /// assert_eq!(metrics.depth, 4);
+    /// assert_eq!(metrics.detectors, 6);
+    /// ```
+    pub fn of(expr: &Expr, options: &GenerateOptions) -> Self {
+        Self {
+            length: to_ddr_string_with_options(expr, options).chars().count(),
+            ..Self::without_length(expr, options)
+        }
+    }
+
+    /// Метрики без длины (`length` = 0): считаются по выражению, не выводя
+    /// строку, которая для большого счёта может быть огромной
+    pub fn without_length(expr: &Expr, options: &GenerateOptions) -> Self {
+        Self {
+            terms: terms(expr, options),
+            depth: depth(expr, options),
+            length: 0,
+            detectors: expr.detectors().len(),
+        }
+    }
+}
+
+/// Ограничения контроллера; `None` — без ограничения
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub max_terms: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub max_length: Option<usize>,
+    pub max_detectors: Option<usize>,
+}
+
+impl Limits {
+    /// Без ограничений
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Все превышенные ограничения
+    pub fn check(&self, metrics: &Metrics) -> Vec<DialectError> {
+        let exceeded = |actual: usize, max: Option<usize>| max.filter(|&max| actual > max);
+
+        let mut errors = Vec::new();
+        if let Some(max) = exceeded(metrics.terms, self.max_terms) {
+            errors.push(DialectError::TooManyTerms { terms: metrics.terms, max });
+        }
+        if let Some(max) = exceeded(metrics.depth, self.max_depth) {
+            errors.push(DialectError::TooDeep { depth: metrics.depth, max });
+        }
+        if let Some(max) = exceeded(metrics.length, self.max_length) {
+            errors.push(DialectError::TooLong { length: metrics.length, max });
+        }
+        if let Some(max) = exceeded(metrics.detectors, self.max_detectors) {
+            errors.push(DialectError::TooManyDetectors { detectors: metrics.detectors, max });
+        }
+        errors
+    }
+}
+
+/// Слагаемые в выводе
+fn terms(expr: &Expr, options: &GenerateOptions) -> usize {
+    match expr {
+        Expr::Range(range) => range_terms(range, options),
+        Expr::Compare(_) => 1,
+        Expr::Not(inner) | Expr::Temporal { inner, .. } => terms(inner, options),
+        Expr::Ref { body, .. } if options.expand_refs => terms(body, options),
+        Expr::Ref { .. } => 1,
+        Expr::Binary { left, right, .. } => terms(left, options).saturating_add(terms(right, options)),
+    }
+}
+
+fn depth(expr: &Expr, options: &GenerateOptions) -> usize {
+    match expr {
+        Expr::Range(range) if expands_to_groups(range, options) => 2,
+        Expr::Range(_) | Expr::Compare(_) => 1,
+        Expr::Not(inner) | Expr::Temporal { inner, .. } => 1 + depth(inner, options),
+        Expr::Ref { body, .. } if options.expand_refs => depth(body, options),
+        Expr::Ref { .. } => 1,
+        Expr::Binary { left, right, .. } => 1 + depth(left, options).max(depth(right, options)),
+    }
+}
+
+/// Слагаемые диапазона — с учётом раскрытия счёта так же, как в генераторе
+fn range_terms(range: &Range, options: &GenerateOptions) -> usize {
+    let size = range.detector_count() as usize;
+    match range.operator {
+        _ if !expands_to_groups(range, options) => size,
+        RangeOp::AtLeast(n) => binomial(size, n as usize).saturating_mul(n as usize),
+        RangeOp::Exactly(n) => binomial(size, n as usize).saturating_mul(size),
+        RangeOp::Or | RangeOp::And => size,
+    }
+}
+
+/// Раскрывается ли счёт в or из нескольких групп; слишком много групп
+/// генератор не раскрывает, а записывает шаблоном счёта
+fn expands_to_groups(range: &Range, options: &GenerateOptions) -> bool {
+    let size = range.detector_count();
+    let groups = |n: u32| binomial(size as usize, n as usize);
+    options.counting.is_none()
+        && match range.operator {
+            RangeOp::AtLeast(n) => n > 1 && n < size && groups(n) <= MAX_COUNT_GROUPS,
+            RangeOp::Exactly(n) => groups(n) > 1 && groups(n) <= MAX_COUNT_GROUPS,
+            RangeOp::Or | RangeOp::And => false,
+        }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::generator::CountFormat;
+    use crate::conditions::parse_ddr_expression;
+
+    fn metrics(source: &str, options: &GenerateOptions) -> Metrics {
+        Metrics::of(&parse_ddr_expression(source).unwrap(), options)
+    }
+
+    #[test]
+    fn test_metrics() {
+        let options = GenerateOptions::default();
+        let m = metrics("((1-2) and occ(D3) > 5) or delay(not 4, 2)", &options);
+        assert_eq!(m, Metrics { terms: 4, depth: 4, length: 72, detectors: 4 });
+
+        // Счёт: раскрытый и записанный как есть
+        assert_eq!(metrics("=1of 1-3", &options).terms, 9);
+        // Слишком много групп — генератор пишет шаблон счёта
+        let large = metrics("12of 1-40", &options);
+        assert_eq!((large.terms, large.depth, large.detectors), (40, 1, 40));
+        assert_eq!(metrics("35of 1-70", &options).terms, 70);
+        let counting = GenerateOptions { counting: Some(CountFormat::default()), ..options };
+        assert_eq!(metrics("=1of 1-3", &counting), Metrics { terms: 3, depth: 1, length: 36, detectors: 3 });
+    }
+
+    #[test]
+    fn test_limits() {
+        let limits = Limits { max_terms: Some(4), max_depth: Some(2), ..Limits::none() };
+        let errors = limits.check(&metrics("((1-3) and 5) or 7", &GenerateOptions::default()));
+        assert_eq!(
+            errors,
+            [
+                DialectError::TooManyTerms { terms: 5, max: 4 },
+                DialectError::TooDeep { depth: 3, max: 2 },
+            ]
+        );
+        assert!(Limits::none().check(&metrics("or 1-99", &GenerateOptions::default())).is_empty());
+    }
+}
@@ -6,7 +6,7 @@
 //!
 //! # Пример
 //! ```
-//! use ddr_conditions::{parse_ddr_expression, to_ddr_string};
+//! use traffic_core::conditions::{parse_ddr_expression, to_ddr_string};
 //!
 //! let expr = parse_ddr_expression("(or 1-3) and (or 4-6)").unwrap();
 //! let result = to_ddr_string(&expr);
@@ -20,11 +20,45 @@ mod ast;        // ast.rs — структуры данных
 mod parser;     // parser.rs — разбор строки в AST
 mod generator;  // generator.rs — преобразование AST в строку
 mod error;      // error.rs — типы ошибок
+mod eval;       // eval.rs — вычисление выражения по состояниям детекторов
+mod fault;      // fault.rs — неисправные детекторы, трёхзначная логика
+mod temporal;   // temporal.rs — пошаговое вычисление временных операторов
+mod dialect;    // dialect.rs — именованные форматы вывода
+mod simplify;   // simplify.rs — упрощение выражений
+mod symbols;    // symbols.rs — таблица именованных условий
+mod library;    // library.rs — файлы с набором условий перекрёстка
+mod xref;       // xref.rs — перекрёстные ссылки детекторов
+mod metrics;    // metrics.rs — сложность условий и ограничения контроллеров
+mod keywords;   // keywords.rs — написание ключевых слов: русские, любой регистр
+mod lexer;      // lexer.rs — токены текста условия
+mod cst;        // cst.rs — дерево разбора с пробелами и комментариями
+mod format;     // format.rs — форматирование текста условий в едином виде
+mod highlight;  // highlight.rs — подсветка синтаксиса для терминала и HTML
 
 // Реэкспортируем самое важное наружу
 // Теперь пользователь сможет писать:
 // use ddr_conditions::{parse_ddr_expression, Expr, Range, ParseError};
-pub use ast::{Expr, Range, RangeOp, BinaryOp};
-pub use parser::parse_ddr_expression;
-pub use generator::{to_ddr_string, GenerateOptions};
-pub use error::ParseError;
\ No newline at end of file
+pub use ast::{Expr, Range, RangeOp, BinaryOp, TemporalOp, Comparison, CompareOp, Measure, MAX_RANGE_SIZE};
+pub use parser::{parse_ddr_expression, parse_ddr_expression_with_keywords, parse_ddr_expression_with_symbols};
+pub use generator::{
+    to_ddr_string, to_ddr_string_with_options, CountFormat, GenerateOptions, TemporalFormat,
+};
+pub use error::{DialectError, EvalError, ParseError};
+pub use eval::{evaluate, try_evaluate, DetectorInputs, Measurements};
+pub use fault::{
+    evaluate_with_faults, DetectorState, DetectorStates, FaultEvaluation, FaultPolicies, FaultPolicy, Tristate,
+};
+pub use dialect::Dialect;
+pub use metrics::{Limits, Metrics};
+pub use keywords::Keywords;
+pub use lexer::{tokenize, tokenize_with, Token, TokenKind};
+pub use cst::{Element, Node, NodeKind, SyntaxTree};
+pub use format::{format_condition, FormatOptions, KeywordStyle, ParenStyle};
+pub use highlight::{
+    highlight, highlight_ansi, highlight_html, Highlight, HighlightClass, Notation, HIGHLIGHT_CSS,
+};
+pub use simplify::simplify;
+pub use temporal::TemporalEvaluator;
+pub use symbols::SymbolTable;
+pub use library::{Entry, Library, LibraryError, Location};
+pub use xref::{CrossReference, DetectorStatus, XrefRow};
\ No newline at end of file
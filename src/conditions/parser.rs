@@ -1,162 +1,581 @@
 // Пример 1: Простой диапазон
-// "1-3" → range_parser → Expr::Range
+// "1-3" → tokenize → [Number, Dash, Number] → range → Expr::Range
 
 // Пример 2: Диапазон с оператором
-// "and 1-3" → range_parser → Expr::Range(Range { op: And, ... })
+// "and 1-3" → range_op + range → Expr::Range(Range { op: And, ... })
 
 // Пример 3: Скобки с диапазоном
-// "(1-3)" → parens_parser → expr → range_parser → Expr::Range
+// "(1-3)" → parens → expr → range → Expr::Range
 
 // Пример 4: AND двух диапазонов
 // "(1-3) and (4-6)" 
-// parens_parser → Expr::Range
-// binary_op_parser(And)
-// parens_parser → Expr::Range
+// parens → Expr::Range
+// binary_op(And)
+// parens → Expr::Range
 // → Expr::Binary { op: And, left: Range(1-3), right: Range(4-6) }
 
 // Пример 5: Цепочка с приоритетом
 // "(1-3) and (4-6) or (7-9)"
-// precedence расставит приоритеты:
+// операторы применяются слева направо:
 // → Expr::Binary { 
 //     op: Or,
 //     left: Expr::Binary { op: And, left: Range(1-3), right: Range(4-6) },
 //     right: Range(7-9)
 //   }
 
-use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::{char, digit1, multispace0},
-    combinator::{map, opt, value},
-    sequence::delimited,
-    Parser,
-    error::Error,
-};
+// Пример 6: Ссылка и отрицание
+// "$NB and not 9"
+// reference → Expr::Ref { name: "NB", body: <разобранный текст NB> }
+// (текст NB подставляется после разбора, см. `Syntax::resolve`)
+// binary_op(And)
+// not → Expr::Not(Range(9-9))
+
+// Пример 7: Временной оператор
+// "delay(3, 5) or rise(7)"
+// temporal → Expr::Temporal { op: Delay { ms: 5000 }, inner: Range(3-3) }
+// binary_op(Or)
+// temporal → Expr::Temporal { op: Rise, inner: Range(7-7) }
+
+use std::cell::RefCell;
 
 use crate::conditions::ast::*;
-use crate::conditions::error::ParseError;
+use crate::conditions::cst::{is_detector_name, NodeKind};
+use crate::conditions::error::{starts_with_comparison, ParseError};
+use crate::conditions::keywords::{Keywords, DEFAULT_KEYWORDS};
+use crate::conditions::lexer::{tokenize_with, Token, TokenKind};
+use crate::conditions::symbols::SymbolTable;
 
 /// Основная функция для внешнего использования
 pub fn parse_ddr_expression(input: &str) -> Result<Expr, ParseError> {
-    let input = input.trim();
-    
-    match expr_parser().parse(input) {
-        Ok(("", expr)) => Ok(expr),
-        Ok((remaining, _)) => Err(ParseError::ExtraInput(remaining.to_string())),
-        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
-            // Попробуем сделать ошибку понятнее
-            let input_preview = if input.len() > 20 {
-                format!("{}...", &input[..20])
-            } else {
-                input.to_string()
-            };
-            
-            match e.code {
-                nom::error::ErrorKind::Digit => {
-                    Err(ParseError::ExpectedNumber(format!("в '{}'", input_preview)))
-                }
-                nom::error::ErrorKind::Char => {
-                    Err(ParseError::UnexpectedChar(
-                        input.chars().next().unwrap_or('?'), 
-                        0
-                    ))
-                }
-                nom::error::ErrorKind::Tag => {
-                    Err(ParseError::UnknownOperator(input_preview))
-                }
-                _ => {
-                    eprintln!("Ошибка парсинга: {:?}", e);
-                    Err(ParseError::InternalError)
-                }
-            }
+    parse_with(input, &Context::new(None))
+}
+
+/// Разбор условия со ссылками `$ИМЯ` на условия из таблицы имён.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conditions::{parse_ddr_expression_with_symbols, to_ddr_string, SymbolTable};
+///
+/// let mut symbols = SymbolTable::new();
+/// symbols.define("NB", "1-2").unwrap();
+///
+/// let expr = parse_ddr_expression_with_symbols("$NB and not 9", &symbols).unwrap();
+/// assert_eq!(to_ddr_string(&expr), "(ddr(D1) or ddr(D2)) and (not ddr(D9))");
+/// ```
+pub fn parse_ddr_expression_with_symbols(
+    input: &str,
+    symbols: &SymbolTable,
+) -> Result<Expr, ParseError> {
+    parse_with(input, &Context::new(Some(symbols)))
+}
+
+/// Разбор условия с заданным написанием ключевых слов.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conditions::{parse_ddr_expression, parse_ddr_expression_with_keywords, Keywords};
+///
+/// let expr = parse_ddr_expression_with_keywords("2из 1–4 или не 7", &Keywords::russian()).unwrap();
+/// assert_eq!(expr, parse_ddr_expression("2of 1-4 or not 7").unwrap());
+/// ```
+pub fn parse_ddr_expression_with_keywords(
+    input: &str,
+    keywords: &Keywords,
+) -> Result<Expr, ParseError> {
+    parse_with(input, &Context::new(None).with_keywords(keywords))
+}
+
+/// Контекст разбора: таблица имён, написание ключевых слов и цепочка
+/// раскрываемых сейчас условий
+pub(crate) struct Context<'a> {
+    symbols: Option<&'a SymbolTable>,
+    keywords: &'a Keywords,
+    stack: RefCell<Vec<String>>,
+}
+
+impl<'a> Context<'a> {
+    pub(crate) fn new(symbols: Option<&'a SymbolTable>) -> Self {
+        Self {
+            symbols,
+            keywords: &DEFAULT_KEYWORDS,
+            stack: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn with_keywords(self, keywords: &'a Keywords) -> Self {
+        Self { keywords, ..self }
+    }
+
+    /// Разбирает текст условия `name` в Expr::Ref
+    fn resolve(&self, name: &str) -> Result<Expr, ParseError> {
+        let Some(source) = self.symbols.and_then(|symbols| symbols.get(name)) else {
+            // Позицию проставит вызывающий, он знает, где стоит '$'
+            return Err(ParseError::UnknownSymbol { name: name.to_string(), position: 0 });
+        };
+
+        if let Some(start) = self.stack.borrow().iter().position(|n| n == name) {
+            let mut chain = self.stack.borrow()[start..].to_vec();
+            chain.push(name.to_string());
+            return Err(ParseError::CyclicReference(chain));
+        }
+
+        self.stack.borrow_mut().push(name.to_string());
+        let result = parse_with(source, self);
+        self.stack.borrow_mut().pop();
+
+        match result {
+            Ok(body) => Ok(Expr::Ref { name: name.to_string(), body: Box::new(body) }),
+            // Уже привязанные к своему условию ошибки не оборачиваем повторно
+            Err(e @ (ParseError::InDefinition { .. } | ParseError::CyclicReference(_))) => Err(e),
+            Err(e) => Err(ParseError::InDefinition { name: name.to_string(), error: Box::new(e) }),
         }
-        Err(_) => Err(ParseError::InternalError),
     }
 }
 
-/// Парсер числа
-fn number_parser<'a>() -> impl Parser<&'a str, Output = u32, Error = Error<&'a str>> {
-    map(digit1, |s: &str| s.parse().unwrap())
+/// Разбор строки в заданном контексте
+pub(crate) fn parse_with(input: &str, ctx: &Context<'_>) -> Result<Expr, ParseError> {
+    Syntax::parse(input, ctx.keywords).resolve(ctx)
 }
 
-/// Обёртка для игнорирования пробелов
-fn ws<'a, Par>(parser: Par) -> impl Parser<&'a str, Output = Par::Output, Error = Error<&'a str>>
-where
-    Par: Parser<&'a str, Error = Error<&'a str>>,
-{
-    delimited(multispace0, parser, multispace0)
+/// Шаг построения дерева разбора (`SyntaxTree`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Event {
+    /// Начало узла
+    Start(NodeKind),
+    /// Следующий значимый токен; пробелы и комментарии перед ним — в тот же узел
+    Token,
+    /// Конец узла
+    Finish,
 }
 
-/// Парсер оператора внутри диапазона (or/and/|/&) - опционально
-fn range_op_parser<'a>() -> impl Parser<&'a str, Output = Option<RangeOp>, Error = Error<&'a str>> {
-    opt(ws(alt((
-        value(RangeOp::And, alt((tag("and"), tag("&")))),
-        value(RangeOp::Or, alt((tag("or"), tag("|")))),
-    ))))
+/// Результат разбора текста: выражение, в котором ссылки `$ИМЯ` ещё не
+/// раскрыты, и шаги, по которым строится дерево разбора
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Syntax {
+    /// Все токены текста, включая пробелы и комментарии
+    pub(crate) tokens: Vec<Token>,
+    /// Узлы дерева в порядке разбора; при ошибке не полны
+    pub(crate) events: Vec<Event>,
+    /// Выражение или ошибка разбора
+    pub(crate) result: Result<Expr, ParseError>,
+    /// Позиции '$' ссылок в порядке текста
+    references: Vec<usize>,
 }
 
-/// Парсер диапазона: [or/and] число-число
-fn range_parser<'a>() -> impl Parser<&'a str, Output = Range, Error = Error<&'a str>> {
-    move |input: &'a str| {
-        let (input, op) = range_op_parser().parse(input)?;
-        let (input, start) = ws(number_parser()).parse(input)?;
-        let (input, _) = ws(char('-')).parse(input)?;
-        let (input, end) = ws(number_parser()).parse(input)?;
-        
-        Ok((input, Range::new(start, end, op.unwrap_or(RangeOp::Or))))
+impl Syntax {
+    /// Разбирает текст; ссылки `$ИМЯ` не раскрываются
+    pub(crate) fn parse(input: &str, keywords: &Keywords) -> Self {
+        let all = tokenize_with(input, keywords);
+        let tokens = all.iter().filter(|token| !token.kind.is_trivia()).cloned().collect();
+        let mut parser = Parser {
+            source: input,
+            tokens,
+            position: 0,
+            keywords,
+            events: Vec::new(),
+            references: Vec::new(),
+        };
+
+        let result = parser.expr().and_then(|expr| match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(Fail::Error(extra_input_error(input, &input[token.span.start..], keywords))),
+        });
+        let result = result.map_err(|fail| match fail {
+            Fail::Error(error) => error,
+            Fail::Expected { position, number } => {
+                // Комментарии не должны влиять на разбор ошибки: '(' или 'and'
+                // в комментарии — не часть условия
+                let mut text = input.to_string();
+                for token in all.iter().filter(|token| token.kind == TokenKind::Comment) {
+                    text.replace_range(token.span.clone(), &" ".repeat(token.span.len()));
+                }
+                ParseError::expected_at(&text, position, number, keywords)
+            }
+        });
+
+        Self { tokens: all, events: parser.events, result, references: parser.references }
+    }
+
+    /// Выражение с раскрытыми по таблице имён контекста ссылками
+    pub(crate) fn resolve(&self, ctx: &Context<'_>) -> Result<Expr, ParseError> {
+        let expr = self.result.clone()?;
+        resolve_references(expr, &mut self.references.iter().copied(), ctx)
     }
 }
 
-/// Парсер выражения в скобках
-fn parens_parser<'a>() -> impl Parser<&'a str, Output = Expr, Error = Error<&'a str>> {
-    move |input: &'a str| {
-        let (input, _) = ws(char('(')).parse(input)?;
-        let (input, expr) = expr_parser().parse(input)?;
-        let (input, _) = ws(char(')')).parse(input)?;
-        
-        Ok((input, expr))
+/// Подставляет тела ссылок; `positions` — места их '$' в порядке текста,
+/// в том же порядке ссылки встречаются при обходе слева направо
+fn resolve_references(
+    expr: Expr,
+    positions: &mut impl Iterator<Item = usize>,
+    ctx: &Context<'_>,
+) -> Result<Expr, ParseError> {
+    Ok(match expr {
+        Expr::Ref { name, .. } => {
+            let position = positions.next().unwrap_or_default();
+            ctx.resolve(&name).map_err(|error| match error {
+                ParseError::UnknownSymbol { name, .. } => ParseError::UnknownSymbol { name, position },
+                other => other,
+            })?
+        }
+        Expr::Not(inner) => Expr::Not(Box::new(resolve_references(*inner, positions, ctx)?)),
+        Expr::Temporal { op, inner } => {
+            Expr::Temporal { op, inner: Box::new(resolve_references(*inner, positions, ctx)?) }
+        }
+        Expr::Binary { op, left, right } => {
+            let left = resolve_references(*left, positions, ctx)?;
+            let right = resolve_references(*right, positions, ctx)?;
+            Expr::Binary { op, left: Box::new(left), right: Box::new(right) }
+        }
+        other => other,
+    })
+}
+
+/// Ошибка для хвоста, который остался после разобранного выражения
+fn extra_input_error(input: &str, remaining: &str, keywords: &Keywords) -> ParseError {
+    let rest = tokenize_with(remaining, keywords)
+        .iter()
+        .take_while(|token| token.kind != TokenKind::Comment)
+        .last()
+        .map_or("", |token| remaining[..token.span.end].trim_end());
+    let position = input.len() - remaining.len();
+    let word: String = rest.chars().take_while(|c| c.is_alphanumeric()).collect();
+
+    if starts_with_comparison(rest) {
+        ParseError::BooleanComparison { position }
+    } else if rest.starts_with(char::is_alphabetic) {
+        ParseError::UnknownOperator { word, position }
+    } else {
+        ParseError::ExtraInput { rest: rest.to_string(), position }
     }
 }
 
-/// Парсер бинарного оператора (and/or/&/|)
-fn binary_op_parser<'a>() -> impl Parser<&'a str, Output = BinaryOp, Error = Error<&'a str>> {
-    ws(alt((
-        value(BinaryOp::And, alt((tag("and"), tag("&")))),
-        value(BinaryOp::Or, alt((tag("or"), tag("|")))),
-    )))
+/// Причина отказа разбора
+enum Fail {
+    /// На `position` не нашлось ожидаемого: числа (`number`) или знака;
+    /// что именно там не так, решает `ParseError::expected_at`
+    Expected { position: usize, number: bool },
+    /// Ошибка уже известна точно
+    Error(ParseError),
 }
 
-/// Парсер атомарного выражения (скобки или диапазон)
-fn atom_parser<'a>() -> impl Parser<&'a str, Output = Expr, Error = Error<&'a str>> {
-    alt((
-        parens_parser(),
-        map(range_parser(), Expr::Range),
-    ))
+type Parsed<T> = Result<T, Fail>;
+
+/// Разбор по значимым токенам (без пробелов и комментариев)
+struct Parser<'s, 'k> {
+    source: &'s str,
+    tokens: Vec<Token>,
+    position: usize,
+    keywords: &'k Keywords,
+    /// Шаги построения дерева разбора
+    events: Vec<Event>,
+    /// Позиции '$' ссылок
+    references: Vec<usize>,
 }
 
-/// Парсер выражения (с левой ассоциативностью)
-fn expr_parser<'a>() -> impl Parser<&'a str, Output = Expr, Error = Error<&'a str>> {
-    move |mut input: &'a str| {
-        let (rest, mut left) = atom_parser().parse(input)?;
-        input = rest;
-        
-        loop {
-            match binary_op_parser().parse(input) {
-                Ok((rest, op)) => {
-                    let (rest, right) = atom_parser().parse(rest)?;
-                    left = Expr::Binary {
-                        op,
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    };
-                    input = rest;
+impl<'s> Parser<'s, '_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Вид и текст `n`-го токена впереди
+    fn peek_nth(&self, n: usize) -> Option<(TokenKind, &'s str)> {
+        self.tokens.get(self.position + n).map(|token| (token.kind, self.word(token)))
+    }
+
+    /// Текст токена; ключевое слово — в каноническом написании (`ИЛИ` → `or`)
+    fn word(&self, token: &Token) -> &'s str {
+        let text = token.text(self.source);
+        match token.kind {
+            TokenKind::Keyword => self.keywords.canonical(text).unwrap_or(text),
+            _ => text,
+        }
+    }
+
+    fn at(&self, kind: TokenKind, texts: &[&str]) -> bool {
+        self.peek_nth(0).is_some_and(|(k, text)| k == kind && (texts.is_empty() || texts.contains(&text)))
+    }
+
+    /// Байтовая позиция следующего токена (конец строки, если токенов нет)
+    fn offset(&self) -> usize {
+        self.peek().map_or(self.source.len(), |token| token.span.start)
+    }
+
+    fn bump(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        self.position += 1;
+        self.events.push(Event::Token);
+        token
+    }
+
+    fn start(&mut self, kind: NodeKind) {
+        self.events.push(Event::Start(kind));
+    }
+
+    fn finish(&mut self) {
+        self.events.push(Event::Finish);
+    }
+
+    /// Отказ на следующем токене: ожидалось число
+    fn expected_number(&self) -> Fail {
+        Fail::Expected { position: self.offset(), number: true }
+    }
+
+    /// Следующий токен должен быть `kind` (с одним из `texts`)
+    fn expect(&mut self, kind: TokenKind, texts: &[&str]) -> Parsed<Token> {
+        if !self.at(kind, texts) {
+            return Err(Fail::Expected { position: self.offset(), number: false });
+        }
+        Ok(self.bump())
+    }
+
+    /// Целое число: номер детектора или счёт
+    fn integer(&mut self) -> Parsed<u32> {
+        match self.peek_nth(0) {
+            Some((TokenKind::Number, text)) => match text.parse() {
+                Ok(number) => {
+                    self.bump();
+                    Ok(number)
                 }
-                Err(nom::Err::Error(_)) => break,
-                Err(e) => return Err(e),
+                Err(_) => Err(self.expected_number()),
+            },
+            _ => Err(self.expected_number()),
+        }
+    }
+
+    /// Неотрицательное число с дробной частью: "60", "2.5"
+    fn decimal(&mut self) -> Parsed<f64> {
+        match self.peek_nth(0) {
+            Some((TokenKind::Number, text)) => {
+                self.bump();
+                Ok(text.parse().unwrap())
             }
+            _ => Err(self.expected_number()),
+        }
+    }
+
+    /// Длительность в секундах ("5", "2.5") → миллисекунды; нулевая или
+    /// не помещающаяся в u64 — ошибка
+    fn duration(&mut self) -> Parsed<u64> {
+        let (position, value) = (self.offset(), self.peek_nth(0).map_or("", |(_, text)| text));
+        let ms = (self.decimal()? * 1000.0).round();
+        // u64::MAX as f64 = 2^64, всё меньше помещается в u64
+        if ms < 1.0 || ms >= u64::MAX as f64 {
+            return Err(Fail::Error(ParseError::InvalidDuration { value: value.to_string(), position }));
+        }
+        Ok(ms as u64)
+    }
+
+    /// Выражение (с левой ассоциативностью)
+    fn expr(&mut self) -> Parsed<Expr> {
+        let start = self.events.len();
+        let mut left = self.atom()?;
+        while let Some(op) = self.binary_op() {
+            // Левый операнд становится первым ребёнком узла Binary
+            self.events.insert(start, Event::Start(NodeKind::Binary));
+            let right = self.atom()?;
+            self.finish();
+            left = Expr::Binary { op, left: Box::new(left), right: Box::new(right) };
         }
-        
-        Ok((input, left))
+        Ok(left)
+    }
+
+    /// Бинарный оператор (and/or/&/| и их написания из `Keywords`)
+    fn binary_op(&mut self) -> Option<BinaryOp> {
+        let op = if self.at(TokenKind::Keyword, &["and"]) || self.at(TokenKind::Symbol, &["&"]) {
+            BinaryOp::And
+        } else if self.at(TokenKind::Keyword, &["or"]) || self.at(TokenKind::Symbol, &["|"]) {
+            BinaryOp::Or
+        } else {
+            return None;
+        };
+        self.bump();
+        Some(op)
+    }
+
+    /// Атомарное выражение: скобки, отрицание, временной оператор,
+    /// сравнение, ссылка или диапазон
+    fn atom(&mut self) -> Parsed<Expr> {
+        let called = matches!(self.peek_nth(1), Some((TokenKind::LParen, _)));
+        match self.peek_nth(0) {
+            Some((TokenKind::LParen, _)) => self.parens(),
+            Some((TokenKind::Keyword, "not") | (TokenKind::Symbol, "!")) => self.not(),
+            Some((TokenKind::Keyword, "delay" | "hold" | "rise" | "fall")) if called => self.temporal(),
+            Some((TokenKind::Keyword, "occ" | "cnt")) if called => self.comparison(),
+            Some((TokenKind::Symbol, "$")) => self.reference(),
+            _ => self.range(),
+        }
+    }
+
+    /// Выражение в скобках
+    fn parens(&mut self) -> Parsed<Expr> {
+        self.start(NodeKind::Paren);
+        self.bump();
+        let expr = self.expr()?;
+        self.expect(TokenKind::RParen, &[])?;
+        self.finish();
+        Ok(expr)
+    }
+
+    /// Отрицание: not/! перед атомом
+    fn not(&mut self) -> Parsed<Expr> {
+        self.start(NodeKind::Not);
+        self.bump();
+        let inner = self.atom()?;
+        self.finish();
+        Ok(Expr::Not(Box::new(inner)))
+    }
+
+    /// Временной оператор: delay(выражение, с), hold(выражение, с),
+    /// rise(выражение), fall(выражение)
+    fn temporal(&mut self) -> Parsed<Expr> {
+        self.start(NodeKind::Temporal);
+        let keyword = self.bump();
+        let keyword = self.word(&keyword);
+        self.bump();
+        let inner = self.expr()?;
+
+        let op = match keyword {
+            "delay" | "hold" => {
+                self.expect(TokenKind::Symbol, &[","])?;
+                let ms = self.duration()?;
+                if keyword == "delay" { TemporalOp::Delay { ms } } else { TemporalOp::Hold { ms } }
+            }
+            "rise" => TemporalOp::Rise,
+            _ => TemporalOp::Fall,
+        };
+        self.expect(TokenKind::RParen, &[])?;
+        self.finish();
+
+        Ok(Expr::Temporal { op, inner: Box::new(inner) })
+    }
+
+    /// Сравнение измерения: occ(D3) > 60, cnt(1) >= 10
+    fn comparison(&mut self) -> Parsed<Expr> {
+        self.start(NodeKind::Comparison);
+        let keyword = self.bump();
+        let measure = if self.word(&keyword) == "occ" { Measure::Occupancy } else { Measure::Count };
+        self.bump();
+        let detector = match self.peek_nth(0) {
+            Some((TokenKind::Ident, name)) if is_detector_name(name) => {
+                self.bump();
+                name[1..].parse().map_err(|_| self.expected_number())?
+            }
+            _ => self.integer()?,
+        };
+        let close = self.expect(TokenKind::RParen, &[])?;
+
+        let op = match self.peek_nth(0) {
+            Some((TokenKind::Symbol, ">=")) => CompareOp::Ge,
+            Some((TokenKind::Symbol, "<=")) => CompareOp::Le,
+            Some((TokenKind::Symbol, "!=" | "<>")) => CompareOp::Ne,
+            Some((TokenKind::Symbol, ">")) => CompareOp::Gt,
+            Some((TokenKind::Symbol, "<")) => CompareOp::Lt,
+            Some((TokenKind::Symbol, "==" | "=")) => CompareOp::Eq,
+            _ => {
+                return Err(Fail::Error(ParseError::MissingComparison {
+                    measure: self.source[keyword.span.start..close.span.end].to_string(),
+                    position: keyword.span.start,
+                }));
+            }
+        };
+        self.bump();
+        let value = self.decimal()?;
+        self.finish();
+
+        Ok(Expr::Compare(Comparison { measure, detector, op, value }))
+    }
+
+    /// Ссылка на именованное условие: $ИМЯ
+    fn reference(&mut self) -> Parsed<Expr> {
+        self.start(NodeKind::Reference);
+        let dollar = self.bump();
+        // Имя пишется вплотную к '$'
+        let name = match self.peek() {
+            Some(token)
+                if matches!(token.kind, TokenKind::Ident | TokenKind::Keyword)
+                    && token.span.start == dollar.span.end =>
+            {
+                self.bump().text(self.source)
+            }
+            _ => return Err(Fail::Expected { position: dollar.span.end, number: false }),
+        };
+
+        self.finish();
+
+        // Тело подставит `Syntax::resolve`: таблица имён разбору не нужна
+        self.references.push(dollar.span.start);
+        let body = Box::new(Expr::Range(Range::new(0, 0, RangeOp::Or)));
+        Ok(Expr::Ref { name: name.to_string(), body })
+    }
+
+    /// Диапазон: [оператор] число-число или одиночный номер. Начало не
+    /// больше конца, детекторов не больше `MAX_RANGE_SIZE`; счёт должен
+    /// быть выполним: не меньше 1..=N или ровно 0..=N из N детекторов
+    fn range(&mut self) -> Parsed<Expr> {
+        self.start(NodeKind::Range);
+        let position = self.offset();
+        let op = self.range_op()?;
+        let numbers = self.offset();
+        let start = self.integer()?;
+        // После '-' конец диапазона обязателен
+        let end = if self.at(TokenKind::Dash, &[]) {
+            self.bump();
+            self.integer()?
+        } else {
+            start
+        };
+        if start > end {
+            return Err(Fail::Error(ParseError::ReversedRange { start, end, position: numbers }));
+        }
+        if u64::from(end) - u64::from(start) >= u64::from(MAX_RANGE_SIZE) {
+            let max = MAX_RANGE_SIZE;
+            return Err(Fail::Error(ParseError::RangeTooLarge { start, end, max, position: numbers }));
+        }
+        let range = Range::new(start, end, op.unwrap_or(RangeOp::Or));
+
+        let size = range.detector_count();
+        let count = match range.operator {
+            RangeOp::AtLeast(count) if count == 0 || count > size => count,
+            RangeOp::Exactly(count) if count > size => count,
+            _ => {
+                self.finish();
+                return Ok(Expr::Range(range));
+            }
+        };
+        Err(Fail::Error(ParseError::InvalidCount { count, size, position }))
+    }
+
+    /// Оператор внутри диапазона (or/and/|/&, Nof, =Nof и их написания
+    /// из `Keywords`) - необязательный
+    fn range_op(&mut self) -> Parsed<Option<RangeOp>> {
+        if self.at(TokenKind::Keyword, &["and"]) || self.at(TokenKind::Symbol, &["&"]) {
+            self.bump();
+            return Ok(Some(RangeOp::And));
+        }
+        if self.at(TokenKind::Keyword, &["or"]) || self.at(TokenKind::Symbol, &["|"]) {
+            self.bump();
+            return Ok(Some(RangeOp::Or));
+        }
+
+        // Счёт: "2of" — не меньше 2, "=2of" — ровно 2; число пишется вплотную к '='
+        let exact = self.at(TokenKind::Symbol, &["="])
+            && self.tokens.get(self.position + 1).is_some_and(|number| {
+                number.kind == TokenKind::Number && number.span.start == self.tokens[self.position].span.end
+            });
+        let count_at = usize::from(exact);
+        let counted = matches!(self.peek_nth(count_at), Some((TokenKind::Number, _)))
+            && matches!(self.peek_nth(count_at + 1), Some((TokenKind::Keyword, "of")));
+        if !counted {
+            return Ok(None);
+        }
+
+        if exact {
+            self.bump();
+        }
+        let count = self.integer()?;
+        self.bump();
+        Ok(Some(if exact { RangeOp::Exactly(count) } else { RangeOp::AtLeast(count) }))
     }
 }
 
@@ -166,21 +585,44 @@ mod tests {
     
     #[test]
     fn test_range() {
-        let (_, range) = range_parser().parse("1-3").unwrap();
-        assert_eq!(range.start, 1);
-        assert_eq!(range.end, 3);
-        assert_eq!(range.operator, RangeOp::Or);
-        
-        let (_, range) = range_parser().parse("or 1-3").unwrap();
-        assert_eq!(range.operator, RangeOp::Or);
-        
-        let (_, range) = range_parser().parse("and 4-6").unwrap();
-        assert_eq!(range.operator, RangeOp::And);
+        assert_eq!(parse_ddr_expression("1-3"), Ok(Expr::Range(Range::new(1, 3, RangeOp::Or))));
+        assert_eq!(parse_ddr_expression("or 1-3"), Ok(Expr::Range(Range::new(1, 3, RangeOp::Or))));
+        assert_eq!(parse_ddr_expression("and 4-6"), Ok(Expr::Range(Range::new(4, 6, RangeOp::And))));
+
+        assert_eq!(
+            parse_ddr_expression("1 or and 5-1"),
+            Err(ParseError::ReversedRange { start: 5, end: 1, position: 9 })
+        );
+        assert!(parse_ddr_expression("or 1-1024").is_ok());
+        assert_eq!(
+            parse_ddr_expression("or 1-4000000000").unwrap_err().to_string(),
+            "Диапазон 1-4000000000 на позиции 3 слишком большой: допустимо не больше 1024 детекторов"
+        );
+        assert!(parse_ddr_expression("0-4294967295").is_err());
+    }
+
+    #[test]
+    fn test_counting() {
+        assert_eq!(parse_ddr_expression("2of 1-4"), Ok(Expr::Range(Range::new(1, 4, RangeOp::AtLeast(2)))));
+        assert_eq!(parse_ddr_expression("=1 of 5-7"), Ok(Expr::Range(Range::new(5, 7, RangeOp::Exactly(1)))));
+
+        let expr = parse_ddr_expression("(=0of 1-2) or 3of 4-6").unwrap();
+        assert_eq!(expr.to_string(), "(=0of 1-2) or (3of 4-6)");
+        assert_eq!(parse_ddr_expression(&expr.to_string()), Ok(expr));
+
+        assert_eq!(
+            parse_ddr_expression("1 or 5of 1-4"),
+            Err(ParseError::InvalidCount { count: 5, size: 4, position: 5 })
+        );
+        assert_eq!(
+            parse_ddr_expression("0of 1-4").unwrap_err().to_string(),
+            "Нельзя требовать 0 из 4 детекторов на позиции 0"
+        );
     }
     
     #[test]
     fn test_parens() {
-        let (_, expr) = parens_parser().parse("(1-3)").unwrap();
+        let expr = parse_ddr_expression("(1-3)").unwrap();
         match expr {
             Expr::Range(range) => {
                 assert_eq!(range.start, 1);
@@ -192,7 +634,7 @@ mod tests {
     
     #[test]
     fn test_binary() {
-        let (_, expr) = expr_parser().parse("(1-3) and (4-6)").unwrap();
+        let expr = parse_ddr_expression("(1-3) and (4-6)").unwrap();
         match expr {
             Expr::Binary { op, left, right } => {
                 assert_eq!(op, BinaryOp::And);
@@ -217,7 +659,7 @@ mod tests {
     
     #[test]
     fn test_chain() {
-        let (_, expr) = expr_parser().parse("(1-3) and (4-6) or (7-9)").unwrap();
+        let expr = parse_ddr_expression("(1-3) and (4-6) or (7-9)").unwrap();
         match expr {
             Expr::Binary { op, left, right } => {
                 assert_eq!(op, BinaryOp::Or);
@@ -238,4 +680,143 @@ mod tests {
             _ => panic!("Expected OR at top level"),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_temporal() {
+        let expr = parse_ddr_expression("delay(or 1-2, 2.5) and not rise(7)").unwrap();
+        assert_eq!(expr.to_string(), "(delay(or 1-2, 2.5)) and (not rise(7))");
+        assert_eq!(parse_ddr_expression(&expr.to_string()), Ok(expr));
+
+        assert_eq!(
+            parse_ddr_expression("hold((1-3) and 4, 10)").unwrap(),
+            Expr::Temporal {
+                op: TemporalOp::Hold { ms: 10_000 },
+                inner: Box::new(parse_ddr_expression("(1-3) and 4").unwrap()),
+            }
+        );
+        assert_eq!(
+            parse_ddr_expression("delay(3)"),
+            Err(ParseError::UnexpectedChar(')', 7))
+        );
+        assert_eq!(
+            parse_ddr_expression("delay(3, x)"),
+            Err(ParseError::UnknownOperator { word: "x".to_string(), position: 9 })
+        );
+        assert_eq!(
+            parse_ddr_expression("delay(1-3, 99999999999999999)"),
+            Err(ParseError::InvalidDuration { value: "99999999999999999".to_string(), position: 11 })
+        );
+        assert_eq!(
+            parse_ddr_expression("hold(2, 0)"),
+            Err(ParseError::InvalidDuration { value: "0".to_string(), position: 8 })
+        );
+        assert_eq!(
+            parse_ddr_expression("delay(2, 0.0004)"),
+            Err(ParseError::InvalidDuration { value: "0.0004".to_string(), position: 9 })
+        );
+        assert_eq!(
+            parse_ddr_expression("delay(2, 0.001)").unwrap(),
+            Expr::Temporal {
+                op: TemporalOp::Delay { ms: 1 },
+                inner: Box::new(parse_ddr_expression("2").unwrap()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_comparison() {
+        let expr = parse_ddr_expression("occ(D3) > 60 and (cnt(1)>=10.5 or 4)").unwrap();
+        assert_eq!(expr.to_string(), "(occ(D3) > 60) and ((cnt(D1) >= 10.5) or (4))");
+        assert_eq!(parse_ddr_expression(&expr.to_string()), Ok(expr));
+        assert_eq!(
+            parse_ddr_expression("cnt(D7) <> 0"),
+            Ok(Expr::Compare(Comparison {
+                measure: Measure::Count,
+                detector: 7,
+                op: CompareOp::Ne,
+                value: 0.0,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_mixed_types() {
+        assert_eq!(
+            parse_ddr_expression("1 and not occ(D3)"),
+            Err(ParseError::MissingComparison { measure: "occ(D3)".to_string(), position: 10 })
+        );
+        assert_eq!(parse_ddr_expression("3 > 60"), Err(ParseError::BooleanComparison { position: 2 }));
+        assert_eq!(
+            parse_ddr_expression("((1-3) >= 2)"),
+            Err(ParseError::BooleanComparison { position: 7 })
+        );
+        assert_eq!(
+            parse_ddr_expression("occ(D3) > 60 > 5"),
+            Err(ParseError::BooleanComparison { position: 13 })
+        );
+    }
+
+    #[test]
+    fn test_error_positions() {
+        assert_eq!(parse_ddr_expression("(1-3"), Err(ParseError::UnclosedParen(0)));
+        assert_eq!(
+            parse_ddr_expression("1-3 and"),
+            Err(ParseError::MissingOperand { op: "and".to_string(), position: 4 })
+        );
+        assert_eq!(
+            parse_ddr_expression("(1-3) xor (4-6)"),
+            Err(ParseError::UnknownOperator { word: "xor".to_string(), position: 6 })
+        );
+        assert_eq!(
+            parse_ddr_expression("1-3)"),
+            Err(ParseError::ExtraInput { rest: ")".to_string(), position: 3 })
+        );
+        assert_eq!(
+            parse_ddr_expression("(1-3) and (4-x)"),
+            Err(ParseError::UnknownOperator { word: "x".to_string(), position: 13 })
+        );
+        assert_eq!(
+            parse_ddr_expression("  1-"),
+            Err(ParseError::ExpectedNumber { found: "конец строки".to_string(), position: 4 })
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        assert_eq!(
+            parse_ddr_expression("(1-3) # левый ряд\nand 4 # (и правый"),
+            parse_ddr_expression("(1-3) and 4")
+        );
+        assert_eq!(
+            parse_ddr_expression("1 and # 2\n"),
+            Err(ParseError::MissingOperand { op: "and".to_string(), position: 2 })
+        );
+        assert_eq!(
+            parse_ddr_expression("1 ) # хвост"),
+            Err(ParseError::ExtraInput { rest: ")".to_string(), position: 2 })
+        );
+    }
+
+    #[test]
+    fn test_keywords() {
+        assert_eq!(
+            parse_ddr_expression("(ИЛИ 1–3) И НЕ =2из 4-6 OR Delay(7, 1)"),
+            parse_ddr_expression("(or 1-3) and not =2of 4-6 or delay(7, 1)")
+        );
+        assert_eq!(
+            parse_ddr_expression("1 или"),
+            Err(ParseError::MissingOperand { op: "или".to_string(), position: 2 })
+        );
+
+        let strict = Keywords::english();
+        assert_eq!(parse_ddr_expression_with_keywords("1-3 and 4", &strict), parse_ddr_expression("1-3 and 4"));
+        assert_eq!(
+            parse_ddr_expression_with_keywords("1 AND 2", &strict),
+            Err(ParseError::UnknownOperator { word: "AND".to_string(), position: 2 })
+        );
+        assert_eq!(
+            parse_ddr_expression_with_keywords("1–3", &strict),
+            Err(ParseError::ExtraInput { rest: "–3".to_string(), position: 1 })
+        );
+    }
+}
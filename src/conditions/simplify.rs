@@ -0,0 +1,241 @@
+//! Упрощение DDR-выражений
+//!
+//! Приводит выражение к более короткому эквивалентному виду:
+//! - раскрывает вложенные цепочки одного оператора: (a or b) or c → a or b or c;
+//! - склеивает соседние и пересекающиеся диапазоны: (or 1-3) or (or 4-6) → or 1-6;
+//! - убирает повторы и поглощённые операнды: a or (a and b) → a;
+//! - снимает двойное отрицание: not not a → a.
+//!
+//! Ссылки `$ИМЯ` при упрощении раскрываются.
+
+use crate::conditions::ast::*;
+
+/// Возвращает упрощённое выражение, эквивалентное исходному.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conditions::{parse_ddr_expression, simplify};
+///
+/// let expr = parse_ddr_expression("(or 1-3) or (or 4-6) or (and 2-5)").unwrap();
+/// assert_eq!(simplify(&expr).to_string(), "or 1-6");
+/// ```
+pub fn simplify(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Range(range) => Expr::Range(normalize(range.clone())),
+        Expr::Ref { body, .. } => simplify(body),
+        Expr::Compare(comparison) => Expr::Compare(comparison.clone()),
+        Expr::Temporal { op, inner } => Expr::Temporal { op: *op, inner: Box::new(simplify(inner)) },
+        Expr::Not(inner) => match simplify(inner) {
+            Expr::Not(double) => *double,
+            inner => Expr::Not(Box::new(inner)),
+        },
+        Expr::Binary { op, .. } => {
+            let mut operands = Vec::new();
+            flatten(expr, *op, &mut operands);
+            let operands = merge_ranges(operands, *op);
+            let operands = absorb(operands, *op);
+            rebuild(operands, *op)
+        }
+    }
+}
+
+/// Диапазон из одного детектора всегда записываем как or; счёт,
+/// совпадающий с or или and, заменяем на них
+fn normalize(mut range: Range) -> Range {
+    range.operator = match range.operator {
+        RangeOp::AtLeast(1) => RangeOp::Or,
+        RangeOp::AtLeast(n) if n == range.detector_count() => RangeOp::And,
+        operator => operator,
+    };
+    if range.start == range.end && range.operator == RangeOp::And {
+        range.operator = RangeOp::Or;
+    }
+    range
+}
+
+/// Собирает операнды цепочки оператора `op`, упрощая их по пути
+fn flatten(expr: &Expr, op: BinaryOp, out: &mut Vec<Expr>) {
+    match expr {
+        Expr::Binary { op: inner, left, right } if *inner == op => {
+            flatten(left, op, out);
+            flatten(right, op, out);
+        }
+        _ => match simplify(expr) {
+            // Упрощение могло дать цепочку того же оператора — раскрываем и её
+            simplified @ Expr::Binary { op: inner, .. } if inner == op => {
+                collect_chain(simplified, op, out)
+            }
+            simplified => out.push(simplified),
+        },
+    }
+}
+
+fn collect_chain(expr: Expr, op: BinaryOp, out: &mut Vec<Expr>) {
+    match expr {
+        Expr::Binary { op: inner, left, right } if inner == op => {
+            collect_chain(*left, op, out);
+            collect_chain(*right, op, out);
+        }
+        other => out.push(other),
+    }
+}
+
+/// Оператор диапазона, совпадающий по смыслу с оператором цепочки
+fn chain_range_op(op: BinaryOp) -> RangeOp {
+    match op {
+        BinaryOp::Or => RangeOp::Or,
+        BinaryOp::And => RangeOp::And,
+    }
+}
+
+/// Склеивает диапазоны, которые можно объединить внутри цепочки
+fn merge_ranges(operands: Vec<Expr>, op: BinaryOp) -> Vec<Expr> {
+    let target = chain_range_op(op);
+    let mut mergeable = Vec::new();
+    let mut rest = Vec::new();
+
+    for operand in operands {
+        match operand {
+            Expr::Range(range)
+                if range.start <= range.end
+                    && (range.operator == target
+                        || (range.start == range.end && matches!(range.operator, RangeOp::Or | RangeOp::And))) =>
+            {
+                mergeable.push(range)
+            }
+            other => rest.push(other),
+        }
+    }
+
+    mergeable.sort_by_key(|range| (range.start, range.end));
+    let mut merged: Vec<Range> = Vec::new();
+    for range in mergeable {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end.saturating_add(1) => {
+                last.end = last.end.max(range.end);
+                last.operator = target;
+            }
+            _ => merged.push(range),
+        }
+    }
+
+    merged
+        .into_iter()
+        .map(|range| Expr::Range(normalize(range)))
+        .chain(rest)
+        .collect()
+}
+
+/// Убирает повторы и операнды, поглощённые другими:
+/// в цепочке or лишний тот, кто влечёт другого; в цепочке and — тот, кого влечёт другой
+fn absorb(operands: Vec<Expr>, op: BinaryOp) -> Vec<Expr> {
+    // redundant(a, b): a не нужен, если в цепочке есть b
+    let redundant = |a: &Expr, b: &Expr| match op {
+        BinaryOp::Or => implies(a, b),
+        BinaryOp::And => implies(b, a),
+    };
+
+    let mut kept: Vec<Expr> = Vec::new();
+    for operand in operands {
+        if kept.iter().any(|k| redundant(&operand, k)) {
+            continue;
+        }
+        kept.retain(|k| !redundant(k, &operand));
+        kept.push(operand);
+    }
+    kept
+}
+
+/// Достаточное (но не необходимое) условие того, что из `a` следует `b`
+fn implies(a: &Expr, b: &Expr) -> bool {
+    if a == b {
+        return true;
+    }
+
+    match (a, b) {
+        (Expr::Range(a), Expr::Range(b)) => range_implies(a, b),
+        (Expr::Binary { op: BinaryOp::Or, left, right }, _) => {
+            implies(left, b) && implies(right, b)
+        }
+        (_, Expr::Binary { op: BinaryOp::And, left, right }) => {
+            implies(a, left) && implies(a, right)
+        }
+        (Expr::Binary { op: BinaryOp::And, left, right }, _) => {
+            implies(left, b) || implies(right, b)
+        }
+        (_, Expr::Binary { op: BinaryOp::Or, left, right }) => {
+            implies(a, left) || implies(a, right)
+        }
+        (Expr::Ref { body, .. }, _) => implies(body, b),
+        (_, Expr::Ref { body, .. }) => implies(a, body),
+        // not a → not b, если b → a
+        (Expr::Not(a), Expr::Not(b)) => implies(b, a),
+        _ => false,
+    }
+}
+
+fn range_implies(a: &Range, b: &Range) -> bool {
+    if a.start > a.end || b.start > b.end {
+        return false;
+    }
+    let a_in_b = b.start <= a.start && a.end <= b.end;
+    let b_in_a = a.start <= b.start && b.end <= a.end;
+    let overlap = a.start <= b.end && b.start <= a.end;
+
+    // Счёт (кроме сведённого к or/and в normalize) здесь не сравниваем
+    let single = |r: &Range| r.start == r.end && matches!(r.operator, RangeOp::Or | RangeOp::And);
+    let is_and = |r: &Range| r.operator == RangeOp::And || single(r);
+    let is_or = |r: &Range| r.operator == RangeOp::Or || single(r);
+
+    (is_or(a) && is_or(b) && a_in_b)
+        || (is_and(a) && is_and(b) && b_in_a)
+        || (is_and(a) && is_or(b) && overlap)
+}
+
+/// Собирает цепочку обратно с левой ассоциативностью, как это делает парсер
+fn rebuild(operands: Vec<Expr>, op: BinaryOp) -> Expr {
+    let mut iter = operands.into_iter();
+    let first = iter.next().expect("в цепочке есть хотя бы один операнд");
+    iter.fold(first, |left, right| Expr::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::parse_ddr_expression;
+
+    fn simplified(input: &str) -> String {
+        simplify(&parse_ddr_expression(input).unwrap()).to_string()
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        assert_eq!(simplified("(1-3) or (4-6)"), "or 1-6");
+        assert_eq!(simplified("(and 1-3) and (and 3-5)"), "and 1-5");
+        assert_eq!(simplified("(1-2) or (5-6)"), "(or 1-2) or (or 5-6)");
+        assert_eq!(simplified("(and 1-1) and (and 2-3)"), "and 1-3");
+        assert_eq!(simplified("not not (3-3)"), "3");
+        assert_eq!(simplified("(1of 1-3) or (4-5)"), "or 1-5");
+        assert_eq!(simplified("(3of 1-3) and (=0of 4-4)"), "(and 1-3) and (=0of 4-4)");
+    }
+
+    #[test]
+    fn test_absorption() {
+        assert_eq!(simplified("(or 1-5) and (or 2-3)"), "or 2-3");
+        assert_eq!(simplified("(1-2) or ((1-2) and (5-6))"), "or 1-2");
+        assert_eq!(simplified("(and 1-3) or (and 2-3)"), "and 2-3");
+        assert_eq!(simplified("((5-6) and (8-9)) or ((5-6) and (8-9))"), "(or 5-6) and (or 8-9)");
+    }
+
+    #[test]
+    fn test_nested_chains() {
+        assert_eq!(
+            simplified("((1-2) and (7-8)) and ((3-4) and (7-8))"),
+            "((or 1-2) and (or 7-8)) and (or 3-4)"
+        );
+    }
+}
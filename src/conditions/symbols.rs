@@ -0,0 +1,211 @@
+//! Таблица именованных условий
+//!
+//! На больших перекрёстках одно и то же подусловие ("все петли северного
+//! подхода") повторяется в десятках мест. Его можно определить один раз:
+//!
+//! ```text
+//! NB = 1-4
+//! NB_CLEAR = $NB and not 9
+//! ```
+//!
+//! Таблица хранит исходный текст определений, а разбирает его при
+//! ссылке `$ИМЯ`. Поэтому порядок определений не важен, а циклы
+//! (`A = $B`, `B = $A`) обнаруживаются при разборе.
+
+use std::collections::BTreeMap;
+
+use crate::conditions::ast::Expr;
+use crate::conditions::error::ParseError;
+use crate::conditions::parser::{parse_with, Context};
+
+/// Именованные условия: имя → исходный текст
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    definitions: BTreeMap<String, String>,
+}
+
+impl SymbolTable {
+    /// Пустая таблица
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Определяет (или переопределяет) условие `name`.
+    ///
+    /// Текст условия проверяется только при разборе ссылки на него.
+    pub fn define(&mut self, name: &str, source: &str) -> Result<(), ParseError> {
+        let name = name.trim();
+        if !is_valid_name(name) {
+            return Err(ParseError::InvalidName(name.to_string()));
+        }
+        self.definitions.insert(name.to_string(), source.trim().to_string());
+        Ok(())
+    }
+
+    /// Разбирает строку определения `ИМЯ = условие` и добавляет его в таблицу.
+    /// Возвращает имя определённого условия.
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::conditions::SymbolTable;
+    ///
+    /// let mut symbols = SymbolTable::new();
+    /// assert_eq!(symbols.define_line("NB = 1-4").unwrap(), "NB");
+    /// assert_eq!(symbols.get("NB"), Some("1-4"));
+    /// ```
+    pub fn define_line(&mut self, line: &str) -> Result<String, ParseError> {
+        let (name, source) = split_definition(line)
+            .ok_or_else(|| ParseError::InvalidDefinition(line.trim().to_string()))?;
+        self.define(name, source)?;
+        Ok(name.trim().to_string())
+    }
+
+    /// Похожа ли строка на определение `ИМЯ = условие`, а не на условие
+    /// со сравнением (`cnt(D1) >= 10`, `occ(D3) == 5`)
+    pub fn is_definition(line: &str) -> bool {
+        split_definition(line).is_some()
+    }
+
+    /// Удаляет условие, возвращая его текст
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.definitions.remove(name)
+    }
+
+    /// Исходный текст условия
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.definitions.get(name).map(String::as_str)
+    }
+
+    /// Есть ли условие с таким именем
+    pub fn contains(&self, name: &str) -> bool {
+        self.definitions.contains_key(name)
+    }
+
+    /// Определения в порядке имён
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.definitions
+            .iter()
+            .map(|(name, source)| (name.as_str(), source.as_str()))
+    }
+
+    /// Количество определений
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    /// Пуста ли таблица
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// Разбирает условие `name` со всеми вложенными ссылками
+    pub fn resolve(&self, name: &str) -> Result<Expr, ParseError> {
+        if !self.contains(name) {
+            return Err(ParseError::UnknownSymbol { name: name.to_string(), position: 0 });
+        }
+        let source = format!("${}", name);
+        parse_with(&source, &Context::new(Some(self)))
+    }
+
+    /// Проверяет все определения: синтаксис, неизвестные имена, циклы.
+    /// Возвращает первую найденную ошибку.
+    pub fn check(&self) -> Result<(), ParseError> {
+        self.definitions
+            .keys()
+            .try_for_each(|name| self.resolve(name).map(|_| ()))
+    }
+}
+
+/// Допустимое имя: буква или '_', затем буквы, цифры, '_'
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Делит строку `ИМЯ = условие` на имя и текст условия
+pub(crate) fn split_definition(line: &str) -> Option<(&str, &str)> {
+    let (name, source) = line.split_once('=')?;
+    if source.starts_with('=') {
+        return None;
+    }
+    let name = name.trim();
+    is_valid_name(name.trim_start_matches('$'))
+        .then_some((name.trim_start_matches('$'), source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::{parse_ddr_expression_with_symbols, to_ddr_string};
+
+    fn table(lines: &[&str]) -> SymbolTable {
+        let mut symbols = SymbolTable::new();
+        for line in lines {
+            symbols.define_line(line).unwrap();
+        }
+        symbols
+    }
+
+    #[test]
+    fn test_nested_references() {
+        // Порядок определений не важен
+        let symbols = table(&["ALL = $NB or $SB", "NB = 1-2", "SB = and 5-6"]);
+        let expr = parse_ddr_expression_with_symbols("$ALL and not 9", &symbols).unwrap();
+
+        assert_eq!(expr.to_string(), "($ALL) and (not 9)");
+        assert_eq!(
+            to_ddr_string(&expr),
+            "((ddr(D1) or ddr(D2)) or (ddr(D5) and ddr(D6))) and (not ddr(D9))"
+        );
+        assert!(symbols.check().is_ok());
+    }
+
+    #[test]
+    fn test_unknown_and_invalid() {
+        let symbols = table(&["NB = 1-2", "BAD = 1-"]);
+        assert_eq!(
+            parse_ddr_expression_with_symbols("(1-3) or $SB", &symbols),
+            Err(ParseError::UnknownSymbol { name: "SB".to_string(), position: 9 })
+        );
+        assert_eq!(
+            parse_ddr_expression_with_symbols("$BAD", &symbols),
+            Err(ParseError::InDefinition {
+                name: "BAD".to_string(),
+                error: Box::new(ParseError::ExpectedNumber {
+                    found: "конец строки".to_string(),
+                    position: 2,
+                }),
+            })
+        );
+        assert_eq!(
+            SymbolTable::new().define_line("1NB = 1-2"),
+            Err(ParseError::InvalidDefinition("1NB = 1-2".to_string()))
+        );
+        assert_eq!(
+            SymbolTable::new().define("N B", "1-2"),
+            Err(ParseError::InvalidName("N B".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_cycles() {
+        let symbols = table(&["A = 1-2 or $B", "B = $C", "C = $A", "SELF = $SELF"]);
+        assert_eq!(
+            symbols.resolve("A"),
+            Err(ParseError::CyclicReference(vec![
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+                "A".to_string(),
+            ]))
+        );
+        assert_eq!(
+            symbols.resolve("SELF").unwrap_err().to_string(),
+            "Циклическая ссылка: $SELF → $SELF"
+        );
+        assert!(symbols.check().is_err());
+    }
+}
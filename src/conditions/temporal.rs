@@ -0,0 +1,164 @@
+//! Вычисление условий во времени
+//!
+//! Временные операторы зависят от истории, поэтому вычислитель хранит
+//! состояние каждого оператора и получает состояния детекторов по шагам.
+
+use crate::conditions::ast::*;
+use crate::conditions::eval::{evaluate_comparison, evaluate_range, DetectorInputs};
+
+/// Состояние одного временного оператора
+#[derive(Debug, Clone, Copy, Default)]
+struct TimerState {
+    /// Значение условия на прошлом шаге
+    previous: bool,
+    /// Когда условие последний раз изменилось; `None` — с начала ложно
+    changed_ms: Option<u64>,
+}
+
+/// Пошаговый вычислитель условия с временными операторами.
+///
+/// До первого шага все условия считаются ложными: если условие истинно
+/// уже на первом шаге, `rise` срабатывает на нём.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conditions::{parse_ddr_expression, TemporalEvaluator};
+///
+/// let expr = parse_ddr_expression("delay(3, 2)").unwrap();
+/// let mut evaluator = TemporalEvaluator::new(&expr);
+///
+/// assert!(!evaluator.tick(0, &[3][..]));
+/// assert!(!evaluator.tick(1000, &[3][..]));
+/// assert!(evaluator.tick(2000, &[3][..]));  // занят 2 с подряд
+/// assert!(!evaluator.tick(3000, &[][..]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TemporalEvaluator {
+    expr: Expr,
+    /// По одному на временной оператор, в порядке обхода выражения
+    timers: Vec<TimerState>,
+}
+
+impl TemporalEvaluator {
+    pub fn new(expr: &Expr) -> Self {
+        Self {
+            expr: expr.clone(),
+            timers: vec![TimerState::default(); count_timers(expr)],
+        }
+    }
+
+    /// Вычисляемое выражение
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Вычисляет условие в момент `time_ms` (моменты не убывают от шага
+    /// к шагу) и запоминает состояние для следующего шага
+    pub fn tick<I>(&mut self, time_ms: u64, inputs: &I) -> bool
+    where
+        I: DetectorInputs + ?Sized,
+    {
+        let mut next = 0;
+        step(&self.expr, time_ms, inputs, &mut self.timers, &mut next)
+    }
+
+    /// Забывает историю — как перед первым шагом
+    pub fn reset(&mut self) {
+        self.timers.fill(TimerState::default());
+    }
+}
+
+fn count_timers(expr: &Expr) -> usize {
+    match expr {
+        Expr::Range(_) | Expr::Compare(_) => 0,
+        Expr::Not(inner) => count_timers(inner),
+        Expr::Ref { body, .. } => count_timers(body),
+        Expr::Temporal { inner, .. } => 1 + count_timers(inner),
+        Expr::Binary { left, right, .. } => count_timers(left) + count_timers(right),
+    }
+}
+
+/// Один шаг. Обе стороны бинарных операций вычисляются всегда, чтобы
+/// таймеры видели каждый шаг.
+fn step<I>(expr: &Expr, now: u64, inputs: &I, timers: &mut [TimerState], next: &mut usize) -> bool
+where
+    I: DetectorInputs + ?Sized,
+{
+    match expr {
+        Expr::Range(range) => evaluate_range(range, inputs),
+        Expr::Compare(comparison) => evaluate_comparison(comparison, inputs).unwrap_or(false),
+        Expr::Not(inner) => !step(inner, now, inputs, timers, next),
+        Expr::Ref { body, .. } => step(body, now, inputs, timers, next),
+        Expr::Temporal { op, inner } => {
+            let index = *next;
+            *next += 1;
+            let value = step(inner, now, inputs, timers, next);
+
+            let timer = &mut timers[index];
+            if value != timer.previous {
+                timer.changed_ms = Some(now);
+                timer.previous = value;
+            }
+            let since = |changed: u64| now.saturating_sub(changed);
+
+            match *op {
+                TemporalOp::Delay { ms } => value && timer.changed_ms.is_some_and(|c| since(c) >= ms),
+                TemporalOp::Hold { ms } => value || timer.changed_ms.is_some_and(|c| since(c) < ms),
+                TemporalOp::Rise => value && timer.changed_ms == Some(now),
+                TemporalOp::Fall => !value && timer.changed_ms == Some(now),
+            }
+        }
+        Expr::Binary { op, left, right } => {
+            let left = step(left, now, inputs, timers, next);
+            let right = step(right, now, inputs, timers, next);
+            match op {
+                BinaryOp::And => left && right,
+                BinaryOp::Or => left || right,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::parse_ddr_expression;
+
+    /// Значения условия по шагам в 1 с; `active[i]` — занятые детекторы на шаге i
+    fn run(source: &str, active: &[&[u32]]) -> Vec<bool> {
+        let mut evaluator = TemporalEvaluator::new(&parse_ddr_expression(source).unwrap());
+        active
+            .iter()
+            .enumerate()
+            .map(|(i, detectors)| evaluator.tick(i as u64 * 1000, *detectors))
+            .collect()
+    }
+
+    #[test]
+    fn test_edges() {
+        let steps: &[&[u32]] = &[&[7], &[7], &[], &[], &[7]];
+        assert_eq!(run("rise(7)", steps), [true, false, false, false, true]);
+        assert_eq!(run("fall(7)", steps), [false, false, true, false, false]);
+    }
+
+    #[test]
+    fn test_delay_and_hold() {
+        let steps: &[&[u32]] = &[&[], &[3], &[3], &[3], &[], &[], &[], &[3]];
+        assert_eq!(run("delay(3, 2)", steps), [false, false, false, true, false, false, false, false]);
+        assert_eq!(run("hold(3, 2)", steps), [false, true, true, true, true, true, false, true]);
+    }
+
+    #[test]
+    fn test_nested_and_reset() {
+        // Вызов по фронту D1, пока D2 свободен не меньше 1 с
+        let source = "rise(1) and delay(not 2, 1)";
+        let steps: &[&[u32]] = &[&[1], &[], &[1], &[2], &[1]];
+        assert_eq!(run(source, steps), [false, false, true, false, false]);
+
+        let mut evaluator = TemporalEvaluator::new(&parse_ddr_expression("rise(1)").unwrap());
+        assert!(evaluator.tick(0, &[1][..]));
+        assert!(!evaluator.tick(1000, &[1][..]));
+        evaluator.reset();
+        assert!(evaluator.tick(2000, &[1][..]));
+    }
+}
@@ -0,0 +1,263 @@
+//! Перекрёстные ссылки детекторов по набору условий
+//!
+//! Для набора условий показывает:
+//! - в каких условиях используется каждый детектор;
+//! - какие настроенные детекторы не используются нигде;
+//! - какие условия ссылаются на несуществующие детекторы.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::{parse_ddr_expression, CrossReference};
+//!
+//! let mut xref = CrossReference::with_configured(1..=4);
+//! xref.add("phase1", &parse_ddr_expression("1-2").unwrap());
+//! xref.add("phase2", &parse_ddr_expression("2 and 7").unwrap());
+//!
+//! assert_eq!(xref.users(2), ["phase1", "phase2"]);
+//! assert_eq!(xref.unused(), [3, 4]);
+//! assert_eq!(xref.unknown()["phase2"], [7]);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::conditions::ast::Expr;
+use crate::conditions::library::Library;
+
+/// Состояние детектора в отчёте
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectorStatus {
+    /// Настроен и используется
+    Used,
+    /// Настроен, но ни одно условие на него не ссылается
+    Unused,
+    /// Используется, но не настроен
+    Unknown,
+}
+
+impl DetectorStatus {
+    /// Ключ для CSV и JSON
+    pub fn key(&self) -> &'static str {
+        match self {
+            DetectorStatus::Used => "used",
+            DetectorStatus::Unused => "unused",
+            DetectorStatus::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for DetectorStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetectorStatus::Used => write!(f, "используется"),
+            DetectorStatus::Unused => write!(f, "не используется"),
+            DetectorStatus::Unknown => write!(f, "не настроен"),
+        }
+    }
+}
+
+/// Строка отчёта: детектор и условия, где он встречается
+#[derive(Debug, Clone, PartialEq)]
+pub struct XrefRow<'a> {
+    pub detector: u32,
+    pub status: DetectorStatus,
+    pub conditions: &'a [String],
+}
+
+/// Индекс «детектор → условия»
+#[derive(Debug, Clone, Default)]
+pub struct CrossReference {
+    /// Детектор → имена условий в порядке добавления
+    usage: BTreeMap<u32, Vec<String>>,
+    /// Настроенные детекторы; `None` — конфигурация неизвестна
+    configured: Option<BTreeSet<u32>>,
+}
+
+impl CrossReference {
+    /// Индекс без списка настроенных детекторов: все детекторы считаются существующими
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Индекс с известным списком настроенных детекторов
+    pub fn with_configured(detectors: impl IntoIterator<Item = u32>) -> Self {
+        Self {
+            usage: BTreeMap::new(),
+            configured: Some(detectors.into_iter().collect()),
+        }
+    }
+
+    /// Индекс по всем условиям библиотеки
+    pub fn from_library(library: &Library, configured: Option<BTreeSet<u32>>) -> Self {
+        let mut xref = Self { usage: BTreeMap::new(), configured };
+        for entry in library.entries() {
+            xref.add(&entry.name, &entry.expr);
+        }
+        xref
+    }
+
+    /// Добавляет условие в индекс
+    pub fn add(&mut self, name: &str, expr: &Expr) {
+        for detector in expr.detectors() {
+            let users = self.usage.entry(detector).or_default();
+            if !users.iter().any(|user| user == name) {
+                users.push(name.to_string());
+            }
+        }
+    }
+
+    /// Условия, в которых встречается детектор
+    pub fn users(&self, detector: u32) -> &[String] {
+        self.usage.get(&detector).map_or(&[], Vec::as_slice)
+    }
+
+    /// Настроенные детекторы, на которые нет ни одной ссылки
+    pub fn unused(&self) -> Vec<u32> {
+        self.configured
+            .iter()
+            .flatten()
+            .filter(|detector| !self.usage.contains_key(detector))
+            .copied()
+            .collect()
+    }
+
+    /// Условия, ссылающиеся на ненастроенные детекторы: условие → детекторы
+    pub fn unknown(&self) -> BTreeMap<&str, Vec<u32>> {
+        let mut unknown: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+        for (detector, users) in &self.usage {
+            if self.status(*detector) == DetectorStatus::Unknown {
+                for user in users {
+                    unknown.entry(user.as_str()).or_default().push(*detector);
+                }
+            }
+        }
+        unknown
+    }
+
+    /// Состояние детектора
+    pub fn status(&self, detector: u32) -> DetectorStatus {
+        let used = self.usage.contains_key(&detector);
+        match &self.configured {
+            Some(configured) if !configured.contains(&detector) => DetectorStatus::Unknown,
+            _ if used => DetectorStatus::Used,
+            _ => DetectorStatus::Unused,
+        }
+    }
+
+    /// Строки отчёта по всем упомянутым и настроенным детекторам, по возрастанию номера
+    pub fn rows(&self) -> Vec<XrefRow<'_>> {
+        let mut detectors: BTreeSet<u32> = self.usage.keys().copied().collect();
+        detectors.extend(self.configured.iter().flatten());
+
+        detectors
+            .into_iter()
+            .map(|detector| XrefRow {
+                detector,
+                status: self.status(detector),
+                conditions: self.users(detector),
+            })
+            .collect()
+    }
+
+    /// Таблица CSV: `detector,status,conditions`, условия через ';'
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("detector,status,conditions\n");
+        for row in self.rows() {
+            out.push_str(&format!(
+                "{},{},{}\n",
+                row.detector,
+                row.status.key(),
+                csv_field(&row.conditions.join(";"))
+            ));
+        }
+        out
+    }
+
+    /// Таблица Markdown для отчётов
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("| Детектор | Состояние | Условия |\n|---:|---|---|\n");
+        for row in self.rows() {
+            let conditions = row.conditions.join(", ").replace('|', "\\|");
+            out.push_str(&format!("| D{} | {} | {} |\n", row.detector, row.status, conditions));
+        }
+        out
+    }
+
+    /// Полный отчёт в JSON
+    pub fn to_json(&self) -> Value {
+        let detectors: Vec<Value> = self
+            .rows()
+            .iter()
+            .map(|row| {
+                json!({
+                    "detector": row.detector,
+                    "status": row.status.key(),
+                    "conditions": row.conditions,
+                })
+            })
+            .collect();
+
+        json!({
+            "detectors": detectors,
+            "unused": self.unused(),
+            "unknown": self.unknown(),
+        })
+    }
+}
+
+/// Экранирует поле CSV, если в нём есть разделители или кавычки
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::parse_ddr_expression;
+
+    fn sample() -> CrossReference {
+        let mut xref = CrossReference::with_configured([1, 2, 3, 5]);
+        xref.add("phase1", &parse_ddr_expression("1-2").unwrap());
+        xref.add("phase2", &parse_ddr_expression("(2) and not 9").unwrap());
+        xref
+    }
+
+    #[test]
+    fn test_index() {
+        let xref = sample();
+        assert_eq!(xref.users(2), ["phase1", "phase2"]);
+        assert!(xref.users(4).is_empty());
+        assert_eq!(xref.unused(), [3, 5]);
+        assert_eq!(xref.unknown(), BTreeMap::from([("phase2", vec![9])]));
+        assert_eq!(xref.status(9), DetectorStatus::Unknown);
+
+        // Без конфигурации неизвестных и неиспользуемых нет
+        let mut open = CrossReference::new();
+        open.add("phase2", &parse_ddr_expression("9").unwrap());
+        assert!(open.unknown().is_empty());
+        assert_eq!(open.status(9), DetectorStatus::Used);
+    }
+
+    #[test]
+    fn test_exports() {
+        let xref = sample();
+        assert_eq!(
+            xref.to_csv(),
+            "detector,status,conditions\n\
+             1,used,phase1\n\
+             2,used,phase1;phase2\n\
+             3,unused,\n\
+             5,unused,\n\
+             9,unknown,phase2\n"
+        );
+        assert!(xref.to_markdown().contains("| D2 | используется | phase1, phase2 |\n"));
+        assert_eq!(xref.to_json()["unknown"], json!({ "phase2": [9] }));
+        assert_eq!(xref.to_json()["detectors"][1]["conditions"], json!(["phase1", "phase2"]));
+    }
+}
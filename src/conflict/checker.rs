@@ -0,0 +1,225 @@
+//! Проверка фаз на совместимость направлений и список допустимых переходов
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::conflict::matrix::ConflictMatrix;
+use crate::junction::Junction;
+
+/// Фаза как набор номеров направлений
+#[derive(Debug, Clone, PartialEq)]
+pub struct StagePlan {
+    pub number: u32,
+    pub phases: BTreeSet<u32>,
+}
+
+impl StagePlan {
+    pub fn new(number: u32, phases: impl IntoIterator<Item = u32>) -> Self {
+        Self { number, phases: phases.into_iter().collect() }
+    }
+
+    /// Фазы перекрёстка с порядковыми номерами направлений
+    pub fn from_junction(junction: &Junction) -> Vec<StagePlan> {
+        junction
+            .stages
+            .iter()
+            .map(|stage| StagePlan::new(stage.number, junction.stage_phase_numbers(stage)))
+            .collect()
+    }
+}
+
+/// Конфликтующие направления внутри одной фазы
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub stage: u32,
+    pub a: u32,
+    pub b: u32,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Фаза {}: направления {} и {} конфликтуют", self.stage, self.a, self.b)
+    }
+}
+
+/// Допустимый переход между фазами
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub from: u32,
+    pub to: u32,
+    /// Направления, которые выключаются
+    pub ending: Vec<u32>,
+    /// Направления, которые включаются
+    pub starting: Vec<u32>,
+    /// Направления, которые горят в обеих фазах
+    pub continuing: Vec<u32>,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} → {}: выключаются {}; включаются {}; продолжают {}",
+            self.from,
+            self.to,
+            list(&self.ending),
+            list(&self.starting),
+            list(&self.continuing)
+        )
+    }
+}
+
+/// Результат проверки
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompatibilityReport {
+    pub violations: Vec<Violation>,
+    pub transitions: Vec<Transition>,
+}
+
+impl CompatibilityReport {
+    /// Нет ни одной фазы с конфликтующими направлениями
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Допустимые переходы из фазы
+    pub fn transitions_from(&self, stage: u32) -> impl Iterator<Item = &Transition> {
+        self.transitions.iter().filter(move |t| t.from == stage)
+    }
+}
+
+/// Отчёт для человека: сначала нарушения, затем переходы
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.violations.is_empty() {
+            writeln!(f, "Конфликтов внутри фаз нет")?;
+        } else {
+            writeln!(f, "Конфликты внутри фаз ({}):", self.violations.len())?;
+            for violation in &self.violations {
+                writeln!(f, "  {}", violation)?;
+            }
+        }
+
+        writeln!(f, "Допустимые переходы ({}):", self.transitions.len())?;
+        for transition in &self.transitions {
+            writeln!(f, "  {}", transition)?;
+        }
+        Ok(())
+    }
+}
+
+/// Проверяет фазы: ищет конфликтующие направления внутри каждой фазы и
+/// перечисляет допустимые переходы — между фазами без конфликтов с разным
+/// набором направлений.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conflict::{check_stages, ConflictMatrix, StagePlan};
+///
+/// let conflicts = ConflictMatrix::parse("1: 2\n3: 2").unwrap();
+/// let stages = [StagePlan::new(1, [1, 3]), StagePlan::new(2, [2]), StagePlan::new(3, [1, 2])];
+/// let report = check_stages(&conflicts, &stages);
+///
+/// assert_eq!(report.violations[0].to_string(), "Фаза 3: направления 1 и 2 конфликтуют");
+/// assert_eq!(report.transitions.len(), 2);  // 1 → 2 и 2 → 1
+/// ```
+pub fn check_stages(conflicts: &ConflictMatrix, stages: &[StagePlan]) -> CompatibilityReport {
+    let mut report = CompatibilityReport::default();
+    let mut valid = Vec::new();
+
+    for stage in stages {
+        let phases: Vec<u32> = stage.phases.iter().copied().collect();
+        let before = report.violations.len();
+        for (i, &a) in phases.iter().enumerate() {
+            for &b in &phases[i + 1..] {
+                if conflicts.conflicts(a, b) {
+                    report.violations.push(Violation { stage: stage.number, a, b });
+                }
+            }
+        }
+        if report.violations.len() == before {
+            valid.push(stage);
+        }
+    }
+
+    for from in &valid {
+        for to in &valid {
+            if from.number == to.number || from.phases == to.phases {
+                continue;
+            }
+            report.transitions.push(Transition {
+                from: from.number,
+                to: to.number,
+                ending: from.phases.difference(&to.phases).copied().collect(),
+                starting: to.phases.difference(&from.phases).copied().collect(),
+                continuing: from.phases.intersection(&to.phases).copied().collect(),
+            });
+        }
+    }
+
+    report
+}
+
+/// "1, 3" или "—" для пустого списка
+fn list(phases: &[u32]) -> String {
+    if phases.is_empty() {
+        return "—".to_string();
+    }
+    phases
+        .iter()
+        .map(|phase| phase.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::junction::{Phase, PhaseKind, Stage};
+
+    #[test]
+    fn test_report() {
+        let conflicts = ConflictMatrix::parse("1: 2 or 4\n3: 2").unwrap();
+        let stages = [
+            StagePlan::new(1, [1, 3]),
+            StagePlan::new(2, [2]),
+            StagePlan::new(3, [1, 2, 4]),
+            StagePlan::new(4, [3, 4]),
+        ];
+        let report = check_stages(&conflicts, &stages);
+
+        assert!(!report.is_ok());
+        assert_eq!(report.transitions_from(4).count(), 2);
+        assert_eq!(
+            report.to_string(),
+            "\
+Конфликты внутри фаз (2):
+  Фаза 3: направления 1 и 2 конфликтуют
+  Фаза 3: направления 1 и 4 конфликтуют
+Допустимые переходы (6):
+  1 → 2: выключаются 1, 3; включаются 2; продолжают —
+  1 → 4: выключаются 1; включаются 4; продолжают 3
+  2 → 1: выключаются 2; включаются 1, 3; продолжают —
+  2 → 4: выключаются 2; включаются 3, 4; продолжают —
+  4 → 1: выключаются 4; включаются 1; продолжают 3
+  4 → 2: выключаются 3, 4; включаются 2; продолжают —
+"
+        );
+    }
+
+    #[test]
+    fn test_from_junction() {
+        let mut junction = Junction::new("CO4554");
+        junction.add_phase(Phase::new("A", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("B", PhaseKind::Vehicle));
+        junction.add_stage(Stage::new(1, &["A"]));
+        junction.add_stage(Stage::new(2, &["A", "B"]));
+
+        let stages = StagePlan::from_junction(&junction);
+        assert_eq!(stages[1], StagePlan::new(2, [1, 2]));
+
+        let report = check_stages(&ConflictMatrix::parse("1: 2").unwrap(), &stages);
+        assert_eq!(report.violations, [Violation { stage: 2, a: 1, b: 2 }]);
+        assert!(report.transitions.is_empty());
+    }
+}
@@ -0,0 +1,25 @@
+//! Типы ошибок описания конфликтов
+
+use thiserror::Error;
+
+use crate::conditions::ParseError;
+
+/// Ошибки разбора описания конфликтов
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConflictError {
+    /// Ошибка: строка не похожа на `номер: направления`
+    #[error("Строка {line}: ожидалось 'номер: направления', получено '{text}'")]
+    InvalidLine { line: usize, text: String },
+
+    /// Ошибка в списке конфликтующих направлений
+    #[error("Строка {line}: {error}")]
+    Syntax { line: usize, error: ParseError },
+
+    /// Ошибка: направление конфликтует само с собой
+    #[error("Строка {line}: направление {phase} не может конфликтовать само с собой")]
+    SelfConflict { line: usize, phase: u32 },
+
+    /// Ошибка: направления нет на перекрёстке
+    #[error("Направление '{0}' не описано на перекрёстке")]
+    UnknownPhase(String),
+}
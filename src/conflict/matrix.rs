@@ -0,0 +1,147 @@
+//! Матрица конфликтов направлений
+
+use std::collections::BTreeSet;
+
+use crate::conditions::parse_ddr_expression;
+use crate::conflict::error::ConflictError;
+use crate::intergreen::IntergreenMatrix;
+use crate::junction::Junction;
+
+/// Симметричное отношение «направления конфликтуют»
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConflictMatrix {
+    /// Пары (меньший номер, больший номер)
+    pairs: BTreeSet<(u32, u32)>,
+}
+
+impl ConflictMatrix {
+    /// Матрица без конфликтов
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Разбирает описание конфликтов: по строке `направление: конфликтующие`.
+    /// Список конфликтующих записывается как номера в условиях:
+    /// `1: 3-5 or 7`. Строки с `#` пропускаются.
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::conflict::ConflictMatrix;
+    ///
+    /// let matrix = ConflictMatrix::parse("1: 3-4\n2: 4 or 6").unwrap();
+    /// assert!(matrix.conflicts(4, 1));
+    /// assert!(!matrix.conflicts(1, 2));
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, ConflictError> {
+        let mut matrix = Self::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let content = line.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let invalid = || ConflictError::InvalidLine { line: line_number, text: content.to_string() };
+            let (phase, others) = content.split_once(':').ok_or_else(invalid)?;
+            let phase: u32 = phase.trim().parse().map_err(|_| invalid())?;
+            let others = parse_ddr_expression(others)
+                .map_err(|error| ConflictError::Syntax { line: line_number, error })?;
+
+            for other in others.detectors() {
+                if other == phase {
+                    return Err(ConflictError::SelfConflict { line: line_number, phase });
+                }
+                matrix.add(phase, other);
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// Конфликты по матрице промтактов: направления конфликтуют, если для них
+    /// задан промтакт. Номера — порядковые номера направлений перекрёстка.
+    pub fn from_intergreen(matrix: &IntergreenMatrix, junction: &Junction) -> Result<Self, ConflictError> {
+        let number = |name: &str| {
+            junction
+                .phase_number(name)
+                .ok_or_else(|| ConflictError::UnknownPhase(name.to_string()))
+        };
+
+        let mut conflicts = Self::new();
+        for from in matrix.phases() {
+            for to in matrix.phases() {
+                if from != to && matrix.get(from, to).is_some() {
+                    conflicts.add(number(from)?, number(to)?);
+                }
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Отмечает направления конфликтующими (в обе стороны)
+    pub fn add(&mut self, a: u32, b: u32) {
+        if a != b {
+            self.pairs.insert((a.min(b), a.max(b)));
+        }
+    }
+
+    /// Конфликтуют ли направления
+    pub fn conflicts(&self, a: u32, b: u32) -> bool {
+        self.pairs.contains(&(a.min(b), a.max(b)))
+    }
+
+    /// Все конфликтующие пары (меньший номер первым)
+    pub fn pairs(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.pairs.iter().copied()
+    }
+
+    /// Направления, конфликтующие с заданным
+    pub fn conflicting(&self, phase: u32) -> BTreeSet<u32> {
+        self.pairs
+            .iter()
+            .filter_map(|&(a, b)| match phase {
+                _ if phase == a => Some(b),
+                _ if phase == b => Some(a),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::junction::{Phase, PhaseKind};
+
+    #[test]
+    fn test_parse() {
+        let matrix = ConflictMatrix::parse("# конфликты\n1: 2-3\n\n4: 1 or 5  # пешеходы\n").unwrap();
+        assert_eq!(matrix.pairs().collect::<Vec<_>>(), [(1, 2), (1, 3), (1, 4), (4, 5)]);
+        assert_eq!(matrix.conflicting(1), BTreeSet::from([2, 3, 4]));
+
+        assert_eq!(
+            ConflictMatrix::parse("1: 1-2"),
+            Err(ConflictError::SelfConflict { line: 1, phase: 1 })
+        );
+        assert_eq!(
+            ConflictMatrix::parse("\nA: 2").unwrap_err().to_string(),
+            "Строка 2: ожидалось 'номер: направления', получено 'A: 2'"
+        );
+        assert!(matches!(
+            ConflictMatrix::parse("1: 2 xor 3"),
+            Err(ConflictError::Syntax { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_intergreen() {
+        let mut junction = Junction::new("CO4554");
+        junction.add_phase(Phase::new("A", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("B", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("P", PhaseKind::Pedestrian));
+
+        let intergreens = IntergreenMatrix::from_csv(";A;B;P\nA;;5;\nB;4;;6\nP;;7;\n").unwrap();
+        let matrix = ConflictMatrix::from_intergreen(&intergreens, &junction).unwrap();
+        assert_eq!(matrix.pairs().collect::<Vec<_>>(), [(1, 2), (2, 3)]);
+    }
+}
@@ -0,0 +1,26 @@
+//! Конфликты направлений и совместимость фаз
+//!
+//! Направления нумеруются так же, как в условиях: целыми числами,
+//! списки записываются диапазонами (`1: 3-5 or 7`). Для перекрёстка
+//! из `junction` номер направления — его порядковый номер в описании.
+//!
+//! Проверка находит фазы, в которых одновременно разрешены
+//! конфликтующие направления, и перечисляет допустимые переходы.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conflict::{check_stages, ConflictMatrix, StagePlan};
+//!
+//! let conflicts = ConflictMatrix::parse("1: 2").unwrap();
+//! let report = check_stages(&conflicts, &[StagePlan::new(1, [1]), StagePlan::new(2, [2])]);
+//! assert!(report.is_ok());
+//! println!("{}", report);
+//! ```
+
+mod checker;  // checker.rs — проверка фаз и переходы
+mod error;    // error.rs — типы ошибок
+mod matrix;   // matrix.rs — матрица конфликтов
+
+pub use checker::{check_stages, CompatibilityReport, StagePlan, Transition, Violation};
+pub use error::ConflictError;
+pub use matrix::ConflictMatrix;
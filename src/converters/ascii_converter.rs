@@ -1,40 +1,70 @@
-use std::fmt::format;
+use crate::converters::index_encoding::{parse_components, IndexEncoding, IndexValue};
 
 
-struct ConvertedString {
-    raw_str: String,
-    str_as_scn: String,
-    str_as_chars: String,
+pub fn scn_encoding() -> IndexEncoding {
+    // Индекс SCN-объектов: префикс .1 и строка с длиной.
+    // Частный случай IndexEncoding, на котором построены функции ниже.
+    IndexEncoding::Composite(vec![IndexEncoding::Integer, IndexEncoding::LengthPrefixed])
 }
 
 
-// impl ConvertedString {
-//     pub fn new() -> Self {
-
-//     }
-// }
-
-
 pub fn gen_scn_from_chars(string: &str) -> String {
     // Функция генерирует строку ASCII с префиксом на основе входящей.
     // Пример: gen_scn_from_chars("CO4554") => ".1.6.67.79.52.53.53.52"
 
+    let value = IndexValue::Composite(vec![
+        IndexValue::Integer(1),
+        IndexValue::text(string.trim()),
+    ]);
+    scn_encoding()
+        .encode_to_string(&value)
+        .expect("значение соответствует scn_encoding")
+}
+
+
+pub fn gen_chars_from_scn(scn: &str) -> Option<String> {
+    // Обратная к gen_scn_from_chars функция.
+    // Пример: gen_chars_from_scn(".1.6.67.79.52.53.53.52") => Some("CO4554")
+
+    let components = parse_components(scn).ok()?;
+    match find_scn(&components) {
+        Some(found) if found.start == 0 && found.end == components.len() => Some(found.scn),
+        _ => None,
+    }
+}
 
-    // Если нужно просто получить вектор чисел
-    let ascii_codes: Vec<u8> = string.trim().bytes().collect();
-    let len = ascii_codes.len();
-
-
-    println!("{:?}", ascii_codes);
-    
-    // Если нужна строка с кодами через пробел
-    let codes_str = string
-        .bytes()
-        .map(|b| b.to_string())
-        .collect::<Vec<_>>()
-        .join(".");
-    println!("codes_str={codes_str}");
-    let result = format!(".1.{}.{}", len, codes_str);
-    println!("SCN: {result}");
-    result
-}
\ No newline at end of file
+
+/// SCN, найденный внутри OID
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScnMatch {
+    /// Расшифрованный SCN, например "CO4554"
+    pub scn: String,
+    /// Индекс компонента OID, с которого начинается `.1.<len>.`
+    pub start: usize,
+    /// Индекс компонента сразу после последнего символа SCN
+    pub end: usize,
+}
+
+
+pub fn find_scn(components: &[u32]) -> Option<ScnMatch> {
+    // Ищет в компонентах OID первый фрагмент вида 1.<len>.<коды ASCII>,
+    // где все коды — печатные символы. Так SCN находится и в полном OID
+    // объекта: .1.3.6.1.4.1.99999.3.1.6.67.79.52.53.53.52.1 => "CO4554"
+
+    let encoding = scn_encoding();
+    (0..components.len()).find_map(|start| {
+        let (value, used) = encoding.decode_prefix(&components[start..]).ok()?;
+        let IndexValue::Composite(parts) = value else {
+            return None;
+        };
+        let [IndexValue::Integer(1), IndexValue::OctetString(bytes)] = parts.as_slice() else {
+            return None;
+        };
+        if bytes.is_empty() || !bytes.iter().all(u8::is_ascii_graphic) {
+            return None;
+        }
+
+        let scn = String::from_utf8(bytes.clone()).ok()?;
+        Some(ScnMatch { scn, start, end: start + used })
+    })
+}
@@ -0,0 +1,35 @@
+//! Типы ошибок для конвертеров индексов
+
+use thiserror::Error;
+
+/// Ошибки кодирования и разбора индексов таблиц SNMP
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum IndexError {
+    /// Ошибка: значение не подходит под способ кодирования
+    #[error("Значение {value} нельзя закодировать как {encoding}")]
+    TypeMismatch { value: String, encoding: String },
+
+    /// Ошибка: строка фиксированной длины другого размера
+    #[error("Ожидалась строка длиной {expected}, получено {found}")]
+    WrongLength { expected: usize, found: usize },
+
+    /// Ошибка: компонентов OID меньше, чем нужно
+    #[error("Индекс обрывается: нужно ещё {0} компонентов")]
+    Truncated(usize),
+
+    /// Ошибка: компонент не помещается в байт строки или октет адреса
+    #[error("Компонент {0} не помещается в байт")]
+    ByteOutOfRange(u32),
+
+    /// Ошибка: после разбора индекса остались компоненты
+    #[error("Лишние компоненты после индекса: {0}")]
+    ExtraComponents(usize),
+
+    /// Ошибка: IMPLIED допускается только последним в составном индексе
+    #[error("IMPLIED может быть только последней частью составного индекса")]
+    ImpliedNotLast,
+
+    /// Ошибка: строка не является записью OID
+    #[error("Некорректная запись OID '{0}'")]
+    InvalidOid(String),
+}
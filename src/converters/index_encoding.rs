@@ -0,0 +1,352 @@
+//! Кодирование индексов таблиц SNMP в компоненты OID
+//!
+//! Правила взяты из SMIv2 (RFC 2578, раздел 7.7):
+//! - INTEGER — один компонент;
+//! - строка переменной длины — длина, затем коды байт;
+//! - строка фиксированной длины — только коды байт;
+//! - IMPLIED-строка — коды байт до конца OID (только последней);
+//! - OBJECT IDENTIFIER — длина, затем компоненты (или IMPLIED без длины);
+//! - IpAddress — четыре компонента по октету;
+//! - составной индекс — части подряд в порядке INDEX { ... }.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::converters::{IndexEncoding, IndexValue};
+//!
+//! let encoding = IndexEncoding::Composite(vec![
+//!     IndexEncoding::Integer,
+//!     IndexEncoding::LengthPrefixed,
+//! ]);
+//! let value = IndexValue::Composite(vec![IndexValue::Integer(1), IndexValue::text("CO4554")]);
+//!
+//! let oid = encoding.encode_to_string(&value).unwrap();
+//! assert_eq!(oid, ".1.6.67.79.52.53.53.52");
+//! assert_eq!(encoding.decode_str(&oid).unwrap(), value);
+//! ```
+
+use std::fmt;
+
+use crate::converters::error::IndexError;
+
+/// Способ кодирования индекса
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexEncoding {
+    /// INTEGER / Unsigned32: один компонент
+    Integer,
+    /// OCTET STRING переменной длины: `len.c1.c2...`
+    LengthPrefixed,
+    /// OCTET STRING (SIZE(n)): `c1...cn` без длины
+    FixedLength(usize),
+    /// IMPLIED OCTET STRING: `c1.c2...` до конца OID
+    Implied,
+    /// OBJECT IDENTIFIER: `len.s1.s2...`
+    ObjectId,
+    /// IMPLIED OBJECT IDENTIFIER: `s1.s2...` до конца OID
+    ImpliedObjectId,
+    /// IpAddress: `a.b.c.d`
+    IpAddress,
+    /// Составной индекс из нескольких частей
+    Composite(Vec<IndexEncoding>),
+}
+
+/// Значение индекса
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexValue {
+    Integer(u32),
+    OctetString(Vec<u8>),
+    ObjectId(Vec<u32>),
+    IpAddress([u8; 4]),
+    Composite(Vec<IndexValue>),
+}
+
+impl IndexValue {
+    /// Строковое значение из текста
+    pub fn text(text: &str) -> Self {
+        IndexValue::OctetString(text.as_bytes().to_vec())
+    }
+
+    /// Строка как текст, если это строка в UTF-8
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            IndexValue::OctetString(bytes) => std::str::from_utf8(bytes).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IndexValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexValue::Integer(n) => write!(f, "{}", n),
+            IndexValue::OctetString(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => write!(f, "\"{}\"", text),
+                Err(_) => write!(f, "{:02X?}", bytes),
+            },
+            IndexValue::ObjectId(components) => write!(f, "{}", format_components(components)),
+            IndexValue::IpAddress([a, b, c, d]) => write!(f, "{}.{}.{}.{}", a, b, c, d),
+            IndexValue::Composite(parts) => {
+                let parts: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+                write!(f, "[{}]", parts.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for IndexEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexEncoding::Integer => write!(f, "INTEGER"),
+            IndexEncoding::LengthPrefixed => write!(f, "OCTET STRING"),
+            IndexEncoding::FixedLength(n) => write!(f, "OCTET STRING (SIZE({}))", n),
+            IndexEncoding::Implied => write!(f, "IMPLIED OCTET STRING"),
+            IndexEncoding::ObjectId => write!(f, "OBJECT IDENTIFIER"),
+            IndexEncoding::ImpliedObjectId => write!(f, "IMPLIED OBJECT IDENTIFIER"),
+            IndexEncoding::IpAddress => write!(f, "IpAddress"),
+            IndexEncoding::Composite(parts) => {
+                let parts: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+                write!(f, "INDEX {{ {} }}", parts.join(", "))
+            }
+        }
+    }
+}
+
+impl IndexEncoding {
+    /// Кодирует значение в компоненты OID
+    pub fn encode(&self, value: &IndexValue) -> Result<Vec<u32>, IndexError> {
+        let mut out = Vec::new();
+        self.encode_into(value, &mut out)?;
+        Ok(out)
+    }
+
+    /// Кодирует значение в строку OID с ведущей точкой: ".1.6.67..."
+    pub fn encode_to_string(&self, value: &IndexValue) -> Result<String, IndexError> {
+        self.encode(value).map(|components| format_components(&components))
+    }
+
+    /// Декодирует индекс, требуя, чтобы он занимал все компоненты
+    pub fn decode(&self, components: &[u32]) -> Result<IndexValue, IndexError> {
+        let (value, used) = self.decode_prefix(components)?;
+        match components.len() - used {
+            0 => Ok(value),
+            extra => Err(IndexError::ExtraComponents(extra)),
+        }
+    }
+
+    /// Декодирует индекс из строки OID: ".1.6.67..." или "1.6.67..."
+    pub fn decode_str(&self, oid: &str) -> Result<IndexValue, IndexError> {
+        self.decode(&parse_components(oid)?)
+    }
+
+    /// Декодирует индекс с начала `components`.
+    ///
+    /// Возвращает значение и количество использованных компонентов —
+    /// удобно, когда за индексом в OID идёт что-то ещё.
+    pub fn decode_prefix(&self, components: &[u32]) -> Result<(IndexValue, usize), IndexError> {
+        match self {
+            IndexEncoding::Integer => {
+                let n = *components.first().ok_or(IndexError::Truncated(1))?;
+                Ok((IndexValue::Integer(n), 1))
+            }
+            IndexEncoding::LengthPrefixed => {
+                let (len, body) = length_prefixed(components)?;
+                Ok((IndexValue::OctetString(to_bytes(body)?), len + 1))
+            }
+            IndexEncoding::FixedLength(len) => {
+                let body = take(components, *len)?;
+                Ok((IndexValue::OctetString(to_bytes(body)?), *len))
+            }
+            IndexEncoding::Implied => {
+                Ok((IndexValue::OctetString(to_bytes(components)?), components.len()))
+            }
+            IndexEncoding::ObjectId => {
+                let (len, body) = length_prefixed(components)?;
+                Ok((IndexValue::ObjectId(body.to_vec()), len + 1))
+            }
+            IndexEncoding::ImpliedObjectId => {
+                Ok((IndexValue::ObjectId(components.to_vec()), components.len()))
+            }
+            IndexEncoding::IpAddress => {
+                let octets = to_bytes(take(components, 4)?)?;
+                let octets = <[u8; 4]>::try_from(octets).expect("ровно четыре октета");
+                Ok((IndexValue::IpAddress(octets), 4))
+            }
+            IndexEncoding::Composite(parts) => {
+                check_implied_last(parts)?;
+                let mut values = Vec::with_capacity(parts.len());
+                let mut used = 0;
+                for part in parts {
+                    let (value, n) = part.decode_prefix(&components[used..])?;
+                    values.push(value);
+                    used += n;
+                }
+                Ok((IndexValue::Composite(values), used))
+            }
+        }
+    }
+
+    fn encode_into(&self, value: &IndexValue, out: &mut Vec<u32>) -> Result<(), IndexError> {
+        match (self, value) {
+            (IndexEncoding::Integer, IndexValue::Integer(n)) => out.push(*n),
+            (IndexEncoding::LengthPrefixed, IndexValue::OctetString(bytes)) => {
+                out.push(bytes.len() as u32);
+                out.extend(bytes.iter().map(|&b| b as u32));
+            }
+            (IndexEncoding::FixedLength(len), IndexValue::OctetString(bytes)) => {
+                if bytes.len() != *len {
+                    return Err(IndexError::WrongLength { expected: *len, found: bytes.len() });
+                }
+                out.extend(bytes.iter().map(|&b| b as u32));
+            }
+            (IndexEncoding::Implied, IndexValue::OctetString(bytes)) => {
+                out.extend(bytes.iter().map(|&b| b as u32));
+            }
+            (IndexEncoding::ObjectId, IndexValue::ObjectId(components)) => {
+                out.push(components.len() as u32);
+                out.extend_from_slice(components);
+            }
+            (IndexEncoding::ImpliedObjectId, IndexValue::ObjectId(components)) => {
+                out.extend_from_slice(components);
+            }
+            (IndexEncoding::IpAddress, IndexValue::IpAddress(octets)) => {
+                out.extend(octets.iter().map(|&b| b as u32));
+            }
+            (IndexEncoding::Composite(parts), IndexValue::Composite(values))
+                if parts.len() == values.len() =>
+            {
+                check_implied_last(parts)?;
+                for (part, value) in parts.iter().zip(values) {
+                    part.encode_into(value, out)?;
+                }
+            }
+            _ => {
+                return Err(IndexError::TypeMismatch {
+                    value: value.to_string(),
+                    encoding: self.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_implied_last(parts: &[IndexEncoding]) -> Result<(), IndexError> {
+    let implied = |p: &IndexEncoding| {
+        matches!(p, IndexEncoding::Implied | IndexEncoding::ImpliedObjectId)
+    };
+    match parts.split_last() {
+        Some((_, init)) if init.iter().any(implied) => Err(IndexError::ImpliedNotLast),
+        _ => Ok(()),
+    }
+}
+
+fn take(components: &[u32], len: usize) -> Result<&[u32], IndexError> {
+    components
+        .get(..len)
+        .ok_or_else(|| IndexError::Truncated(len - components.len()))
+}
+
+fn length_prefixed(components: &[u32]) -> Result<(usize, &[u32]), IndexError> {
+    let len = *components.first().ok_or(IndexError::Truncated(1))? as usize;
+    Ok((len, take(&components[1..], len)?))
+}
+
+fn to_bytes(components: &[u32]) -> Result<Vec<u8>, IndexError> {
+    components
+        .iter()
+        .map(|&c| u8::try_from(c).map_err(|_| IndexError::ByteOutOfRange(c)))
+        .collect()
+}
+
+/// Форматирует компоненты в строку с ведущей точкой
+pub fn format_components(components: &[u32]) -> String {
+    components.iter().map(|c| format!(".{}", c)).collect()
+}
+
+/// Разбирает строку ".1.6.67..." (или без ведущей точки) в компоненты
+pub fn parse_components(oid: &str) -> Result<Vec<u32>, IndexError> {
+    let body = oid.trim();
+    let body = body.strip_prefix('.').unwrap_or(body);
+    if body.is_empty() {
+        return Ok(Vec::new());
+    }
+    body.split('.')
+        .map(|part| part.parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| IndexError::InvalidOid(oid.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(encoding: IndexEncoding, value: IndexValue, expected: &str) {
+        assert_eq!(encoding.encode_to_string(&value).unwrap(), expected);
+        assert_eq!(encoding.decode_str(expected).unwrap(), value);
+    }
+
+    #[test]
+    fn test_simple_encodings() {
+        roundtrip(IndexEncoding::Integer, IndexValue::Integer(42), ".42");
+        roundtrip(IndexEncoding::LengthPrefixed, IndexValue::text("CO1"), ".3.67.79.49");
+        roundtrip(IndexEncoding::FixedLength(3), IndexValue::text("CO1"), ".67.79.49");
+        roundtrip(IndexEncoding::Implied, IndexValue::text("CO1"), ".67.79.49");
+        roundtrip(IndexEncoding::ObjectId, IndexValue::ObjectId(vec![1, 3, 6]), ".3.1.3.6");
+        roundtrip(IndexEncoding::ImpliedObjectId, IndexValue::ObjectId(vec![1, 3, 6]), ".1.3.6");
+        roundtrip(IndexEncoding::IpAddress, IndexValue::IpAddress([10, 0, 0, 1]), ".10.0.0.1");
+        roundtrip(IndexEncoding::LengthPrefixed, IndexValue::text(""), ".0");
+    }
+
+    #[test]
+    fn test_composite() {
+        let encoding = IndexEncoding::Composite(vec![
+            IndexEncoding::IpAddress,
+            IndexEncoding::LengthPrefixed,
+            IndexEncoding::Integer,
+            IndexEncoding::Implied,
+        ]);
+        let value = IndexValue::Composite(vec![
+            IndexValue::IpAddress([192, 168, 0, 5]),
+            IndexValue::text("CO"),
+            IndexValue::Integer(7),
+            IndexValue::text("AB"),
+        ]);
+        roundtrip(encoding, value, ".192.168.0.5.2.67.79.7.65.66");
+    }
+
+    #[test]
+    fn test_decode_prefix_leaves_tail() {
+        let (value, used) = IndexEncoding::LengthPrefixed
+            .decode_prefix(&[2, 67, 79, 5, 1])
+            .unwrap();
+        assert_eq!(value, IndexValue::text("CO"));
+        assert_eq!(used, 3);
+
+        assert_eq!(
+            IndexEncoding::LengthPrefixed.decode(&[2, 67, 79, 5]),
+            Err(IndexError::ExtraComponents(1))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            IndexEncoding::LengthPrefixed.decode(&[4, 67, 79]),
+            Err(IndexError::Truncated(2))
+        );
+        assert_eq!(
+            IndexEncoding::Implied.decode(&[67, 300]),
+            Err(IndexError::ByteOutOfRange(300))
+        );
+        assert_eq!(
+            IndexEncoding::FixedLength(4).encode(&IndexValue::text("CO1")),
+            Err(IndexError::WrongLength { expected: 4, found: 3 })
+        );
+        assert!(matches!(
+            IndexEncoding::Integer.encode(&IndexValue::text("1")),
+            Err(IndexError::TypeMismatch { .. })
+        ));
+
+        let bad = IndexEncoding::Composite(vec![IndexEncoding::Implied, IndexEncoding::Integer]);
+        assert_eq!(bad.decode(&[1, 2]), Err(IndexError::ImpliedNotLast));
+    }
+}
@@ -1,2 +1,6 @@
 pub mod ascii_converter;
-pub use ascii_converter::gen_scn_from_chars;
\ No newline at end of file
+pub mod error;
+pub mod index_encoding;
+pub use ascii_converter::{find_scn, gen_chars_from_scn, gen_scn_from_chars, scn_encoding, ScnMatch};
+pub use error::IndexError;
+pub use index_encoding::{format_components, parse_components, IndexEncoding, IndexValue};
@@ -0,0 +1,128 @@
+//! Компактный двоичный формат журнала
+//!
+//! ```text
+//! "DLOG" версия(1 байт) { Δt varint, (детектор << 1 | состояние) varint }*
+//! ```
+//!
+//! Время хранится приращением от предыдущего события в мс, числа — в
+//! LEB128 (7 бит на байт). Типичное событие занимает 2–4 байта против
+//! 15–20 в CSV.
+
+use crate::detector_log::error::DetectorLogError;
+use crate::detector_log::log::DetectorLog;
+use crate::simulation::DetectorEvent;
+
+/// Сигнатура двоичного журнала
+pub const MAGIC: &[u8; 4] = b"DLOG";
+const VERSION: u8 = 1;
+
+impl DetectorLog {
+    /// Кодирует журнал в двоичный формат
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(5 + self.events().len() * 3);
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+
+        let mut previous = 0;
+        for event in self.events() {
+            write_varint(&mut out, event.time_ms - previous);
+            write_varint(&mut out, (event.detector as u64) << 1 | u64::from(event.active));
+            previous = event.time_ms;
+        }
+        out
+    }
+
+    /// Разбирает двоичный журнал
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DetectorLogError> {
+        let body = data.strip_prefix(MAGIC).ok_or(DetectorLogError::BadMagic)?;
+        let (&version, _) = body.split_first().ok_or(DetectorLogError::Truncated(MAGIC.len()))?;
+        if version != VERSION {
+            return Err(DetectorLogError::UnsupportedVersion(version));
+        }
+
+        let mut position = MAGIC.len() + 1;
+        let mut time_ms: u64 = 0;
+        let mut events = Vec::new();
+        while position < data.len() {
+            let delta = read_varint(data, &mut position)?;
+            time_ms = time_ms.checked_add(delta).ok_or(DetectorLogError::Overflow(position))?;
+
+            let start = position;
+            let packed = read_varint(data, &mut position)?;
+            let detector = u32::try_from(packed >> 1).map_err(|_| DetectorLogError::Overflow(start))?;
+            events.push(DetectorEvent { time_ms, detector, active: packed & 1 == 1 });
+        }
+        Ok(Self::new(events))
+    }
+
+    /// Читает журнал из файла: двоичный формат узнаётся по сигнатуре,
+    /// иначе файл разбирается как CSV
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, DetectorLogError> {
+        let data = std::fs::read(path)?;
+        if data.starts_with(MAGIC) {
+            return Self::from_bytes(&data);
+        }
+        let text = String::from_utf8(data).map_err(|_| DetectorLogError::Csv {
+            line: 1,
+            message: "файл не в UTF-8".to_string(),
+        })?;
+        Self::from_csv(&text)
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn read_varint(data: &[u8], position: &mut usize) -> Result<u64, DetectorLogError> {
+    let start = *position;
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*position).ok_or(DetectorLogError::Truncated(*position))?;
+        *position += 1;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DetectorLogError::Overflow(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let log = DetectorLog::new(vec![
+            DetectorEvent::new(0, 1, true),
+            DetectorEvent::new(150, 200, true),
+            DetectorEvent::new(150, 1, false),
+            DetectorEvent::new(90_000_000, 200, false),
+        ]);
+        let bytes = log.to_bytes();
+        assert_eq!(&bytes[..7], b"DLOG\x01\x00\x03");
+        assert_eq!(DetectorLog::from_bytes(&bytes).unwrap(), log);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(DetectorLog::from_bytes(b"time,"), Err(DetectorLogError::BadMagic)));
+        assert!(matches!(
+            DetectorLog::from_bytes(b"DLOG\x02"),
+            Err(DetectorLogError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            DetectorLog::from_bytes(b"DLOG\x01\x05\x83"),
+            Err(DetectorLogError::Truncated(7))
+        ));
+    }
+}
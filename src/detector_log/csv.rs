@@ -0,0 +1,152 @@
+//! Чтение и запись журнала в CSV
+//!
+//! ```text
+//! time,detector,state
+//! 12.5,D3,1
+//! 13.1,D3,0
+//! 08:15:02.250;4;on
+//! ```
+//!
+//! Время — секунды от начала записи или время суток `ЧЧ:ММ:СС[.ммм]`;
+//! детектор — номер, можно с префиксом `D`; состояние — `1/0`, `on/off`,
+//! `true/false`. Разделитель `,` или `;`; необязательный заголовок — только
+//! первой значимой строкой.
+
+use crate::detector_log::error::DetectorLogError;
+use crate::detector_log::log::DetectorLog;
+use crate::simulation::DetectorEvent;
+
+impl DetectorLog {
+    /// Разбирает журнал из CSV
+    pub fn from_csv(text: &str) -> Result<Self, DetectorLogError> {
+        let mut events = Vec::new();
+        let mut first = true;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let header_allowed = std::mem::replace(&mut first, false);
+
+            let separator = if line.contains(';') { ';' } else { ',' };
+            let fields: Vec<&str> = line.split(separator).map(str::trim).collect();
+            let csv_error = |message: String| DetectorLogError::Csv { line: i + 1, message };
+
+            // Заголовок: только первая значимая строка, и в ней вместо времени — слово
+            if header_allowed && parse_time(fields[0]).is_none() && fields[0].starts_with(char::is_alphabetic) {
+                continue;
+            }
+            if fields.len() != 3 {
+                return Err(csv_error(format!("ожидалось 3 поля, получено {}", fields.len())));
+            }
+
+            let time_ms = parse_time(fields[0])
+                .ok_or_else(|| csv_error(format!("неверное время '{}'", fields[0])))?;
+            let detector = fields[1]
+                .trim_start_matches(['D', 'd'])
+                .parse()
+                .map_err(|_| csv_error(format!("неверный номер детектора '{}'", fields[1])))?;
+            let active = match fields[2].to_ascii_lowercase().as_str() {
+                "1" | "on" | "true" => true,
+                "0" | "off" | "false" => false,
+                other => return Err(csv_error(format!("неверное состояние '{}'", other))),
+            };
+
+            events.push(DetectorEvent { time_ms, detector, active });
+        }
+
+        Ok(Self::new(events))
+    }
+
+    /// Записывает журнал в CSV (время в секундах)
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("time,detector,state\n");
+        for event in self.events() {
+            out.push_str(&format!(
+                "{}.{:03},{},{}\n",
+                event.time_ms / 1000,
+                event.time_ms % 1000,
+                event.detector,
+                u8::from(event.active)
+            ));
+        }
+        out
+    }
+}
+
+/// "12.5" → 12500; "08:15:02.250" → мс от полуночи. Время, которое не
+/// помещается в u64 мс, — ошибка
+fn parse_time(text: &str) -> Option<u64> {
+    let text = text.replace(',', ".");
+    if !text.contains(':') {
+        return milliseconds(text.parse().ok()?);
+    }
+
+    let parts: Vec<&str> = text.split(':').collect();
+    let [hours, minutes, seconds] = parts[..] else {
+        return None;
+    };
+    let hours: u64 = hours.parse().ok()?;
+    let minutes: u64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+    if minutes >= 60 || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+    hours
+        .checked_mul(3600)?
+        .checked_add(minutes * 60)?
+        .checked_mul(1000)?
+        .checked_add(milliseconds(seconds)?)
+}
+
+/// Секунды → мс; отрицательное, бесконечное или слишком большое — `None`
+fn milliseconds(seconds: f64) -> Option<u64> {
+    let ms = (seconds * 1000.0).round();
+    // u64::MAX as f64 = 2^64, всё меньше помещается в u64
+    (ms.is_finite() && ms >= 0.0 && ms < u64::MAX as f64).then_some(ms as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_csv() {
+        let log = DetectorLog::from_csv(
+            "# запись СО 4554\ntime,detector,state\n1.5,D3,1\n0.25;4;on\n2,3,off\n08:00:01.5,d1,true\n",
+        )
+        .unwrap();
+        assert_eq!(
+            log.events(),
+            [
+                DetectorEvent::new(250, 4, true),
+                DetectorEvent::new(1500, 3, true),
+                DetectorEvent::new(2000, 3, false),
+                DetectorEvent::new(28_801_500, 1, true),
+            ]
+        );
+        assert_eq!(
+            DetectorLog::from_csv(&log.to_csv()).unwrap(),
+            log
+        );
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let error = |text: &str| DetectorLog::from_csv(text).unwrap_err().to_string();
+        assert_eq!(error("1,2,1\n1.5,X2,1"), "Строка 2: неверный номер детектора 'X2'");
+        assert_eq!(error("1,2,maybe"), "Строка 1: неверное состояние 'maybe'");
+        assert_eq!(error("1,2"), "Строка 1: ожидалось 3 поля, получено 2");
+        assert_eq!(error("1:99:00,2,1"), "Строка 1: неверное время '1:99:00'");
+        assert_eq!(
+            error("99999999999999999:00:00,1,1"),
+            "Строка 1: неверное время '99999999999999999:00:00'"
+        );
+        assert_eq!(error("1e20,1,1"), "Строка 1: неверное время '1e20'");
+        // Строка со словом вместо времени — заголовок, только если она первая
+        assert_eq!(error("time,detector,state\nвремя,2,1\n1,2,1"), "Строка 2: неверное время 'время'");
+        assert_eq!(error("# журнал\n\n1,2,1\n.5x,3,1"), "Строка 4: неверное время '.5x'");
+        assert_eq!(DetectorLog::from_csv(".5,3,1").unwrap().events(), [DetectorEvent::new(500, 3, true)]);
+    }
+}
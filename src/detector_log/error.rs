@@ -0,0 +1,31 @@
+//! Типы ошибок журнала детекторов
+
+use thiserror::Error;
+
+/// Ошибки чтения журнала событий детекторов
+#[derive(Error, Debug)]
+pub enum DetectorLogError {
+    /// Ошибка: строка CSV не разбирается
+    #[error("Строка {line}: {message}")]
+    Csv { line: usize, message: String },
+
+    /// Ошибка: файл не в двоичном формате журнала
+    #[error("Неверная сигнатура двоичного журнала")]
+    BadMagic,
+
+    /// Ошибка: неизвестная версия двоичного формата
+    #[error("Неподдерживаемая версия двоичного журнала: {0}")]
+    UnsupportedVersion(u8),
+
+    /// Ошибка: данные обрываются на середине записи
+    #[error("Двоичный журнал обрывается на позиции {0}")]
+    Truncated(usize),
+
+    /// Ошибка: число не помещается в поле
+    #[error("Слишком большое значение на позиции {0}")]
+    Overflow(usize),
+
+    /// Ошибка ввода-вывода
+    #[error("Ошибка ввода-вывода: {0}")]
+    Io(#[from] std::io::Error),
+}
@@ -0,0 +1,135 @@
+//! Журнал событий: интервалы занятости и состояние на момент времени
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::conditions::{evaluate, Expr};
+use crate::simulation::DetectorEvent;
+
+/// Интервал занятости детектора, мс от начала записи
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occupancy {
+    pub start_ms: u64,
+    /// `None` — детектор занят до конца записи
+    pub end_ms: Option<u64>,
+}
+
+impl Occupancy {
+    /// Занят ли детектор в момент `time_ms`
+    pub fn contains(&self, time_ms: u64) -> bool {
+        self.start_ms <= time_ms && self.end_ms.is_none_or(|end| time_ms < end)
+    }
+}
+
+/// Журнал событий детекторов, упорядоченный по времени
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DetectorLog {
+    events: Vec<DetectorEvent>,
+}
+
+impl DetectorLog {
+    /// Журнал из событий в любом порядке; события одного момента
+    /// сохраняют исходный порядок
+    pub fn new(mut events: Vec<DetectorEvent>) -> Self {
+        events.sort_by_key(|event| event.time_ms);
+        Self { events }
+    }
+
+    /// События по времени — например, для `simulation::simulate`
+    pub fn events(&self) -> &[DetectorEvent] {
+        &self.events
+    }
+
+    /// Время последнего события, мс
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map_or(0, |event| event.time_ms)
+    }
+
+    /// Номера детекторов, встречающихся в журнале
+    pub fn detectors(&self) -> BTreeSet<u32> {
+        self.events.iter().map(|event| event.detector).collect()
+    }
+
+    /// Интервалы занятости по детекторам.
+    ///
+    /// Повторное «занят» без «свободен» и «свободен» без «занят» (например,
+    /// детектор был занят до начала записи) пропускаются.
+    pub fn occupancy(&self) -> BTreeMap<u32, Vec<Occupancy>> {
+        let mut intervals: BTreeMap<u32, Vec<Occupancy>> = BTreeMap::new();
+
+        for event in &self.events {
+            let list = intervals.entry(event.detector).or_default();
+            let open = list.last_mut().filter(|last| last.end_ms.is_none());
+            match (event.active, open) {
+                (true, None) => list.push(Occupancy { start_ms: event.time_ms, end_ms: None }),
+                (false, Some(last)) => last.end_ms = Some(event.time_ms),
+                _ => {}
+            }
+        }
+
+        intervals.retain(|_, list| !list.is_empty());
+        intervals
+    }
+
+    /// Занятые детекторы в момент `time_ms` (с учётом событий этого момента)
+    pub fn snapshot(&self, time_ms: u64) -> BTreeSet<u32> {
+        let mut active = BTreeSet::new();
+        for event in self.events.iter().take_while(|event| event.time_ms <= time_ms) {
+            if event.active {
+                active.insert(event.detector);
+            } else {
+                active.remove(&event.detector);
+            }
+        }
+        active
+    }
+
+    /// Значение условия в момент `time_ms`
+    pub fn evaluate_at(&self, expr: &Expr, time_ms: u64) -> bool {
+        evaluate(expr, &self.snapshot(time_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::parse_ddr_expression;
+
+    fn log() -> DetectorLog {
+        DetectorLog::new(vec![
+            DetectorEvent::new(5000, 1, false),
+            DetectorEvent::new(1000, 1, true),
+            DetectorEvent::new(2000, 2, false),
+            DetectorEvent::new(3000, 2, true),
+            DetectorEvent::new(3500, 2, true),
+            DetectorEvent::new(8000, 1, true),
+        ])
+    }
+
+    #[test]
+    fn test_occupancy() {
+        let occupancy = log().occupancy();
+        assert_eq!(
+            occupancy[&1],
+            [
+                Occupancy { start_ms: 1000, end_ms: Some(5000) },
+                Occupancy { start_ms: 8000, end_ms: None },
+            ]
+        );
+        assert_eq!(occupancy[&2], [Occupancy { start_ms: 3000, end_ms: None }]);
+        assert!(occupancy[&1][0].contains(4999));
+        assert!(!occupancy[&1][0].contains(5000));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let log = log();
+        assert_eq!(log.snapshot(0), BTreeSet::new());
+        assert_eq!(log.snapshot(3000), BTreeSet::from([1, 2]));
+        assert_eq!(log.snapshot(6000), BTreeSet::from([2]));
+
+        let expr = parse_ddr_expression("and 1-2").unwrap();
+        assert!(log.evaluate_at(&expr, 4000));
+        assert!(!log.evaluate_at(&expr, 6000));
+        assert_eq!(log.duration_ms(), 8000);
+    }
+}
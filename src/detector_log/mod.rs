@@ -0,0 +1,29 @@
+//! Журнал событий детекторов
+//!
+//! Читает записи «время, детектор, занят/свободен» из CSV или
+//! компактного двоичного формата, превращает их в интервалы занятости
+//! и даёт состояние детекторов на любой момент — для вычисления условий
+//! и для симуляции (`simulation::simulate`).
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::parse_ddr_expression;
+//! use traffic_core::detector_log::DetectorLog;
+//!
+//! let log = DetectorLog::from_csv("time,detector,state\n1.0,3,1\n2.5,3,0\n").unwrap();
+//! let expr = parse_ddr_expression("3 or 4").unwrap();
+//! assert!(log.evaluate_at(&expr, 2000));
+//! assert!(!log.evaluate_at(&expr, 2500));
+//!
+//! let bytes = log.to_bytes();
+//! assert_eq!(DetectorLog::from_bytes(&bytes).unwrap(), log);
+//! ```
+
+mod binary;  // binary.rs — двоичный формат
+mod csv;     // csv.rs — чтение и запись CSV
+mod error;   // error.rs — типы ошибок
+mod log;     // log.rs — журнал, интервалы занятости, срезы
+
+pub use binary::MAGIC;
+pub use error::DetectorLogError;
+pub use log::{DetectorLog, Occupancy};
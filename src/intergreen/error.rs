@@ -0,0 +1,36 @@
+//! Типы ошибок матрицы промтактов
+
+use thiserror::Error;
+
+/// Ошибки разбора и проверки матрицы промтактов
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum IntergreenError {
+    /// Ошибка: таблица CSV не разбирается
+    #[error("Строка {line}: {message}")]
+    Csv { line: usize, message: String },
+
+    /// Ошибка: направления нет в матрице или на перекрёстке
+    #[error("Неизвестное направление '{0}'")]
+    UnknownPhase(String),
+
+    /// Ошибка: направление перекрёстка не описано в матрице
+    #[error("Направление '{0}' отсутствует в матрице промтактов")]
+    MissingPhase(String),
+
+    /// Ошибка: промтакт направления с самим собой
+    #[error("Промтакт {phase} → {phase} должен быть пустым или 0, указано {value} с")]
+    NonZeroDiagonal { phase: String, value: u32 },
+
+    /// Ошибка: конфликт задан только в одну сторону
+    #[error("Конфликт {from} → {to} задан, а {to} → {from} — нет")]
+    AsymmetricConflict { from: String, to: String },
+
+    /// Ошибка: промтакт меньше допустимого для типа направления
+    #[error("Промтакт {from} → {to} = {value} с меньше минимума {minimum} с")]
+    BelowMinimum {
+        from: String,
+        to: String,
+        value: u32,
+        minimum: u32,
+    },
+}
@@ -0,0 +1,346 @@
+//! Матрица промтактов: разбор, проверка, промтакты между фазами
+
+use std::collections::BTreeMap;
+
+use crate::intergreen::error::IntergreenError;
+use crate::junction::{Junction, PhaseKind, Stage};
+
+/// Минимальные промтакты по типу завершающего направления, секунды
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Minimums {
+    pub vehicle: u32,
+    pub pedestrian: u32,
+    pub filter_arrow: u32,
+}
+
+impl Minimums {
+    /// Минимум для направления заданного типа
+    pub fn for_kind(&self, kind: PhaseKind) -> u32 {
+        match kind {
+            PhaseKind::Vehicle => self.vehicle,
+            PhaseKind::Pedestrian => self.pedestrian,
+            PhaseKind::FilterArrow => self.filter_arrow,
+        }
+    }
+}
+
+impl Default for Minimums {
+    fn default() -> Self {
+        Self {
+            vehicle: 3,
+            pedestrian: 4,
+            filter_arrow: 3,
+        }
+    }
+}
+
+/// Квадратная матрица промтактов по именам направлений
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntergreenMatrix {
+    phases: Vec<String>,
+    /// Строка — завершающее направление, столбец — начинающее
+    values: Vec<Option<u32>>,
+}
+
+impl IntergreenMatrix {
+    /// Пустая матрица (без конфликтов) для заданных направлений
+    pub fn new(phases: &[&str]) -> Self {
+        Self {
+            phases: phases.iter().map(|phase| phase.to_string()).collect(),
+            values: vec![None; phases.len() * phases.len()],
+        }
+    }
+
+    /// Разбирает таблицу CSV: первая строка — заголовок с направлениями,
+    /// далее по строке на завершающее направление. Разделитель `;` или `,`,
+    /// пустая клетка или `-` — нет конфликта, строки с `#` пропускаются.
+    pub fn from_csv(text: &str) -> Result<Self, IntergreenError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let Some((header_line, header)) = lines.next() else {
+            return Err(IntergreenError::Csv { line: 1, message: "таблица пуста".to_string() });
+        };
+        let separator = if header.contains(';') { ';' } else { ',' };
+
+        let phases: Vec<&str> = header.split(separator).skip(1).map(str::trim).collect();
+        for (i, phase) in phases.iter().enumerate() {
+            if phase.is_empty() || phases[..i].contains(phase) {
+                return Err(IntergreenError::Csv {
+                    line: header_line,
+                    message: format!("пустое или повторное имя направления '{}'", phase),
+                });
+            }
+        }
+
+        let mut matrix = Self::new(&phases);
+        let mut seen = vec![false; phases.len()];
+
+        for (line, row) in lines {
+            let csv_error = |message: String| IntergreenError::Csv { line, message };
+            let mut cells = row.split(separator).map(str::trim);
+            let name = cells.next().unwrap_or_default();
+            let from = matrix
+                .index(name)
+                .ok_or_else(|| csv_error(format!("направления '{}' нет в заголовке", name)))?;
+            if std::mem::replace(&mut seen[from], true) {
+                return Err(csv_error(format!("строка направления '{}' повторяется", name)));
+            }
+
+            let cells: Vec<&str> = cells.collect();
+            if cells.len() != phases.len() {
+                return Err(csv_error(format!(
+                    "ожидалось {} значений, получено {}",
+                    phases.len(),
+                    cells.len()
+                )));
+            }
+
+            for (to, cell) in cells.into_iter().enumerate() {
+                let value = match cell {
+                    "" | "-" => None,
+                    _ => Some(cell.parse::<u32>().map_err(|_| {
+                        csv_error(format!("'{}' не является числом секунд", cell))
+                    })?),
+                };
+                matrix.values[from * phases.len() + to] = value;
+            }
+        }
+
+        if let Some(missing) = seen.iter().position(|seen| !seen) {
+            return Err(IntergreenError::Csv {
+                line: header_line,
+                message: format!("нет строки для направления '{}'", phases[missing]),
+            });
+        }
+        Ok(matrix)
+    }
+
+    /// Таблица CSV с разделителем `;` — в том же виде, что читает `from_csv`
+    pub fn to_csv(&self) -> String {
+        let mut out = format!(";{}\n", self.phases.join(";"));
+        for (from, phase) in self.phases.iter().enumerate() {
+            let cells: Vec<String> = (0..self.phases.len())
+                .map(|to| self.values[from * self.phases.len() + to].map_or(String::new(), |v| v.to_string()))
+                .collect();
+            out.push_str(&format!("{};{}\n", phase, cells.join(";")));
+        }
+        out
+    }
+
+    /// Направления матрицы в порядке столбцов
+    pub fn phases(&self) -> &[String] {
+        &self.phases
+    }
+
+    fn index(&self, phase: &str) -> Option<usize> {
+        self.phases.iter().position(|p| p == phase)
+    }
+
+    /// Промтакт `from → to`; `None` — не конфликтуют или направления нет в матрице
+    pub fn get(&self, from: &str, to: &str) -> Option<u32> {
+        let (from, to) = (self.index(from)?, self.index(to)?);
+        self.values[from * self.phases.len() + to]
+    }
+
+    /// Задаёт промтакт `from → to`
+    pub fn set(&mut self, from: &str, to: &str, value: Option<u32>) -> Result<(), IntergreenError> {
+        let unknown = |phase: &str| IntergreenError::UnknownPhase(phase.to_string());
+        let from = self.index(from).ok_or_else(|| unknown(from))?;
+        let to = self.index(to).ok_or_else(|| unknown(to))?;
+        let n = self.phases.len();
+        self.values[from * n + to] = value;
+        Ok(())
+    }
+
+    /// Конфликтуют ли направления (промтакт задан хотя бы в одну сторону)
+    pub fn conflicts(&self, a: &str, b: &str) -> bool {
+        self.get(a, b).is_some() || self.get(b, a).is_some()
+    }
+
+    /// Проверяет матрицу для перекрёстка: направления совпадают с описанными,
+    /// диагональ пустая или нулевая, конфликты симметричны, промтакты не меньше
+    /// минимума для типа завершающего направления. Возвращает все ошибки.
+    pub fn validate(&self, junction: &Junction, minimums: &Minimums) -> Result<(), Vec<IntergreenError>> {
+        let mut errors = Vec::new();
+
+        for phase in &self.phases {
+            if junction.phase(phase).is_none() {
+                errors.push(IntergreenError::UnknownPhase(phase.clone()));
+            }
+        }
+        for phase in &junction.phases {
+            if self.index(&phase.name).is_none() {
+                errors.push(IntergreenError::MissingPhase(phase.name.clone()));
+            }
+        }
+
+        for from in &self.phases {
+            for to in &self.phases {
+                let Some(value) = self.get(from, to) else {
+                    continue;
+                };
+
+                if from == to {
+                    if value != 0 {
+                        errors.push(IntergreenError::NonZeroDiagonal { phase: from.clone(), value });
+                    }
+                    continue;
+                }
+
+                if self.get(to, from).is_none() {
+                    errors.push(IntergreenError::AsymmetricConflict {
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                }
+
+                if let Some(phase) = junction.phase(from) {
+                    let minimum = minimums.for_kind(phase.kind);
+                    if value < minimum {
+                        errors.push(IntergreenError::BelowMinimum {
+                            from: from.clone(),
+                            to: to.clone(),
+                            value,
+                            minimum,
+                        });
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Промтакт при смене фазы `from` на `to`: наибольший промтакт между
+    /// завершающимися и начинающимися направлениями. Направления, которые
+    /// горят в обеих фазах, не учитываются. 0 — конфликтов нет.
+    pub fn stage_intergreen(&self, from: &Stage, to: &Stage) -> u32 {
+        let ending = from.phases.iter().filter(|phase| !to.phases.contains(phase));
+        ending
+            .flat_map(|end| {
+                to.phases
+                    .iter()
+                    .filter(|start| !from.phases.contains(start))
+                    .filter_map(move |start| self.get(end, start))
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Промтакты для всех пар фаз перекрёстка: (из, в) → секунды
+    pub fn stage_intergreens(&self, junction: &Junction) -> BTreeMap<(u32, u32), u32> {
+        let mut result = BTreeMap::new();
+        for from in &junction.stages {
+            for to in &junction.stages {
+                if from.number != to.number {
+                    result.insert((from.number, to.number), self.stage_intergreen(from, to));
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::junction::Phase;
+
+    const TABLE: &str = "\
+# промтакты СО 4554
+;A;B;P
+A;0;5;6
+B;4;;-
+P;8;;
+";
+
+    fn junction() -> Junction {
+        let mut junction = Junction::new("CO4554");
+        junction.add_phase(Phase::new("A", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("B", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("P", PhaseKind::Pedestrian));
+        junction.add_stage(Stage::new(1, &["A"]));
+        junction.add_stage(Stage::new(2, &["B", "P"]));
+        junction.add_stage(Stage::new(3, &["B"]));
+        junction
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let matrix = IntergreenMatrix::from_csv(TABLE).unwrap();
+        assert_eq!(matrix.phases(), ["A", "B", "P"]);
+        assert_eq!(matrix.get("A", "P"), Some(6));
+        assert_eq!(matrix.get("B", "P"), None);
+        assert!(matrix.conflicts("P", "A"));
+        assert_eq!(matrix.to_csv(), ";A;B;P\nA;0;5;6\nB;4;;\nP;8;;\n");
+        assert_eq!(IntergreenMatrix::from_csv(&matrix.to_csv()).unwrap(), matrix);
+
+        let comma = IntergreenMatrix::from_csv(",A,B\nB,4,\nA,,5\n").unwrap();
+        assert_eq!(comma.get("A", "B"), Some(5));
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let error = |text: &str| IntergreenMatrix::from_csv(text).unwrap_err().to_string();
+        assert_eq!(error(";A;B\nA;;x\nB;;\n"), "Строка 2: 'x' не является числом секунд");
+        assert_eq!(error(";A;B\nA;;5\nC;;\n"), "Строка 3: направления 'C' нет в заголовке");
+        assert_eq!(error(";A;B\nA;;5;\nB;;\n"), "Строка 2: ожидалось 2 значений, получено 3");
+        assert_eq!(error(";A;B\nA;;5\n"), "Строка 1: нет строки для направления 'B'");
+    }
+
+    #[test]
+    fn test_validate() {
+        let matrix = IntergreenMatrix::from_csv(TABLE).unwrap();
+        assert_eq!(matrix.validate(&junction(), &Minimums::default()), Ok(()));
+
+        let mut bad = matrix.clone();
+        bad.set("A", "A", Some(2)).unwrap();
+        bad.set("B", "A", None).unwrap();
+        bad.set("P", "A", Some(3)).unwrap();
+        assert_eq!(
+            bad.validate(&junction(), &Minimums::default()),
+            Err(vec![
+                IntergreenError::NonZeroDiagonal { phase: "A".to_string(), value: 2 },
+                IntergreenError::AsymmetricConflict { from: "A".to_string(), to: "B".to_string() },
+                IntergreenError::BelowMinimum {
+                    from: "P".to_string(),
+                    to: "A".to_string(),
+                    value: 3,
+                    minimum: 4,
+                },
+            ])
+        );
+
+        let mut other = junction();
+        other.add_phase(Phase::new("C", PhaseKind::FilterArrow));
+        assert_eq!(
+            matrix.validate(&other, &Minimums::default()),
+            Err(vec![IntergreenError::MissingPhase("C".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_stage_intergreens() {
+        let matrix = IntergreenMatrix::from_csv(TABLE).unwrap();
+        let stages = matrix.stage_intergreens(&junction());
+
+        // 1 → 2: завершается A, начинаются B и P: max(5, 6)
+        assert_eq!(stages[&(1, 2)], 6);
+        // 2 → 1: max(B→A = 4, P→A = 8)
+        assert_eq!(stages[&(2, 1)], 8);
+        // 2 → 3: B продолжает гореть, P завершается, начинающихся нет
+        assert_eq!(stages[&(2, 3)], 0);
+        // 3 → 2: B продолжает гореть, P начинается без конфликтов
+        assert_eq!(stages[&(3, 2)], 0);
+        assert_eq!(stages[&(1, 3)], 5);
+        assert_eq!(stages.len(), 6);
+    }
+}
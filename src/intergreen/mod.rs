@@ -0,0 +1,30 @@
+//! Матрица промтактов (межфазных интервалов)
+//!
+//! Промтакт `A → B` — время в секундах от конца зелёного сигнала
+//! направления `A` до начала зелёного конфликтующего направления `B`.
+//! Пустая клетка — направления не конфликтуют.
+//!
+//! Матрица читается из таблицы, в которой её ведут инженеры
+//! (строки — завершающие направления, столбцы — начинающие):
+//!
+//! ```text
+//! ;A;B;P
+//! A;;5;6
+//! B;4;;
+//! P;8;;
+//! ```
+//!
+//! # Пример
+//! ```
+//! use traffic_core::intergreen::IntergreenMatrix;
+//!
+//! let matrix = IntergreenMatrix::from_csv(";A;B\nA;;5\nB;4;\n").unwrap();
+//! assert_eq!(matrix.get("A", "B"), Some(5));
+//! assert_eq!(matrix.get("A", "A"), None);
+//! ```
+
+mod error;   // error.rs — типы ошибок
+mod matrix;  // matrix.rs — матрица, разбор CSV, проверка, промтакты фаз
+
+pub use error::IntergreenError;
+pub use matrix::{IntergreenMatrix, Minimums};
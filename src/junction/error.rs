@@ -0,0 +1,33 @@
+//! Типы ошибок модели перекрёстка
+
+use thiserror::Error;
+
+use crate::junction::model::ConditionKind;
+
+/// Ошибки проверки модели перекрёстка
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum JunctionError {
+    /// Ошибка: два направления с одним именем
+    #[error("Направление '{0}' описано дважды")]
+    DuplicatePhase(String),
+
+    /// Ошибка: две фазы с одним номером
+    #[error("Фаза {0} описана дважды")]
+    DuplicateStage(u32),
+
+    /// Ошибка: фаза ссылается на неописанное направление
+    #[error("Фаза {stage}: неизвестное направление '{phase}'")]
+    UnknownPhase { stage: u32, phase: String },
+
+    /// Ошибка: в фазе нет ни одного направления
+    #[error("Фаза {0}: нет ни одного направления")]
+    EmptyStage(u32),
+
+    /// Ошибка: условие ссылается на детектор, которого нет на перекрёстке
+    #[error("Фаза {stage}: условие {kind} ссылается на несуществующий детектор D{detector}")]
+    UnknownDetector {
+        stage: u32,
+        kind: ConditionKind,
+        detector: u32,
+    },
+}
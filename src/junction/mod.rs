@@ -0,0 +1,32 @@
+//! Модель светофорного объекта
+//!
+//! Направления (phases) — транспортные, пешеходные и дополнительные
+//! секции (стрелки); фазы (stages) — наборы одновременно разрешённых
+//! направлений. Для каждой фазы задаются условия вызова и продления
+//! в виде `conditions::Expr`.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::parse_ddr_expression;
+//! use traffic_core::junction::{Junction, Phase, PhaseKind, Stage};
+//!
+//! let mut junction = Junction::new("CO4554");
+//! junction.add_detectors(1..=4);
+//! junction.add_phase(Phase::new("A", PhaseKind::Vehicle));
+//! junction.add_phase(Phase::new("P1", PhaseKind::Pedestrian));
+//! junction.add_stage(
+//!     Stage::new(1, &["A"]).with_demand(parse_ddr_expression("1-2").unwrap()),
+//! );
+//! junction.add_stage(
+//!     Stage::new(2, &["P1"]).with_demand(parse_ddr_expression("4 or 7").unwrap()),
+//! );
+//!
+//! let errors = junction.validate().unwrap_err();
+//! assert_eq!(errors[0].to_string(), "Фаза 2: условие вызова ссылается на несуществующий детектор D7");
+//! ```
+
+mod error;  // error.rs — типы ошибок
+mod model;  // model.rs — направления, фазы, перекрёсток
+
+pub use error::JunctionError;
+pub use model::{ConditionKind, Junction, Phase, PhaseKind, Stage};
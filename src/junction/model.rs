@@ -0,0 +1,287 @@
+//! Направления, фазы и перекрёсток
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use crate::conditions::Expr;
+use crate::junction::error::JunctionError;
+
+/// Тип направления
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhaseKind {
+    /// Транспортное направление
+    Vehicle,
+    /// Пешеходное направление
+    Pedestrian,
+    /// Дополнительная секция (стрелка)
+    FilterArrow,
+}
+
+impl fmt::Display for PhaseKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhaseKind::Vehicle => write!(f, "транспортное"),
+            PhaseKind::Pedestrian => write!(f, "пешеходное"),
+            PhaseKind::FilterArrow => write!(f, "стрелка"),
+        }
+    }
+}
+
+/// Направление (сигнальная группа)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phase {
+    /// Имя направления: "A", "P1"...
+    pub name: String,
+    pub kind: PhaseKind,
+}
+
+impl Phase {
+    pub fn new(name: &str, kind: PhaseKind) -> Self {
+        Self { name: name.to_string(), kind }
+    }
+}
+
+/// Назначение условия фазы
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionKind {
+    /// Вызов фазы
+    Demand,
+    /// Продление фазы
+    Extension,
+}
+
+impl fmt::Display for ConditionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionKind::Demand => write!(f, "вызова"),
+            ConditionKind::Extension => write!(f, "продления"),
+        }
+    }
+}
+
+/// Фаза: одновременно разрешённые направления и её условия
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stage {
+    /// Номер фазы
+    pub number: u32,
+
+    /// Имена направлений, разрешённых в фазе
+    pub phases: Vec<String>,
+
+    /// Условие вызова; `None` — фаза вызывается всегда
+    pub demand: Option<Expr>,
+
+    /// Условие продления; `None` — фаза не продлевается
+    pub extension: Option<Expr>,
+}
+
+impl Stage {
+    /// Фаза без условий
+    pub fn new(number: u32, phases: &[&str]) -> Self {
+        Self {
+            number,
+            phases: phases.iter().map(|phase| phase.to_string()).collect(),
+            demand: None,
+            extension: None,
+        }
+    }
+
+    /// Задаёт условие вызова
+    pub fn with_demand(mut self, demand: Expr) -> Self {
+        self.demand = Some(demand);
+        self
+    }
+
+    /// Задаёт условие продления
+    pub fn with_extension(mut self, extension: Expr) -> Self {
+        self.extension = Some(extension);
+        self
+    }
+
+    /// Условие заданного назначения
+    pub fn condition(&self, kind: ConditionKind) -> Option<&Expr> {
+        match kind {
+            ConditionKind::Demand => self.demand.as_ref(),
+            ConditionKind::Extension => self.extension.as_ref(),
+        }
+    }
+}
+
+/// Светофорный объект
+#[derive(Debug, Clone, Default)]
+pub struct Junction {
+    /// Имя объекта (обычно SCN)
+    pub name: String,
+
+    /// Номера детекторов, подключённых к контроллеру
+    pub detectors: BTreeSet<u32>,
+
+    pub phases: Vec<Phase>,
+    pub stages: Vec<Stage>,
+}
+
+impl Junction {
+    /// Пустой перекрёсток
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Добавляет детекторы
+    pub fn add_detectors(&mut self, detectors: impl IntoIterator<Item = u32>) {
+        self.detectors.extend(detectors);
+    }
+
+    /// Добавляет направление
+    pub fn add_phase(&mut self, phase: Phase) {
+        self.phases.push(phase);
+    }
+
+    /// Добавляет фазу
+    pub fn add_stage(&mut self, stage: Stage) {
+        self.stages.push(stage);
+    }
+
+    /// Направление по имени
+    pub fn phase(&self, name: &str) -> Option<&Phase> {
+        self.phases.iter().find(|phase| phase.name == name)
+    }
+
+    /// Фаза по номеру
+    pub fn stage(&self, number: u32) -> Option<&Stage> {
+        self.stages.iter().find(|stage| stage.number == number)
+    }
+
+    /// Номер направления — его порядковый номер в описании, начиная с 1.
+    /// Так направления нумеруются в контроллере и в условиях.
+    pub fn phase_number(&self, name: &str) -> Option<u32> {
+        self.phases
+            .iter()
+            .position(|phase| phase.name == name)
+            .map(|index| index as u32 + 1)
+    }
+
+    /// Номера направлений фазы; неизвестные направления пропускаются
+    pub fn stage_phase_numbers(&self, stage: &Stage) -> BTreeSet<u32> {
+        stage
+            .phases
+            .iter()
+            .filter_map(|phase| self.phase_number(phase))
+            .collect()
+    }
+
+    /// Проверяет модель: уникальность имён, ссылки фаз на направления
+    /// и на существующие детекторы. Возвращает все найденные ошибки.
+    pub fn validate(&self) -> Result<(), Vec<JunctionError>> {
+        let mut errors = Vec::new();
+
+        let mut names = HashSet::new();
+        for phase in &self.phases {
+            if !names.insert(phase.name.as_str()) {
+                errors.push(JunctionError::DuplicatePhase(phase.name.clone()));
+            }
+        }
+
+        let mut numbers = HashSet::new();
+        for stage in &self.stages {
+            if !numbers.insert(stage.number) {
+                errors.push(JunctionError::DuplicateStage(stage.number));
+            }
+            if stage.phases.is_empty() {
+                errors.push(JunctionError::EmptyStage(stage.number));
+            }
+            for phase in &stage.phases {
+                if self.phase(phase).is_none() {
+                    errors.push(JunctionError::UnknownPhase {
+                        stage: stage.number,
+                        phase: phase.clone(),
+                    });
+                }
+            }
+
+            for kind in [ConditionKind::Demand, ConditionKind::Extension] {
+                let Some(expr) = stage.condition(kind) else {
+                    continue;
+                };
+                for detector in expr.detectors() {
+                    if !self.detectors.contains(&detector) {
+                        errors.push(JunctionError::UnknownDetector {
+                            stage: stage.number,
+                            kind,
+                            detector,
+                        });
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::parse_ddr_expression;
+
+    fn junction() -> Junction {
+        let mut junction = Junction::new("CO4554");
+        junction.add_detectors(1..=6);
+        junction.add_phase(Phase::new("A", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("B", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("C", PhaseKind::FilterArrow));
+        junction.add_phase(Phase::new("P", PhaseKind::Pedestrian));
+        junction.add_stage(
+            Stage::new(1, &["A", "C"])
+                .with_demand(parse_ddr_expression("1-2").unwrap())
+                .with_extension(parse_ddr_expression("and 1-2").unwrap()),
+        );
+        junction.add_stage(Stage::new(2, &["B", "P"]).with_demand(parse_ddr_expression("3-6").unwrap()));
+        junction
+    }
+
+    #[test]
+    fn test_valid_junction() {
+        let junction = junction();
+        assert_eq!(junction.validate(), Ok(()));
+        assert_eq!(junction.phase("C").unwrap().kind, PhaseKind::FilterArrow);
+        assert!(junction.stage(1).unwrap().condition(ConditionKind::Extension).is_some());
+        assert!(junction.stage(2).unwrap().condition(ConditionKind::Extension).is_none());
+        assert_eq!(junction.phase_number("C"), Some(3));
+        assert_eq!(
+            junction.stage_phase_numbers(junction.stage(2).unwrap()),
+            BTreeSet::from([2, 4])
+        );
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let mut junction = junction();
+        junction.add_phase(Phase::new("A", PhaseKind::Vehicle));
+        junction.add_stage(
+            Stage::new(2, &["X"]).with_extension(parse_ddr_expression("6 or 9").unwrap()),
+        );
+        junction.add_stage(Stage::new(3, &[]));
+
+        assert_eq!(
+            junction.validate(),
+            Err(vec![
+                JunctionError::DuplicatePhase("A".to_string()),
+                JunctionError::DuplicateStage(2),
+                JunctionError::UnknownPhase { stage: 2, phase: "X".to_string() },
+                JunctionError::UnknownDetector {
+                    stage: 2,
+                    kind: ConditionKind::Extension,
+                    detector: 9,
+                },
+                JunctionError::EmptyStage(3),
+            ])
+        );
+    }
+}
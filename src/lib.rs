@@ -1,6 +1,11 @@
 // src/lib.rs
 pub mod conditions;  // просто реэкспортируем весь модуль
-
-// Теперь другие части проекта могут писать:
-use crate::conditions::parse_ddr_expression;
 pub mod converters;
+pub mod snmp;        // SNMP-кодек, клиент и симулятор контроллера
+pub mod junction;    // модель перекрёстка: направления, фазы, условия
+pub mod intergreen;  // матрица промтактов
+pub mod conflict;    // конфликты направлений и совместимость фаз
+pub mod timing;      // расчёт жёсткого плана по Вебстеру
+pub mod simulation;  // офлайн-симуляция адаптивного управления
+pub mod detector_log; // журнал событий детекторов
+pub mod repl;        // интерактивный редактор условий
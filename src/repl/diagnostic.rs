@@ -0,0 +1,62 @@
+//! Диагностика ошибок разбора с указателем-кареткой
+//!
+//! ```text
+//! ошибка: Неизвестный оператор 'xor' на позиции 6. Используйте and/or или &/|
+//!   | (1-3) xor (4-6)
+//!   |       ^^^
+//! ```
+
+use crate::conditions::ParseError;
+
+// ANSI-цвета терминала
+pub(crate) const RED: &str = "\x1b[31m";
+pub(crate) const GREEN: &str = "\x1b[32m";
+pub(crate) const CYAN: &str = "\x1b[36m";
+pub(crate) const BOLD: &str = "\x1b[1m";
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// Оборачивает текст в ANSI-стиль, если цвет включён
+pub(crate) fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", style, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Печатает ошибку с исходной строкой и подчёркиванием проблемного места.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conditions::parse_ddr_expression;
+/// use traffic_core::repl::render_error;
+///
+/// let source = "(1-3) xor (4-6)";
+/// let error = parse_ddr_expression(source).unwrap_err();
+/// let text = render_error(source, &error, false);
+/// assert!(text.ends_with("  |       ^^^"));
+/// ```
+pub fn render_error(source: &str, error: &ParseError, color: bool) -> String {
+    let header = format!("{} {}", paint("ошибка:", &format!("{}{}", BOLD, RED), color), error);
+    let Some(span) = error.span() else {
+        return header;
+    };
+
+    // Смещения в байтах переводим в колонки символов
+    let start = span.start.min(source.len());
+    let end = span.end.clamp(start, source.len());
+    let column = source[..start].chars().count();
+    let width = source[start..end].chars().count().max(1);
+
+    let gutter = paint("  |", CYAN, color);
+    let caret = paint(&"^".repeat(width), &format!("{}{}", BOLD, RED), color);
+    format!(
+        "{}\n{} {}\n{} {}{}",
+        header,
+        gutter,
+        source,
+        gutter,
+        " ".repeat(column),
+        caret
+    )
+}
@@ -0,0 +1,75 @@
+//! Интерактивный REPL для составления условий
+//!
+//! Возможности:
+//! - история ввода между запусками (`~/.traffic_core_history`);
+//! - `:eval 1,4,5` — вычисление при заданных занятых детекторах;
+//! - `:table` — таблица истинности;
+//! - `:simplify` — упрощение последнего условия;
+//! - `:dialect <имя>`, `:symbols on` — формат вывода;
+//! - `:def ИМЯ = условие` и `$ИМЯ` — именованные подусловия;
+//! - цветная диагностика ошибок с указателем на позицию.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::repl::{Reply, Session};
+//!
+//! let mut session = Session::new(false);
+//! session.handle("(or 1-2) and (or 4-5)");
+//! assert_eq!(session.handle(":eval 1,4"), Reply::Output("true".to_string()));
+//! ```
+
+mod diagnostic;  // diagnostic.rs — вывод ошибок с кареткой
+mod session;     // session.rs — состояние и команды
+
+pub use diagnostic::render_error;
+pub use session::{Reply, Session};
+
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+/// Файл истории в домашнем каталоге
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".traffic_core_history"))
+}
+
+/// Запускает REPL в текущем терминале
+pub fn run() -> rustyline::Result<()> {
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let mut session = Session::new(color);
+    let mut editor = DefaultEditor::new()?;
+
+    let history = history_path();
+    if let Some(path) = &history {
+        // Первого запуска без файла истории не боимся
+        let _ = editor.load_history(path);
+    }
+
+    println!("Интерактивный редактор условий. :help — справка, :quit — выход\n");
+
+    loop {
+        match editor.readline("> ") {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    editor.add_history_entry(line.as_str())?;
+                }
+                match session.handle(&line) {
+                    Reply::Output(text) if text.is_empty() => {}
+                    Reply::Output(text) => println!("{}", text),
+                    Reply::Quit => break,
+                }
+            }
+            // Ctrl-C сбрасывает строку, Ctrl-D выходит
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    if let Some(path) = &history {
+        editor.save_history(path)?;
+    }
+    Ok(())
+}
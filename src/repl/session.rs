@@ -0,0 +1,470 @@
+//! Состояние и команды REPL
+//!
+//! Сессия не знает о терминале: получает строку, возвращает текст ответа.
+//! Благодаря этому команды проверяются обычными тестами.
+
+use crate::conditions::{
+    parse_ddr_expression_with_symbols, simplify, to_ddr_string_with_options, try_evaluate, Dialect,
+    Expr, GenerateOptions, Library, Measurements, ParseError, SymbolTable,
+};
+use crate::repl::diagnostic::{paint, render_error, BOLD, CYAN, GREEN, RED};
+
+/// Больше детекторов в таблице истинности не выводим (2^10 = 1024 строки)
+const MAX_TABLE_DETECTORS: usize = 10;
+
+const HELP: &str = "\
+Введите условие, например: (or 1-3) and (or 4-6)
+
+Команды:
+  :eval 1,4,5         вычислить последнее условие при занятых детекторах 1, 4, 5
+  :eval 1 occ(D3)=75  то же с измерениями occ/cnt для сравнений
+  :table              таблица истинности последнего условия
+  :simplify           упростить последнее условие
+  :dialect [имя]      показать диалекты или выбрать диалект вывода
+  :symbols on|off     символы &/| вместо and/or
+  ИМЯ = условие       определить именованное условие; использовать как $ИМЯ
+  :def ИМЯ = условие  то же самое
+  :defs               список именованных условий
+  :load ФАЙЛ          загрузить имена из файла-библиотеки
+  :undef ИМЯ          удалить именованное условие
+  :help               эта справка
+  :quit               выход (также exit, quit, Ctrl-D)";
+
+/// Ответ сессии на строку ввода
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    /// Текст для вывода (может быть пустым)
+    Output(String),
+    /// Пользователь попросил выйти
+    Quit,
+}
+
+/// Состояние интерактивной сессии
+pub struct Session {
+    dialect: Dialect,
+    options: GenerateOptions,
+    color: bool,
+    /// Именованные условия
+    symbols: SymbolTable,
+    last: Option<Expr>,
+}
+
+impl Session {
+    /// Новая сессия; `color` включает ANSI-раскраску ответов
+    pub fn new(color: bool) -> Self {
+        let dialect = Dialect::default();
+        let options = dialect.options.clone();
+        Self {
+            dialect,
+            options,
+            color,
+            symbols: SymbolTable::new(),
+            last: None,
+        }
+    }
+
+    /// Справка по командам
+    pub fn help(&self) -> &'static str {
+        HELP
+    }
+
+    /// Обрабатывает одну строку ввода
+    pub fn handle(&mut self, line: &str) -> Reply {
+        let line = line.trim();
+        if line.is_empty() {
+            return Reply::Output(String::new());
+        }
+        if matches!(line, "exit" | "quit" | ":quit" | ":q") {
+            return Reply::Quit;
+        }
+
+        let output = match line.strip_prefix(':') {
+            Some(command) => self.command(command),
+            None if SymbolTable::is_definition(line) => self.define(line),
+            None => self.expression(line),
+        };
+        Reply::Output(output)
+    }
+
+    fn command(&mut self, command: &str) -> String {
+        let (name, args) = command
+            .split_once(char::is_whitespace)
+            .map(|(name, args)| (name, args.trim()))
+            .unwrap_or((command, ""));
+
+        match name {
+            "help" | "h" => HELP.to_string(),
+            "eval" => self.eval(args),
+            "table" => self.table(),
+            "simplify" => self.simplify(),
+            "dialect" => self.dialect(args),
+            "symbols" => self.symbols(args),
+            "def" => self.define(args),
+            "defs" => self.list_definitions(),
+            "load" => self.load(args),
+            "undef" => match self.symbols.remove(args.trim_start_matches('$')) {
+                Some(_) => self.ok(&format!("${} удалено", args)),
+                None => self.error(&format!("нет условия с именем '{}'", args)),
+            },
+            _ => self.error(&format!("неизвестная команда ':{}', см. :help", name)),
+        }
+    }
+
+    /// Разбор нового условия: печатаем развёрнутый вид и запоминаем его
+    fn expression(&mut self, line: &str) -> String {
+        match self.parse(line) {
+            Ok(expr) => {
+                let output = self.ok(&to_ddr_string_with_options(&expr, &self.options));
+                self.last = Some(expr);
+                output
+            }
+            Err(message) => message,
+        }
+    }
+
+    /// Разбирает строку со ссылками на именованные условия
+    fn parse(&self, line: &str) -> Result<Expr, String> {
+        parse_ddr_expression_with_symbols(line, &self.symbols).map_err(|e| self.render(line, &e))
+    }
+
+    /// Ошибку внутри именованного условия показываем на его тексте
+    fn render(&self, source: &str, error: &ParseError) -> String {
+        match error {
+            ParseError::InDefinition { name, error: inner } => match self.symbols.get(name) {
+                Some(body) => format!(
+                    "{}\n{}",
+                    self.error(&error.to_string()),
+                    render_error(body, inner, self.color)
+                ),
+                None => render_error(source, error, self.color),
+            },
+            _ => render_error(source, error, self.color),
+        }
+    }
+
+    fn last(&self) -> Result<&Expr, String> {
+        self.last
+            .as_ref()
+            .ok_or_else(|| self.error("сначала введите условие"))
+    }
+
+    fn eval(&self, args: &str) -> String {
+        let expr = match self.last() {
+            Ok(expr) => expr,
+            Err(message) => return message,
+        };
+
+        // Номера занятых детекторов и измерения вида occ(D3)=75
+        let mut inputs = Measurements::new();
+        for part in args.split([',', ' ']).filter(|part| !part.is_empty()) {
+            if part.contains('=') {
+                match Measurements::parse_value(part) {
+                    Some((measure, detector, value)) => inputs = inputs.with(measure, detector, value),
+                    None => return self.error(&format!("ожидалось измерение вида occ(D3)=75, получено '{}'", part)),
+                }
+                continue;
+            }
+            match part.parse::<u32>() {
+                Ok(detector) => inputs = inputs.with_active(detector),
+                Err(_) => return self.error("ожидались номера детекторов через запятую: :eval 1,4,5"),
+            }
+        }
+
+        match try_evaluate(expr, &inputs) {
+            Ok(true) => paint("true", &format!("{}{}", BOLD, GREEN), self.color),
+            Ok(false) => paint("false", &format!("{}{}", BOLD, RED), self.color),
+            Err(e) => self.error(&e.to_string()),
+        }
+    }
+
+    fn table(&self) -> String {
+        let expr = match self.last() {
+            Ok(expr) => expr,
+            Err(message) => return message,
+        };
+
+        let detectors: Vec<u32> = expr.detectors().into_iter().collect();
+        if detectors.len() > MAX_TABLE_DETECTORS {
+            return self.error(&format!(
+                "в условии {} детекторов, таблица строится максимум для {}",
+                detectors.len(),
+                MAX_TABLE_DETECTORS
+            ));
+        }
+
+        let headers: Vec<String> = detectors.iter().map(|d| format!("D{}", d)).collect();
+        let mut lines = vec![paint(&format!("{} | результат", headers.join(" ")), CYAN, self.color)];
+
+        for mask in 0..1u32 << detectors.len() {
+            let active: Vec<u32> = detectors
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << (detectors.len() - 1 - i)) != 0)
+                .map(|(_, d)| *d)
+                .collect();
+
+            let cells: Vec<String> = detectors
+                .iter()
+                .zip(&headers)
+                .map(|(d, header)| {
+                    let bit = if active.contains(d) { "1" } else { "0" };
+                    format!("{:<width$}", bit, width = header.chars().count())
+                })
+                .collect();
+            let result = match try_evaluate(expr, &active[..]) {
+                Ok(true) => paint("1", GREEN, self.color),
+                Ok(false) => "0".to_string(),
+                // Измерений в таблице нет ни в одной строке
+                Err(e) => return self.error(&e.to_string()),
+            };
+            lines.push(format!("{} | {}", cells.join(" "), result));
+        }
+
+        lines.join("\n")
+    }
+
+    fn simplify(&mut self) -> String {
+        let expr = match self.last() {
+            Ok(expr) => simplify(expr),
+            Err(message) => return message,
+        };
+
+        let output = format!(
+            "{}\n{}",
+            self.ok(&expr.to_string()),
+            to_ddr_string_with_options(&expr, &self.options)
+        );
+        self.last = Some(expr);
+        output
+    }
+
+    fn dialect(&mut self, args: &str) -> String {
+        if args.is_empty() {
+            return Dialect::builtin()
+                .iter()
+                .map(|d| {
+                    let marker = if d.name == self.dialect.name { "*" } else { " " };
+                    format!("{} {:<12} {}", marker, d.name, d.description)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        match Dialect::by_name(args) {
+            Some(dialect) => {
+                // Выбранный ранее режим :symbols сохраняется при смене диалекта
+                let use_symbols = self.options.use_symbols != self.dialect.options.use_symbols;
+                self.options = dialect.options.clone();
+                self.options.use_symbols ^= use_symbols;
+                self.dialect = dialect;
+                self.ok(&format!("диалект {}", self.dialect.name))
+            }
+            None => self.error(&format!("нет диалекта '{}', см. :dialect", args)),
+        }
+    }
+
+    fn symbols(&mut self, args: &str) -> String {
+        match args {
+            "on" => self.options.use_symbols = true,
+            "off" => self.options.use_symbols = false,
+            _ => return self.error("используйте :symbols on или :symbols off"),
+        }
+        self.ok(&format!("символы {}", args))
+    }
+
+    fn define(&mut self, args: &str) -> String {
+        let previous = self.symbols.clone();
+        let name = match self.symbols.define_line(args) {
+            Ok(name) => name,
+            Err(e) => return self.error(&e.to_string()),
+        };
+
+        if let Err((_, e)) = self.check_definitions(std::slice::from_ref(&name), previous) {
+            return self.render(args, &e);
+        }
+
+        let source = self.symbols.get(&name).unwrap_or_default();
+        self.ok(&format!("${} = {}", name, source))
+    }
+
+    /// В REPL проверяем определения сразу, а не при первой ссылке: если
+    /// какое-то из имён `names` не разворачивается (цикл, неизвестное имя),
+    /// таблица возвращается к `previous`
+    fn check_definitions(&mut self, names: &[String], previous: SymbolTable) -> Result<(), (String, ParseError)> {
+        for name in names {
+            if let Err(e) = self.symbols.resolve(name) {
+                self.symbols = previous;
+                return Err((name.clone(), e));
+            }
+        }
+        Ok(())
+    }
+
+    fn load(&mut self, path: &str) -> String {
+        match Library::load(path) {
+            Ok(library) => self.load_library(&library),
+            Err(errors) => errors
+                .iter()
+                .map(|e| self.error(&e.to_string()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    /// Добавляет все имена библиотеки; имена из сессии с теми же именами
+    /// заменяются. Имена проверяются, как в :def, и загружаются все или ни одного
+    fn load_library(&mut self, library: &Library) -> String {
+        let previous = self.symbols.clone();
+        let mut names = Vec::new();
+        let mut replaced = Vec::new();
+        for (name, source) in library.symbols().iter() {
+            if let Err(e) = self.symbols.define(name, source) {
+                self.symbols = previous;
+                return self.error(&format!("{}: {}", library.file(), e));
+            }
+            if previous.get(name).is_some_and(|old| old != source) {
+                replaced.push(format!("${}", name));
+            }
+            names.push(name.to_string());
+        }
+
+        if let Err((name, e)) = self.check_definitions(&names, previous) {
+            return self.error(&format!("{}: ${}: {}", library.file(), name, e));
+        }
+
+        let loaded = self.ok(&format!("загружено имён: {}", names.len()));
+        match replaced.is_empty() {
+            true => loaded,
+            false => format!("{}\nзаменены: {}", loaded, replaced.join(", ")),
+        }
+    }
+
+    fn list_definitions(&self) -> String {
+        if self.symbols.is_empty() {
+            return "именованных условий нет".to_string();
+        }
+        self.symbols
+            .iter()
+            .map(|(name, body)| format!("${} = {}", name, body))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn ok(&self, text: &str) -> String {
+        format!("{} {}", paint("✓", GREEN, self.color), text)
+    }
+
+    fn error(&self, text: &str) -> String {
+        format!("{} {}", paint("ошибка:", &format!("{}{}", BOLD, RED), self.color), text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(session: &mut Session, line: &str) -> String {
+        match session.handle(line) {
+            Reply::Output(text) => text,
+            Reply::Quit => panic!("Unexpected quit"),
+        }
+    }
+
+    #[test]
+    fn test_expression_and_eval() {
+        let mut session = Session::new(false);
+        assert_eq!(
+            output(&mut session, "(or 1-2) and (or 4-5)"),
+            "✓ (ddr(D1) or ddr(D2)) and (ddr(D4) or ddr(D5))"
+        );
+        assert_eq!(output(&mut session, ":eval 1,4"), "true");
+        assert_eq!(output(&mut session, ":eval 1 2"), "false");
+        assert_eq!(session.handle(":quit"), Reply::Quit);
+    }
+
+    #[test]
+    fn test_table() {
+        let mut session = Session::new(false);
+        output(&mut session, "and 1-2");
+        assert_eq!(
+            output(&mut session, ":table"),
+            "D1 D2 | результат\n0  0  | 0\n0  1  | 0\n1  0  | 0\n1  1  | 1"
+        );
+    }
+
+    #[test]
+    fn test_dialect_symbols_and_simplify() {
+        let mut session = Session::new(false);
+        output(&mut session, ":dialect plain");
+        output(&mut session, ":symbols on");
+        output(&mut session, "(1-2) or (3-4)");
+        assert_eq!(output(&mut session, ":simplify"), "✓ or 1-4\nD1 | D2 | D3 | D4");
+
+        output(&mut session, ":dialect channel");
+        assert_eq!(output(&mut session, "1-2"), "✓ CH1 | CH2");
+        assert!(output(&mut session, ":dialect nope").starts_with("ошибка:"));
+    }
+
+    #[test]
+    fn test_definitions() {
+        let mut session = Session::new(false);
+        assert_eq!(output(&mut session, ":def NB = 1-4"), "✓ $NB = 1-4");
+        output(&mut session, "SB = $NB and not 9");
+        assert_eq!(
+            output(&mut session, "$SB"),
+            "✓ (ddr(D1) or ddr(D2) or ddr(D3) or ddr(D4)) and (not ddr(D9))"
+        );
+        assert_eq!(
+            output(&mut session, "$XX"),
+            "ошибка: Неизвестное имя '$XX' на позиции 0\n  | $XX\n  | ^^^"
+        );
+        assert_eq!(
+            output(&mut session, "NB = $SB"),
+            "ошибка: Циклическая ссылка: $NB → $SB → $NB"
+        );
+        assert_eq!(output(&mut session, ":defs"), "$NB = 1-4\n$SB = $NB and not 9");
+
+        // :load заменяет имена сессии и сообщает об этом
+        let library = Library::parse("cross.ddr", "NB = 5-6\nphase1: $NB or 7").unwrap();
+        assert_eq!(session.load_library(&library), "✓ загружено имён: 2\nзаменены: $NB");
+        assert_eq!(output(&mut session, "$SB"), "✓ (ddr(D5) or ddr(D6)) and (not ddr(D9))");
+        assert!(output(&mut session, ":load нет-такого-файла.ddr").starts_with("ошибка: "));
+        assert_eq!(output(&mut session, ":defs"), "$NB = 5-6\n$SB = $NB and not 9\n$phase1 = $NB or 7");
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let mut session = Session::new(false);
+        for (line, expected) in [
+            ("cnt(D1) >= 10", "✓ cnt(ddr(D1)) >= 10"),
+            ("occ(D3) <= 5", "✓ occ(ddr(D3)) <= 5"),
+            ("occ(D3) != 5", "✓ occ(ddr(D3)) != 5"),
+            ("occ(D3) == 5", "✓ occ(ddr(D3)) = 5"),
+            ("cnt(D2) > 1", "✓ cnt(ddr(D2)) > 1"),
+            ("1-2 and cnt(D1) <= 4", "✓ (ddr(D1) or ddr(D2)) and (cnt(ddr(D1)) <= 4)"),
+            ("=1of 1-3", "✓ (ddr(D1) and not ddr(D2) and not ddr(D3)) or (not ddr(D1) and ddr(D2) and not ddr(D3)) or (not ddr(D1) and not ddr(D2) and ddr(D3))"),
+        ] {
+            assert_eq!(output(&mut session, line), expected, "{}", line);
+        }
+
+        output(&mut session, "not occ(D3) > 60");
+        assert_eq!(output(&mut session, ":eval 3"), "ошибка: Нет измерения occ(D3)");
+        assert_eq!(output(&mut session, ":eval 3 occ(D3)=75"), "false");
+        assert_eq!(output(&mut session, ":eval 1,occ(D3)=40"), "true");
+        assert_eq!(
+            output(&mut session, ":eval occ(D3)=много"),
+            "ошибка: ожидалось измерение вида occ(D3)=75, получено 'occ(D3)=много'"
+        );
+        assert_eq!(output(&mut session, ":table"), "ошибка: Нет измерения occ(D3)");
+        assert_eq!(output(&mut session, "LIMIT = occ(D3) >= 60"), "✓ $LIMIT = occ(D3) >= 60");
+    }
+
+    #[test]
+    fn test_caret_diagnostic() {
+        let mut session = Session::new(false);
+        assert_eq!(
+            output(&mut session, "(1-3) xor (4-6)"),
+            "ошибка: Неизвестный оператор 'xor' на позиции 6. Используйте and/or или &/|\n  | (1-3) xor (4-6)\n  |       ^^^"
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Пошаговая модель контроллера с адаптивным управлением
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::conditions::TemporalEvaluator;
+use crate::junction::Junction;
+use crate::simulation::error::SimulationError;
+use crate::simulation::timeline::{EndReason, Interval, Timeline, TimelineEntry};
+
+/// Изменение состояния детектора
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectorEvent {
+    /// Время от начала записи, мс
+    pub time_ms: u64,
+    pub detector: u32,
+    /// `true` — детектор занят, `false` — освободился
+    pub active: bool,
+}
+
+impl DetectorEvent {
+    pub fn new(time_ms: u64, detector: u32, active: bool) -> Self {
+        Self { time_ms, detector, active }
+    }
+}
+
+/// Минимальное и максимальное зелёное фазы, с
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageTimes {
+    pub min_green: u32,
+    pub max_green: u32,
+}
+
+impl Default for StageTimes {
+    fn default() -> Self {
+        Self {
+            min_green: 7,
+            max_green: 60,
+        }
+    }
+}
+
+/// Настройки симуляции
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationConfig {
+    /// Шаг симуляции, мс
+    pub tick_ms: u64,
+
+    /// Сколько продление держит фазу после того, как условие продления
+    /// перестало выполняться, мс
+    pub passage_ms: u64,
+
+    /// Времена фаз, для которых не задано своё в `stage_times`
+    pub default_times: StageTimes,
+    pub stage_times: BTreeMap<u32, StageTimes>,
+
+    /// Промтакты между фазами (из, в) → с; нет пары — промтакт 0
+    pub intergreens: BTreeMap<(u32, u32), u32>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            tick_ms: 100,
+            passage_ms: 3000,
+            default_times: StageTimes::default(),
+            stage_times: BTreeMap::new(),
+            intergreens: BTreeMap::new(),
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Времена фазы
+    pub fn times(&self, stage: u32) -> StageTimes {
+        self.stage_times.get(&stage).copied().unwrap_or(self.default_times)
+    }
+}
+
+/// Что горит сейчас
+enum State {
+    Green { index: usize, since: u64 },
+    Intergreen { from: usize, to: usize, since: u64, until: u64 },
+}
+
+/// Прогоняет работу контроллера на `duration_ms` по записанным событиям детекторов.
+///
+/// Фазы идут в порядке описания перекрёстка, начиная с первой; фаза без
+/// вызова пропускается. Каждый шаг вызовы фаз запоминаются до обслуживания.
+/// Зелёное держится не меньше минимума; после минимума, если есть вызов
+/// другой фазы, заканчивается по разрыву потока (продление не выполнялось
+/// `passage_ms`) или по максимуму. Без вызовов фаза горит дальше.
+/// Условия вычисляются на каждом шаге, поэтому временные операторы
+/// (`delay`, `rise`...) работают по шагам симуляции.
+///
+/// # Пример
+/// ```
+/// use traffic_core::conditions::parse_ddr_expression;
+/// use traffic_core::junction::{Junction, Phase, PhaseKind, Stage};
+/// use traffic_core::simulation::{simulate, DetectorEvent, SimulationConfig};
+///
+/// let mut junction = Junction::new("CO4554");
+/// junction.add_phase(Phase::new("A", PhaseKind::Vehicle));
+/// junction.add_phase(Phase::new("B", PhaseKind::Vehicle));
+/// junction.add_stage(Stage::new(1, &["A"]));
+/// junction.add_stage(Stage::new(2, &["B"]).with_demand(parse_ddr_expression("5").unwrap()));
+///
+/// let events = [DetectorEvent::new(20_000, 5, true), DetectorEvent::new(21_000, 5, false)];
+/// let timeline = simulate(&junction, &SimulationConfig::default(), &events, 60_000).unwrap();
+/// assert_eq!(timeline.stages(), [1, 2, 1]);
+/// ```
+pub fn simulate(
+    junction: &Junction,
+    config: &SimulationConfig,
+    events: &[DetectorEvent],
+    duration_ms: u64,
+) -> Result<Timeline, SimulationError> {
+    let stages = &junction.stages;
+    if stages.is_empty() {
+        return Err(SimulationError::NoStages);
+    }
+    if config.tick_ms == 0 {
+        return Err(SimulationError::ZeroTick);
+    }
+    for stage in stages {
+        let times = config.times(stage.number);
+        if times.min_green > times.max_green {
+            return Err(SimulationError::InvalidGreens {
+                stage: stage.number,
+                min_green: times.min_green,
+                max_green: times.max_green,
+            });
+        }
+    }
+
+    let mut events = events.to_vec();
+    events.sort_by_key(|event| event.time_ms);
+    let mut pending = events.into_iter().peekable();
+
+    // Без условия вызова фаза вызывается всегда
+    let evaluator = |expr: &Option<_>| expr.as_ref().map(TemporalEvaluator::new);
+    let mut demands: Vec<_> = stages.iter().map(|stage| evaluator(&stage.demand)).collect();
+    let mut extensions: Vec<_> = stages.iter().map(|stage| evaluator(&stage.extension)).collect();
+
+    let mut active = BTreeSet::new();
+    let mut demanded = vec![false; stages.len()];
+    let mut last_extension = 0;
+    let mut state = State::Green { index: 0, since: 0 };
+    let mut timeline = Timeline::default();
+
+    let mut now = 0;
+    while now < duration_ms {
+        while let Some(event) = pending.next_if(|event| event.time_ms <= now) {
+            if event.active {
+                active.insert(event.detector);
+            } else {
+                active.remove(&event.detector);
+            }
+        }
+
+        let serving = match state {
+            State::Green { index, .. } => Some(index),
+            State::Intergreen { .. } => None,
+        };
+        let demand: Vec<bool> = demands
+            .iter_mut()
+            .map(|demand| demand.as_mut().is_none_or(|demand| demand.tick(now, &active)))
+            .collect();
+        let extension: Vec<bool> = extensions
+            .iter_mut()
+            .map(|extension| extension.as_mut().is_some_and(|extension| extension.tick(now, &active)))
+            .collect();
+
+        for (index, &demand) in demand.iter().enumerate() {
+            if Some(index) != serving && demand {
+                demanded[index] = true;
+            }
+        }
+
+        state = match state {
+            State::Green { index, since } => {
+                if extension[index] {
+                    last_extension = now;
+                }
+
+                let times = config.times(stages[index].number);
+                let elapsed = now - since;
+                let next = next_demanded(index, &demanded);
+                let reason = match next {
+                    _ if elapsed < times.min_green as u64 * 1000 => None,
+                    None => None,
+                    Some(_) if elapsed >= times.max_green as u64 * 1000 => Some(EndReason::MaxOut),
+                    Some(_) if now - last_extension >= config.passage_ms => Some(EndReason::GapOut),
+                    Some(_) => None,
+                };
+
+                match (reason, next) {
+                    (Some(reason), Some(to)) => {
+                        timeline.entries.push(TimelineEntry {
+                            start_ms: since,
+                            end_ms: now,
+                            interval: Interval::Green { stage: stages[index].number, reason },
+                        });
+                        let key = (stages[index].number, stages[to].number);
+                        let intergreen = config.intergreens.get(&key).copied().unwrap_or(0) as u64;
+                        State::Intergreen { from: index, to, since: now, until: now + intergreen * 1000 }
+                    }
+                    _ => State::Green { index, since },
+                }
+            }
+            intergreen => intergreen,
+        };
+
+        // Промтакт закончился (или был нулевым) — включаем следующую фазу
+        if let State::Intergreen { from, to, since, until } = state
+            && now >= until
+        {
+            if until > since {
+                timeline.entries.push(TimelineEntry {
+                    start_ms: since,
+                    end_ms: until,
+                    interval: Interval::Intergreen {
+                        from: stages[from].number,
+                        to: stages[to].number,
+                    },
+                });
+            }
+            demanded[to] = false;
+            last_extension = now;
+            state = State::Green { index: to, since: now };
+        }
+
+        now += config.tick_ms;
+    }
+
+    // Закрываем то, что горело в конце
+    let end_ms = duration_ms;
+    match state {
+        State::Green { index, since } => timeline.entries.push(TimelineEntry {
+            start_ms: since,
+            end_ms,
+            interval: Interval::Green { stage: stages[index].number, reason: EndReason::End },
+        }),
+        State::Intergreen { from, to, since, .. } => timeline.entries.push(TimelineEntry {
+            start_ms: since,
+            end_ms,
+            interval: Interval::Intergreen {
+                from: stages[from].number,
+                to: stages[to].number,
+            },
+        }),
+    }
+    Ok(timeline)
+}
+
+/// Следующая по кругу фаза с вызовом
+fn next_demanded(current: usize, demanded: &[bool]) -> Option<usize> {
+    (1..demanded.len())
+        .map(|offset| (current + offset) % demanded.len())
+        .find(|&index| demanded[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::parse_ddr_expression;
+    use crate::junction::{Phase, PhaseKind, Stage};
+
+    fn junction() -> Junction {
+        let mut junction = Junction::new("CO4554");
+        junction.add_detectors(1..=4);
+        junction.add_phase(Phase::new("A", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("B", PhaseKind::Vehicle));
+        junction.add_phase(Phase::new("P", PhaseKind::Pedestrian));
+        junction.add_stage(
+            Stage::new(1, &["A"])
+                .with_demand(parse_ddr_expression("1").unwrap())
+                .with_extension(parse_ddr_expression("1").unwrap()),
+        );
+        junction.add_stage(
+            Stage::new(2, &["B"])
+                .with_demand(parse_ddr_expression("2").unwrap())
+                .with_extension(parse_ddr_expression("2").unwrap()),
+        );
+        junction.add_stage(Stage::new(3, &["P"]).with_demand(parse_ddr_expression("4").unwrap()));
+        junction
+    }
+
+    fn config() -> SimulationConfig {
+        SimulationConfig {
+            tick_ms: 1000,
+            passage_ms: 2000,
+            default_times: StageTimes { min_green: 5, max_green: 20 },
+            intergreens: BTreeMap::from([((1, 2), 4), ((2, 1), 4), ((2, 3), 3), ((3, 1), 5)]),
+            ..SimulationConfig::default()
+        }
+    }
+
+    fn green(start_ms: u64, end_ms: u64, stage: u32, reason: EndReason) -> TimelineEntry {
+        TimelineEntry { start_ms, end_ms, interval: Interval::Green { stage, reason } }
+    }
+
+    fn intergreen(start_ms: u64, end_ms: u64, from: u32, to: u32) -> TimelineEntry {
+        TimelineEntry { start_ms, end_ms, interval: Interval::Intergreen { from, to } }
+    }
+
+    #[test]
+    fn test_gap_out_and_skip() {
+        // Машина на D2 в 3 с, пешеход нажал кнопку в 12 с, фаза 1 без машин
+        let events = [
+            DetectorEvent::new(3000, 2, true),
+            DetectorEvent::new(9000, 2, false),
+            DetectorEvent::new(12000, 4, true),
+            DetectorEvent::new(13000, 4, false),
+        ];
+        let timeline = simulate(&junction(), &config(), &events, 30_000).unwrap();
+
+        assert_eq!(
+            timeline.entries,
+            [
+                // Фаза 1 ждёт минимум 5 с, продления нет — разрыв потока
+                green(0, 5000, 1, EndReason::GapOut),
+                intergreen(5000, 9000, 1, 2),
+                // D2 освободился в 9 с: продление держит ещё 2 с после минимума
+                green(9000, 14000, 2, EndReason::GapOut),
+                intergreen(14000, 17000, 2, 3),
+                green(17000, 30000, 3, EndReason::End),
+            ]
+        );
+        assert_eq!(timeline.stages(), [1, 2, 3]);
+        assert_eq!(timeline.green_time(2), 5000);
+    }
+
+    #[test]
+    fn test_max_out() {
+        // D1 занят всё время — фаза 1 продлевается до максимума
+        let events = [DetectorEvent::new(0, 1, true), DetectorEvent::new(1000, 2, true)];
+        let timeline = simulate(&junction(), &config(), &events, 30_000).unwrap();
+        assert_eq!(timeline.entries[0], green(0, 20000, 1, EndReason::MaxOut));
+        assert_eq!(timeline.entries[2].start_ms, 24000);
+    }
+
+    #[test]
+    fn test_temporal_demand() {
+        // Фаза 2 вызывается, только если D2 занят не меньше 3 с
+        let mut junction = junction();
+        junction.stages[1].demand = Some(parse_ddr_expression("delay(2, 3)").unwrap());
+
+        let short = [DetectorEvent::new(1000, 2, true), DetectorEvent::new(3000, 2, false)];
+        let timeline = simulate(&junction, &config(), &short, 20_000).unwrap();
+        assert_eq!(timeline.stages(), [1]);
+
+        let long = [DetectorEvent::new(1000, 2, true), DetectorEvent::new(5000, 2, false)];
+        let timeline = simulate(&junction, &config(), &long, 20_000).unwrap();
+        assert_eq!(timeline.stages(), [1, 2]);
+    }
+
+    #[test]
+    fn test_rest_without_demand() {
+        let timeline = simulate(&junction(), &config(), &[], 10_000).unwrap();
+        assert_eq!(timeline.entries, [green(0, 10000, 1, EndReason::End)]);
+        assert_eq!(timeline.to_string(), "    0.0 …    10.0  фаза 1 (конец симуляции)\n");
+
+        let mut bad = config();
+        bad.stage_times.insert(2, StageTimes { min_green: 30, max_green: 20 });
+        assert!(matches!(
+            simulate(&junction(), &bad, &[], 10_000),
+            Err(SimulationError::InvalidGreens { stage: 2, .. })
+        ));
+    }
+}
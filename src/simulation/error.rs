@@ -0,0 +1,19 @@
+//! Типы ошибок симуляции
+
+use thiserror::Error;
+
+/// Ошибки настройки симуляции
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SimulationError {
+    /// Ошибка: у перекрёстка нет фаз
+    #[error("У перекрёстка нет ни одной фазы")]
+    NoStages,
+
+    /// Ошибка: нулевой шаг симуляции
+    #[error("Шаг симуляции должен быть больше нуля")]
+    ZeroTick,
+
+    /// Ошибка: минимальное зелёное больше максимального
+    #[error("Фаза {stage}: минимальное зелёное {min_green} с больше максимального {max_green} с")]
+    InvalidGreens { stage: u32, min_green: u32, max_green: u32 },
+}
@@ -0,0 +1,15 @@
+//! Офлайн-симуляция адаптивного управления
+//!
+//! Прогоняет модель перекрёстка (`junction`) по записанным событиям
+//! детекторов: каждый шаг вычисляет условия вызова и продления фаз,
+//! соблюдает минимальные и максимальные зелёные, разрыв потока и
+//! промтакты и строит временную диаграмму фаз. Так можно проверить
+//! настройку по реальной записи детекторов до загрузки в контроллер.
+
+mod engine;    // engine.rs — пошаговая модель контроллера
+mod error;     // error.rs — типы ошибок
+mod timeline;  // timeline.rs — временная диаграмма
+
+pub use engine::{simulate, DetectorEvent, SimulationConfig, StageTimes};
+pub use error::SimulationError;
+pub use timeline::{EndReason, Interval, Timeline, TimelineEntry};
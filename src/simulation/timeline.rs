@@ -0,0 +1,84 @@
+//! Временная диаграмма работы фаз
+
+use std::fmt;
+
+/// Почему закончилось зелёное фазы
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndReason {
+    /// Разрыв потока: условие продления перестало выполняться
+    GapOut,
+    /// Достигнуто максимальное зелёное
+    MaxOut,
+    /// Закончилось время симуляции
+    End,
+}
+
+impl fmt::Display for EndReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndReason::GapOut => write!(f, "разрыв потока"),
+            EndReason::MaxOut => write!(f, "максимум"),
+            EndReason::End => write!(f, "конец симуляции"),
+        }
+    }
+}
+
+/// Участок диаграммы
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    /// Зелёное фазы
+    Green { stage: u32, reason: EndReason },
+    /// Промтакт между фазами
+    Intergreen { from: u32, to: u32 },
+}
+
+/// Участок диаграммы со временем, мс от начала
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineEntry {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub interval: Interval,
+}
+
+/// Результат симуляции
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timeline {
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl Timeline {
+    /// Номера фаз в порядке включения
+    pub fn stages(&self) -> Vec<u32> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry.interval {
+                Interval::Green { stage, .. } => Some(stage),
+                Interval::Intergreen { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Суммарное зелёное фазы, мс
+    pub fn green_time(&self, stage: u32) -> u64 {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.interval, Interval::Green { stage: s, .. } if s == stage))
+            .map(|entry| entry.end_ms - entry.start_ms)
+            .sum()
+    }
+}
+
+/// Таблица для просмотра: "   0.0 …   12.0  фаза 1  (разрыв потока)"
+impl fmt::Display for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let seconds = |ms: u64| ms as f64 / 1000.0;
+            write!(f, "{:7.1} … {:7.1}  ", seconds(entry.start_ms), seconds(entry.end_ms))?;
+            match entry.interval {
+                Interval::Green { stage, reason } => writeln!(f, "фаза {} ({})", stage, reason)?,
+                Interval::Intergreen { from, to } => writeln!(f, "промтакт {} → {}", from, to)?,
+            }
+        }
+        Ok(())
+    }
+}
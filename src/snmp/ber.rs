@@ -0,0 +1,307 @@
+//! Минимальный кодек BER (Basic Encoding Rules)
+//!
+//! Реализует ровно то подмножество ASN.1 BER, которое нужно SNMP v1/v2c:
+//! TLV с короткой и длинной формой длины, INTEGER, OCTET STRING, NULL,
+//! OBJECT IDENTIFIER и беззнаковые типы приложения (Counter32, TimeTicks...).
+
+use crate::snmp::error::SnmpError;
+use crate::snmp::oid::Oid;
+
+// Универсальные теги
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_NULL: u8 = 0x05;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+// Теги приложения (SNMPv2-SMI)
+pub const TAG_IP_ADDRESS: u8 = 0x40;
+pub const TAG_COUNTER32: u8 = 0x41;
+pub const TAG_GAUGE32: u8 = 0x42;
+pub const TAG_TIMETICKS: u8 = 0x43;
+pub const TAG_OPAQUE: u8 = 0x44;
+pub const TAG_COUNTER64: u8 = 0x46;
+
+// Исключения в ответах SNMPv2c
+pub const TAG_NO_SUCH_OBJECT: u8 = 0x80;
+pub const TAG_NO_SUCH_INSTANCE: u8 = 0x81;
+pub const TAG_END_OF_MIB_VIEW: u8 = 0x82;
+
+/// Последовательное чтение TLV из буфера
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Остались ли непрочитанные байты
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    /// Тег следующего TLV без сдвига позиции
+    pub fn peek_tag(&self) -> Result<u8, SnmpError> {
+        self.data
+            .get(self.pos)
+            .copied()
+            .ok_or(SnmpError::UnexpectedEof(self.pos))
+    }
+
+    /// Читает очередной TLV: возвращает тег и содержимое
+    pub fn read_tlv(&mut self) -> Result<(u8, &'a [u8]), SnmpError> {
+        let tag = self.peek_tag()?;
+        self.pos += 1;
+        let len = self.read_length()?;
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(SnmpError::UnexpectedEof(self.pos))?;
+        let content = &self.data[self.pos..end];
+        self.pos = end;
+        Ok((tag, content))
+    }
+
+    /// Читает TLV с заранее известным тегом
+    pub fn expect(&mut self, expected: u8) -> Result<&'a [u8], SnmpError> {
+        let position = self.pos;
+        let (found, content) = self.read_tlv()?;
+        if found != expected {
+            return Err(SnmpError::UnexpectedTag { expected, found, position });
+        }
+        Ok(content)
+    }
+
+    fn read_length(&mut self) -> Result<usize, SnmpError> {
+        let first = *self
+            .data
+            .get(self.pos)
+            .ok_or(SnmpError::UnexpectedEof(self.pos))?;
+        self.pos += 1;
+
+        if first & 0x80 == 0 {
+            return Ok(first as usize);
+        }
+
+        // Длинная форма: младшие 7 бит — количество байт длины
+        let count = (first & 0x7F) as usize;
+        if count == 0 || count > std::mem::size_of::<usize>() {
+            return Err(SnmpError::Malformed(format!("длина из {} байт", count)));
+        }
+        let mut len = 0usize;
+        for _ in 0..count {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or(SnmpError::UnexpectedEof(self.pos))?;
+            self.pos += 1;
+            len = (len << 8) | byte as usize;
+        }
+        Ok(len)
+    }
+}
+
+/// Записывает TLV в конец буфера
+pub fn write_tlv(out: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    out.push(tag);
+    write_length(out, content.len());
+    out.extend_from_slice(content);
+}
+
+fn write_length(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+        return;
+    }
+    let bytes = len.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    out.push(0x80 | (bytes.len() - skip) as u8);
+    out.extend_from_slice(&bytes[skip..]);
+}
+
+/// Кодирует знаковое целое в минимальное дополнение до двух
+pub fn encode_integer(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    // Убираем лишние ведущие 0x00/0xFF, сохраняя знаковый бит
+    while start < bytes.len() - 1 {
+        let (cur, next) = (bytes[start], bytes[start + 1]);
+        if (cur == 0x00 && next & 0x80 == 0) || (cur == 0xFF && next & 0x80 != 0) {
+            start += 1;
+        } else {
+            break;
+        }
+    }
+    bytes[start..].to_vec()
+}
+
+/// Декодирует знаковое целое
+pub fn decode_integer(content: &[u8]) -> Result<i64, SnmpError> {
+    if content.is_empty() || content.len() > 8 {
+        return Err(SnmpError::Malformed(format!(
+            "INTEGER длиной {} байт",
+            content.len()
+        )));
+    }
+    let negative = content[0] & 0x80 != 0;
+    let mut value: i64 = if negative { -1 } else { 0 };
+    for &byte in content {
+        value = (value << 8) | byte as i64;
+    }
+    Ok(value)
+}
+
+/// Кодирует беззнаковое целое (Counter32, Gauge32, TimeTicks, Counter64)
+pub fn encode_unsigned(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count().min(7);
+    let mut out = Vec::with_capacity(9);
+    // Ведущий ноль, чтобы старший бит не приняли за знак
+    if bytes[skip] & 0x80 != 0 {
+        out.push(0);
+    }
+    out.extend_from_slice(&bytes[skip..]);
+    out
+}
+
+/// Декодирует беззнаковое целое
+pub fn decode_unsigned(content: &[u8]) -> Result<u64, SnmpError> {
+    let content = match content {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        _ => content,
+    };
+    if content.is_empty() || content.len() > 8 {
+        return Err(SnmpError::Malformed(format!(
+            "беззнаковое число длиной {} байт",
+            content.len()
+        )));
+    }
+    Ok(content.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+}
+
+/// Кодирует OID: первые два компонента сливаются в один (40 * X + Y);
+/// при X = 0 или 1 второй компонент меньше 40, иначе запись неоднозначна
+pub fn encode_oid(oid: &Oid) -> Result<Vec<u8>, SnmpError> {
+    let components = oid.components();
+    if components.len() < 2 || components[0] > 2 || (components[0] < 2 && components[1] >= 40) {
+        return Err(SnmpError::InvalidOid(oid.to_string()));
+    }
+    let first = components[0]
+        .checked_mul(40)
+        .and_then(|first| first.checked_add(components[1]))
+        .ok_or_else(|| SnmpError::InvalidOid(oid.to_string()))?;
+
+    let mut out = Vec::new();
+    write_base128(&mut out, first);
+    for &component in &components[2..] {
+        write_base128(&mut out, component);
+    }
+    Ok(out)
+}
+
+/// Декодирует OID
+pub fn decode_oid(content: &[u8]) -> Result<Oid, SnmpError> {
+    let mut components = Vec::new();
+    let mut value: u32 = 0;
+    for (i, &byte) in content.iter().enumerate() {
+        value = value
+            .checked_mul(128)
+            .map(|v| v | (byte & 0x7F) as u32)
+            .ok_or_else(|| SnmpError::Malformed("компонент OID больше u32".to_string()))?;
+
+        if byte & 0x80 == 0 {
+            if components.is_empty() {
+                let first = (value / 40).min(2);
+                components.push(first);
+                components.push(value - first * 40);
+            } else {
+                components.push(value);
+            }
+            value = 0;
+        } else if i == content.len() - 1 {
+            return Err(SnmpError::Malformed("OID обрывается посреди компонента".to_string()));
+        }
+    }
+    if components.is_empty() {
+        return Err(SnmpError::Malformed("пустой OID".to_string()));
+    }
+    Ok(Oid::new(components))
+}
+
+fn write_base128(out: &mut Vec<u8>, mut value: u32) {
+    let mut chunk = [0u8; 5];
+    let mut i = chunk.len();
+    loop {
+        i -= 1;
+        chunk[i] = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+    }
+    let last = chunk.len() - 1;
+    for (j, byte) in chunk.iter().enumerate().skip(i) {
+        out.push(if j == last { *byte } else { byte | 0x80 });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_roundtrip() {
+        for value in [0i64, 1, 127, 128, 255, 256, -1, -128, -129, 65535, i32::MAX as i64] {
+            let encoded = encode_integer(value);
+            assert_eq!(decode_integer(&encoded).unwrap(), value, "{}", value);
+        }
+        assert_eq!(encode_integer(128), vec![0x00, 0x80]);
+        assert_eq!(encode_integer(-128), vec![0x80]);
+    }
+
+    #[test]
+    fn test_unsigned_roundtrip() {
+        for value in [0u64, 127, 128, u32::MAX as u64, u64::MAX] {
+            let encoded = encode_unsigned(value);
+            assert_eq!(decode_unsigned(&encoded).unwrap(), value, "{}", value);
+        }
+        assert_eq!(encode_unsigned(u32::MAX as u64), vec![0x00, 0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_oid_roundtrip() {
+        let oid: Oid = "1.3.6.1.4.1.13267.3.2".parse().unwrap();
+        let encoded = encode_oid(&oid).unwrap();
+        assert_eq!(&encoded[..6], &[0x2B, 0x06, 0x01, 0x04, 0x01, 0xE7]);
+        assert_eq!(decode_oid(&encoded).unwrap(), oid);
+
+        let oid: Oid = "2.999.3".parse().unwrap();
+        assert_eq!(decode_oid(&encode_oid(&oid).unwrap()).unwrap(), oid);
+        for invalid in ["2.4294967295", "1.45", "0.40", "3.1"] {
+            let oid: Oid = invalid.parse().unwrap();
+            assert!(matches!(encode_oid(&oid), Err(SnmpError::InvalidOid(_))), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_long_length() {
+        let content = vec![0xAB; 300];
+        let mut out = Vec::new();
+        write_tlv(&mut out, TAG_OCTET_STRING, &content);
+        assert_eq!(&out[..4], &[0x04, 0x82, 0x01, 0x2C]);
+
+        let mut reader = Reader::new(&out);
+        assert_eq!(reader.expect(TAG_OCTET_STRING).unwrap(), &content[..]);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut reader = Reader::new(&[0x04, 0x05, 0x01]);
+        assert!(matches!(reader.read_tlv(), Err(SnmpError::UnexpectedEof(_))));
+    }
+}
@@ -0,0 +1,141 @@
+//! Простой синхронный SNMP-клиент поверх UDP
+//!
+//! Нужен прежде всего для проверки симулятора и для быстрых
+//! запросов к контроллеру без внешних утилит.
+
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+use crate::snmp::error::SnmpError;
+use crate::snmp::oid::Oid;
+use crate::snmp::pdu::{ErrorStatus, Message, Pdu, PduType, Value, VarBind, Version, MAX_DATAGRAM};
+
+/// Таймаут ожидания ответа по умолчанию
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// SNMP-клиент, привязанный к одному агенту
+pub struct SnmpClient {
+    socket: UdpSocket,
+    community: String,
+    version: Version,
+    next_request_id: i32,
+}
+
+impl SnmpClient {
+    /// Создаёт клиента v2c для агента по адресу `agent`.
+    pub fn connect(agent: impl ToSocketAddrs, community: &str) -> Result<Self, SnmpError> {
+        let agent: SocketAddr = agent
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| SnmpError::Malformed("адрес агента не найден".to_string()))?;
+
+        let local = if agent.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(agent)?;
+        socket.set_read_timeout(Some(DEFAULT_TIMEOUT))?;
+
+        Ok(Self {
+            socket,
+            community: community.to_string(),
+            version: Version::V2c,
+            next_request_id: 1,
+        })
+    }
+
+    /// Переключает версию протокола (по умолчанию v2c)
+    pub fn set_version(&mut self, version: Version) {
+        self.version = version;
+    }
+
+    /// Меняет таймаут ожидания ответа
+    pub fn set_timeout(&mut self, timeout: Duration) -> Result<(), SnmpError> {
+        self.socket.set_read_timeout(Some(timeout))?;
+        Ok(())
+    }
+
+    /// GET для набора OID
+    pub fn get(&mut self, oids: &[Oid]) -> Result<Vec<VarBind>, SnmpError> {
+        self.request(PduType::GetRequest, null_varbinds(oids))
+    }
+
+    /// GETNEXT для набора OID
+    pub fn get_next(&mut self, oids: &[Oid]) -> Result<Vec<VarBind>, SnmpError> {
+        self.request(PduType::GetNextRequest, null_varbinds(oids))
+    }
+
+    /// SET для набора переменных
+    pub fn set(&mut self, varbinds: Vec<VarBind>) -> Result<Vec<VarBind>, SnmpError> {
+        self.request(PduType::SetRequest, varbinds)
+    }
+
+    /// Обход поддерева `root` через последовательные GETNEXT
+    pub fn walk(&mut self, root: &Oid) -> Result<Vec<VarBind>, SnmpError> {
+        let mut result = Vec::new();
+        let mut current = root.clone();
+
+        loop {
+            let next = match self.get_next(std::slice::from_ref(&current)) {
+                Ok(mut varbinds) if !varbinds.is_empty() => varbinds.remove(0),
+                Ok(_) => break,
+                // v1-агенты сообщают о конце MIB ошибкой noSuchName
+                Err(SnmpError::ErrorStatus { status: ErrorStatus::NoSuchName, .. }) => break,
+                Err(e) => return Err(e),
+            };
+
+            if next.value.is_exception() || !next.oid.starts_with(root) || next.oid <= current {
+                break;
+            }
+            current = next.oid.clone();
+            result.push(next);
+        }
+
+        Ok(result)
+    }
+
+    fn request(&mut self, kind: PduType, varbinds: Vec<VarBind>) -> Result<Vec<VarBind>, SnmpError> {
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1).max(1);
+
+        let message = Message {
+            version: self.version,
+            community: self.community.clone(),
+            pdu: Pdu::request(kind, request_id, varbinds),
+        };
+        self.socket.send(&message.encode()?)?;
+
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        loop {
+            let len = match self.socket.recv(&mut buf) {
+                Ok(len) => len,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Err(SnmpError::Timeout);
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let response = Message::decode(&buf[..len])?;
+            // Запоздавшие ответы на старые запросы пропускаем
+            if response.pdu.kind != PduType::Response || response.pdu.request_id != request_id {
+                continue;
+            }
+            if response.pdu.error_status != ErrorStatus::NoError {
+                return Err(SnmpError::ErrorStatus {
+                    status: response.pdu.error_status,
+                    index: response.pdu.error_index,
+                });
+            }
+            return Ok(response.pdu.varbinds);
+        }
+    }
+}
+
+fn null_varbinds(oids: &[Oid]) -> Vec<VarBind> {
+    oids.iter()
+        .map(|oid| VarBind::new(oid.clone(), Value::Null))
+        .collect()
+}
@@ -0,0 +1,47 @@
+//! Типы ошибок для SNMP-модуля
+
+use thiserror::Error;
+
+/// Ошибки кодирования, разбора и обмена SNMP-сообщениями
+#[derive(Error, Debug)]
+pub enum SnmpError {
+    /// Ошибка: строка не является OID
+    #[error("Некорректный OID '{0}'")]
+    InvalidOid(String),
+
+    /// Ошибка: пакет обрывается раньше, чем заявлено в длине
+    #[error("Неожиданный конец данных на позиции {0}")]
+    UnexpectedEof(usize),
+
+    /// Ошибка: встретился не тот BER-тег
+    #[error("Ожидался тег 0x{expected:02X}, получен 0x{found:02X} на позиции {position}")]
+    UnexpectedTag { expected: u8, found: u8, position: usize },
+
+    /// Ошибка: неподдерживаемый тег значения или PDU
+    #[error("Неподдерживаемый тег 0x{0:02X}")]
+    UnsupportedTag(u8),
+
+    /// Ошибка: неподдерживаемая версия протокола
+    #[error("Неподдерживаемая версия SNMP: {0}")]
+    UnsupportedVersion(i64),
+
+    /// Ошибка: некорректное содержимое поля
+    #[error("Некорректные данные: {0}")]
+    Malformed(String),
+
+    /// Ошибка: строка дампа snmpwalk не разобрана
+    #[error("Строка {line}: {message}")]
+    WalkSyntax { line: usize, message: String },
+
+    /// Ошибка: агент вернул error-status, отличный от noError
+    #[error("Агент вернул ошибку {status:?} для переменной №{index}")]
+    ErrorStatus { status: crate::snmp::ErrorStatus, index: u32 },
+
+    /// Ошибка: ответ не пришёл вовремя
+    #[error("Таймаут ожидания ответа")]
+    Timeout,
+
+    /// Ошибка ввода-вывода (сокет)
+    #[error("Ошибка ввода-вывода: {0}")]
+    Io(#[from] std::io::Error),
+}
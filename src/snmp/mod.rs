@@ -0,0 +1,30 @@
+//! Модуль для работы с контроллерами по SNMP
+//!
+//! Содержит минимальный кодек SNMP v1/v2c, синхронный клиент,
+//! симулятор агента контроллера, который позволяет проверять
+//! инструменты на localhost без выезда на объект, и разбор
+//! текстовых дампов snmpwalk.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::snmp::{Oid, scn_index};
+//!
+//! let column: Oid = "1.3.6.1.4.1.99999.1".parse().unwrap();
+//! let oid = column.join(&scn_index("CO4554").unwrap());
+//! assert_eq!(oid.to_string(), ".1.3.6.1.4.1.99999.1.1.6.67.79.52.53.53.52");
+//! ```
+
+mod ber;        // ber.rs — кодирование/разбор BER
+mod client;     // client.rs — SNMP-клиент поверх UDP
+mod error;      // error.rs — типы ошибок
+mod oid;        // oid.rs — идентификаторы объектов
+mod pdu;        // pdu.rs — значения, varbind, PDU и сообщения
+mod simulator;  // simulator.rs — симулятор агента контроллера
+mod walk;       // walk.rs — разбор вывода snmpwalk
+
+pub use client::SnmpClient;
+pub use error::SnmpError;
+pub use oid::Oid;
+pub use pdu::{ErrorStatus, Message, Pdu, PduType, Value, VarBind, Version};
+pub use simulator::{scn_index, Access, RunningSimulator, Simulator};
+pub use walk::{group_by_site, parse_walk, parse_walk_line, SiteRows, WalkRow};
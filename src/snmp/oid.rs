@@ -0,0 +1,103 @@
+//! Идентификатор объекта SNMP (OID)
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::snmp::error::SnmpError;
+
+/// OID — последовательность неотрицательных чисел.
+///
+/// Поддерживает запись с ведущей точкой и без неё:
+/// ".1.3.6.1" и "1.3.6.1" дают один и тот же OID.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Oid(Vec<u32>);
+
+impl Oid {
+    /// Создаёт OID из готового набора компонентов.
+    pub fn new(components: Vec<u32>) -> Self {
+        Self(components)
+    }
+
+    /// Компоненты OID.
+    pub fn components(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// Количество компонентов.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Пустой ли OID.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Начинается ли OID с `prefix`.
+    pub fn starts_with(&self, prefix: &Oid) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// Новый OID: текущий + хвост `suffix`.
+    ///
+    /// # Пример
+    /// ```
+    /// use traffic_core::snmp::Oid;
+    ///
+    /// let column: Oid = "1.3.6.1.4.1".parse().unwrap();
+    /// let suffix: Oid = ".1.2.67.79".parse().unwrap();
+    /// assert_eq!(column.join(&suffix).to_string(), ".1.3.6.1.4.1.1.2.67.79");
+    /// ```
+    pub fn join(&self, suffix: &Oid) -> Oid {
+        let mut components = self.0.clone();
+        components.extend_from_slice(&suffix.0);
+        Oid(components)
+    }
+
+    /// Новый OID с одним дополнительным компонентом в конце.
+    pub fn child(&self, component: u32) -> Oid {
+        let mut components = self.0.clone();
+        components.push(component);
+        Oid(components)
+    }
+}
+
+impl From<Vec<u32>> for Oid {
+    fn from(components: Vec<u32>) -> Self {
+        Self(components)
+    }
+}
+
+impl From<&[u32]> for Oid {
+    fn from(components: &[u32]) -> Self {
+        Self(components.to_vec())
+    }
+}
+
+impl FromStr for Oid {
+    type Err = SnmpError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let body = s.strip_prefix('.').unwrap_or(s);
+        if body.is_empty() {
+            return Err(SnmpError::InvalidOid(s.to_string()));
+        }
+
+        body.split('.')
+            .map(|part| part.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map(Oid)
+            .map_err(|_| SnmpError::InvalidOid(s.to_string()))
+    }
+}
+
+impl fmt::Display for Oid {
+    /// Печатает OID в стиле net-snmp: с ведущей точкой.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for component in &self.0 {
+            write!(f, ".{}", component)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,430 @@
+//! SNMP-сообщения: значения, переменные (varbind), PDU
+//!
+//! Поддерживаются версии v1 и v2c и PDU GetRequest, GetNextRequest,
+//! Response и SetRequest — этого хватает для опроса и управления
+//! контроллером по SCN-индексированным объектам.
+
+use std::fmt;
+
+use crate::snmp::ber::{self, Reader};
+use crate::snmp::error::SnmpError;
+use crate::snmp::oid::Oid;
+
+/// Максимальный размер UDP-датаграммы с SNMP-сообщением
+pub(crate) const MAX_DATAGRAM: usize = 65_507;
+
+/// Значение переменной SNMP
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    OctetString(Vec<u8>),
+    Null,
+    ObjectId(Oid),
+    IpAddress([u8; 4]),
+    Counter32(u32),
+    Gauge32(u32),
+    TimeTicks(u32),
+    Opaque(Vec<u8>),
+    Counter64(u64),
+    /// v2c: объекта с таким OID нет
+    NoSuchObject,
+    /// v2c: объект есть, но такого экземпляра нет
+    NoSuchInstance,
+    /// v2c: GETNEXT дошёл до конца MIB
+    EndOfMibView,
+}
+
+impl Value {
+    /// Строковое значение из текста (OCTET STRING)
+    pub fn string(text: &str) -> Self {
+        Value::OctetString(text.as_bytes().to_vec())
+    }
+
+    /// Целое значение, если тип числовой
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(v) => Some(*v),
+            Value::Counter32(v) | Value::Gauge32(v) | Value::TimeTicks(v) => Some(*v as i64),
+            Value::Counter64(v) => i64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Является ли значение исключением v2c
+    pub fn is_exception(&self) -> bool {
+        matches!(
+            self,
+            Value::NoSuchObject | Value::NoSuchInstance | Value::EndOfMibView
+        )
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) -> Result<(), SnmpError> {
+        match self {
+            Value::Integer(v) => ber::write_tlv(out, ber::TAG_INTEGER, &ber::encode_integer(*v)),
+            Value::OctetString(bytes) => ber::write_tlv(out, ber::TAG_OCTET_STRING, bytes),
+            Value::Null => ber::write_tlv(out, ber::TAG_NULL, &[]),
+            Value::ObjectId(oid) => ber::write_tlv(out, ber::TAG_OID, &ber::encode_oid(oid)?),
+            Value::IpAddress(octets) => ber::write_tlv(out, ber::TAG_IP_ADDRESS, octets),
+            Value::Counter32(v) => {
+                ber::write_tlv(out, ber::TAG_COUNTER32, &ber::encode_unsigned(*v as u64))
+            }
+            Value::Gauge32(v) => {
+                ber::write_tlv(out, ber::TAG_GAUGE32, &ber::encode_unsigned(*v as u64))
+            }
+            Value::TimeTicks(v) => {
+                ber::write_tlv(out, ber::TAG_TIMETICKS, &ber::encode_unsigned(*v as u64))
+            }
+            Value::Opaque(bytes) => ber::write_tlv(out, ber::TAG_OPAQUE, bytes),
+            Value::Counter64(v) => {
+                ber::write_tlv(out, ber::TAG_COUNTER64, &ber::encode_unsigned(*v))
+            }
+            Value::NoSuchObject => ber::write_tlv(out, ber::TAG_NO_SUCH_OBJECT, &[]),
+            Value::NoSuchInstance => ber::write_tlv(out, ber::TAG_NO_SUCH_INSTANCE, &[]),
+            Value::EndOfMibView => ber::write_tlv(out, ber::TAG_END_OF_MIB_VIEW, &[]),
+        }
+        Ok(())
+    }
+
+    fn decode(tag: u8, content: &[u8]) -> Result<Self, SnmpError> {
+        let unsigned32 = |content: &[u8]| -> Result<u32, SnmpError> {
+            u32::try_from(ber::decode_unsigned(content)?)
+                .map_err(|_| SnmpError::Malformed("значение больше 32 бит".to_string()))
+        };
+
+        Ok(match tag {
+            ber::TAG_INTEGER => Value::Integer(ber::decode_integer(content)?),
+            ber::TAG_OCTET_STRING => Value::OctetString(content.to_vec()),
+            ber::TAG_NULL => Value::Null,
+            ber::TAG_OID => Value::ObjectId(ber::decode_oid(content)?),
+            ber::TAG_IP_ADDRESS => Value::IpAddress(content.try_into().map_err(|_| {
+                SnmpError::Malformed(format!("IpAddress длиной {} байт", content.len()))
+            })?),
+            ber::TAG_COUNTER32 => Value::Counter32(unsigned32(content)?),
+            ber::TAG_GAUGE32 => Value::Gauge32(unsigned32(content)?),
+            ber::TAG_TIMETICKS => Value::TimeTicks(unsigned32(content)?),
+            ber::TAG_OPAQUE => Value::Opaque(content.to_vec()),
+            ber::TAG_COUNTER64 => Value::Counter64(ber::decode_unsigned(content)?),
+            ber::TAG_NO_SUCH_OBJECT => Value::NoSuchObject,
+            ber::TAG_NO_SUCH_INSTANCE => Value::NoSuchInstance,
+            ber::TAG_END_OF_MIB_VIEW => Value::EndOfMibView,
+            other => return Err(SnmpError::UnsupportedTag(other)),
+        })
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(v) => write!(f, "INTEGER: {}", v),
+            Value::OctetString(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => write!(f, "STRING: \"{}\"", text),
+                Err(_) => write!(f, "Hex-STRING: {}", hex(bytes)),
+            },
+            Value::Null => write!(f, "NULL"),
+            Value::ObjectId(oid) => write!(f, "OID: {}", oid),
+            Value::IpAddress([a, b, c, d]) => write!(f, "IpAddress: {}.{}.{}.{}", a, b, c, d),
+            Value::Counter32(v) => write!(f, "Counter32: {}", v),
+            Value::Gauge32(v) => write!(f, "Gauge32: {}", v),
+            Value::TimeTicks(v) => write!(f, "Timeticks: ({})", v),
+            Value::Opaque(bytes) => write!(f, "OPAQUE: {}", hex(bytes)),
+            Value::Counter64(v) => write!(f, "Counter64: {}", v),
+            Value::NoSuchObject => write!(f, "No Such Object available on this agent at this OID"),
+            Value::NoSuchInstance => write!(f, "No Such Instance currently exists at this OID"),
+            Value::EndOfMibView => write!(f, "No more variables left in this MIB View"),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Пара OID — значение
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarBind {
+    pub oid: Oid,
+    pub value: Value,
+}
+
+impl VarBind {
+    pub fn new(oid: Oid, value: Value) -> Self {
+        Self { oid, value }
+    }
+}
+
+/// Версия протокола
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    V1,
+    V2c,
+}
+
+impl Version {
+    fn to_wire(self) -> i64 {
+        match self {
+            Version::V1 => 0,
+            Version::V2c => 1,
+        }
+    }
+
+    fn from_wire(value: i64) -> Result<Self, SnmpError> {
+        match value {
+            0 => Ok(Version::V1),
+            1 => Ok(Version::V2c),
+            other => Err(SnmpError::UnsupportedVersion(other)),
+        }
+    }
+}
+
+/// Тип PDU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PduType {
+    GetRequest,
+    GetNextRequest,
+    Response,
+    SetRequest,
+}
+
+impl PduType {
+    fn tag(self) -> u8 {
+        match self {
+            PduType::GetRequest => 0xA0,
+            PduType::GetNextRequest => 0xA1,
+            PduType::Response => 0xA2,
+            PduType::SetRequest => 0xA3,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, SnmpError> {
+        match tag {
+            0xA0 => Ok(PduType::GetRequest),
+            0xA1 => Ok(PduType::GetNextRequest),
+            0xA2 => Ok(PduType::Response),
+            0xA3 => Ok(PduType::SetRequest),
+            other => Err(SnmpError::UnsupportedTag(other)),
+        }
+    }
+}
+
+/// error-status из ответа агента (RFC 3416)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorStatus {
+    NoError,
+    TooBig,
+    NoSuchName,
+    BadValue,
+    ReadOnly,
+    GenErr,
+    NoAccess,
+    WrongType,
+    WrongLength,
+    WrongEncoding,
+    WrongValue,
+    NoCreation,
+    InconsistentValue,
+    ResourceUnavailable,
+    CommitFailed,
+    UndoFailed,
+    AuthorizationError,
+    NotWritable,
+    InconsistentName,
+}
+
+impl ErrorStatus {
+    const ALL: [ErrorStatus; 19] = [
+        ErrorStatus::NoError,
+        ErrorStatus::TooBig,
+        ErrorStatus::NoSuchName,
+        ErrorStatus::BadValue,
+        ErrorStatus::ReadOnly,
+        ErrorStatus::GenErr,
+        ErrorStatus::NoAccess,
+        ErrorStatus::WrongType,
+        ErrorStatus::WrongLength,
+        ErrorStatus::WrongEncoding,
+        ErrorStatus::WrongValue,
+        ErrorStatus::NoCreation,
+        ErrorStatus::InconsistentValue,
+        ErrorStatus::ResourceUnavailable,
+        ErrorStatus::CommitFailed,
+        ErrorStatus::UndoFailed,
+        ErrorStatus::AuthorizationError,
+        ErrorStatus::NotWritable,
+        ErrorStatus::InconsistentName,
+    ];
+
+    fn from_wire(value: i64) -> Result<Self, SnmpError> {
+        usize::try_from(value)
+            .ok()
+            .and_then(|i| Self::ALL.get(i).copied())
+            .ok_or_else(|| SnmpError::Malformed(format!("error-status {}", value)))
+    }
+
+    fn to_wire(self) -> i64 {
+        Self::ALL.iter().position(|&s| s == self).unwrap_or(0) as i64
+    }
+}
+
+/// Protocol Data Unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pdu {
+    pub kind: PduType,
+    pub request_id: i32,
+    pub error_status: ErrorStatus,
+    /// Номер переменной с ошибкой, начиная с 1 (0 — ошибки нет)
+    pub error_index: u32,
+    pub varbinds: Vec<VarBind>,
+}
+
+impl Pdu {
+    /// Новый запрос без ошибок
+    pub fn request(kind: PduType, request_id: i32, varbinds: Vec<VarBind>) -> Self {
+        Self {
+            kind,
+            request_id,
+            error_status: ErrorStatus::NoError,
+            error_index: 0,
+            varbinds,
+        }
+    }
+}
+
+/// Полное SNMP-сообщение
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub version: Version,
+    pub community: String,
+    pub pdu: Pdu,
+}
+
+impl Message {
+    /// Кодирует сообщение в байты для отправки по UDP
+    pub fn encode(&self) -> Result<Vec<u8>, SnmpError> {
+        let mut varbinds = Vec::new();
+        for varbind in &self.pdu.varbinds {
+            let mut item = Vec::new();
+            ber::write_tlv(&mut item, ber::TAG_OID, &ber::encode_oid(&varbind.oid)?);
+            varbind.value.encode(&mut item)?;
+            ber::write_tlv(&mut varbinds, ber::TAG_SEQUENCE, &item);
+        }
+
+        let mut pdu = Vec::new();
+        ber::write_tlv(
+            &mut pdu,
+            ber::TAG_INTEGER,
+            &ber::encode_integer(self.pdu.request_id as i64),
+        );
+        ber::write_tlv(
+            &mut pdu,
+            ber::TAG_INTEGER,
+            &ber::encode_integer(self.pdu.error_status.to_wire()),
+        );
+        ber::write_tlv(
+            &mut pdu,
+            ber::TAG_INTEGER,
+            &ber::encode_integer(self.pdu.error_index as i64),
+        );
+        ber::write_tlv(&mut pdu, ber::TAG_SEQUENCE, &varbinds);
+
+        let mut body = Vec::new();
+        ber::write_tlv(
+            &mut body,
+            ber::TAG_INTEGER,
+            &ber::encode_integer(self.version.to_wire()),
+        );
+        ber::write_tlv(&mut body, ber::TAG_OCTET_STRING, self.community.as_bytes());
+        ber::write_tlv(&mut body, self.pdu.kind.tag(), &pdu);
+
+        let mut out = Vec::new();
+        ber::write_tlv(&mut out, ber::TAG_SEQUENCE, &body);
+        Ok(out)
+    }
+
+    /// Разбирает сообщение из байтов UDP-датаграммы
+    pub fn decode(data: &[u8]) -> Result<Self, SnmpError> {
+        let mut outer = Reader::new(data);
+        let mut body = Reader::new(outer.expect(ber::TAG_SEQUENCE)?);
+
+        let version = Version::from_wire(ber::decode_integer(body.expect(ber::TAG_INTEGER)?)?)?;
+        let community = String::from_utf8_lossy(body.expect(ber::TAG_OCTET_STRING)?).into_owned();
+
+        let (tag, pdu_content) = body.read_tlv()?;
+        let kind = PduType::from_tag(tag)?;
+        let mut pdu = Reader::new(pdu_content);
+
+        let request_id = ber::decode_integer(pdu.expect(ber::TAG_INTEGER)?)?;
+        let request_id = i32::try_from(request_id)
+            .map_err(|_| SnmpError::Malformed(format!("request-id {}", request_id)))?;
+        let error_status = ErrorStatus::from_wire(ber::decode_integer(pdu.expect(ber::TAG_INTEGER)?)?)?;
+        let error_index = ber::decode_integer(pdu.expect(ber::TAG_INTEGER)?)?;
+        let error_index = u32::try_from(error_index)
+            .map_err(|_| SnmpError::Malformed(format!("error-index {}", error_index)))?;
+
+        let mut list = Reader::new(pdu.expect(ber::TAG_SEQUENCE)?);
+        let mut varbinds = Vec::new();
+        while !list.is_empty() {
+            let mut item = Reader::new(list.expect(ber::TAG_SEQUENCE)?);
+            let oid = ber::decode_oid(item.expect(ber::TAG_OID)?)?;
+            let (value_tag, value_content) = item.read_tlv()?;
+            varbinds.push(VarBind::new(oid, Value::decode(value_tag, value_content)?));
+        }
+
+        Ok(Message {
+            version,
+            community,
+            pdu: Pdu {
+                kind,
+                request_id,
+                error_status,
+                error_index,
+                varbinds,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        let message = Message {
+            version: Version::V2c,
+            community: "private".to_string(),
+            pdu: Pdu::request(
+                PduType::SetRequest,
+                4242,
+                vec![
+                    VarBind::new("1.3.6.1.2.1.1.5.0".parse().unwrap(), Value::string("CO4554")),
+                    VarBind::new("1.3.6.1.4.1.1.1".parse().unwrap(), Value::Integer(-5)),
+                    VarBind::new("1.3.6.1.4.1.1.2".parse().unwrap(), Value::TimeTicks(123456)),
+                    VarBind::new("1.3.6.1.4.1.1.3".parse().unwrap(), Value::NoSuchInstance),
+                ],
+            ),
+        };
+
+        let bytes = message.encode().unwrap();
+        assert_eq!(Message::decode(&bytes).unwrap(), message);
+    }
+
+    #[test]
+    fn test_decode_net_snmp_get() {
+        // snmpget -v2c -c public localhost 1.3.6.1.2.1.1.1.0
+        let bytes = [
+            0x30, 0x26, 0x02, 0x01, 0x01, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6C, 0x69, 0x63, 0xA0,
+            0x19, 0x02, 0x01, 0x01, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x0E, 0x30, 0x0C,
+            0x06, 0x08, 0x2B, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00, 0x05, 0x00,
+        ];
+        let message = Message::decode(&bytes).unwrap();
+        assert_eq!(message.version, Version::V2c);
+        assert_eq!(message.community, "public");
+        assert_eq!(message.pdu.kind, PduType::GetRequest);
+        assert_eq!(message.pdu.varbinds[0].oid.to_string(), ".1.3.6.1.2.1.1.1.0");
+        assert_eq!(message.pdu.varbinds[0].value, Value::Null);
+    }
+}
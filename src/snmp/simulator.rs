@@ -0,0 +1,596 @@
+//! Симулятор SNMP-агента светофорного контроллера
+//!
+//! Слушает локальный UDP-порт и отвечает на GET/GETNEXT/SET так же,
+//! как это делал бы контроллер на объекте:
+//! - обычные объекты хранят значение и могут быть доступны на запись;
+//! - объекты-детекторы отражают состояние входа (1 — занят, 0 — свободен);
+//! - объекты-условия вычисляют `conditions::Expr` по текущим детекторам
+//!   своего объекта: детектор 1 у CO4554 и у CO9999 — разные входы.
+//!
+//! Все объекты индексируются SCN, закодированным через
+//! [`gen_scn_from_chars`](crate::converters::gen_scn_from_chars).
+//!
+//! # Пример
+//! ```
+//! use traffic_core::conditions::parse_ddr_expression;
+//! use traffic_core::snmp::{Oid, Simulator, SnmpClient, Value, VarBind};
+//!
+//! let simulator = Simulator::new("private");
+//! let detectors: Oid = "1.3.6.1.4.1.99999.1".parse().unwrap();
+//! let demand: Oid = "1.3.6.1.4.1.99999.2".parse().unwrap();
+//! let d1 = simulator.add_detector(&detectors, "CO4554", 1).unwrap();
+//! let cond = simulator
+//!     .add_condition(&demand, "CO4554", parse_ddr_expression("or 1-2").unwrap())
+//!     .unwrap();
+//!
+//! let running = simulator.bind("127.0.0.1:0").unwrap();
+//! let mut client = SnmpClient::connect(running.local_addr(), "private").unwrap();
+//!
+//! client.set(vec![VarBind::new(d1, Value::Integer(1))]).unwrap();
+//! let reply = client.get(&[cond]).unwrap();
+//! assert_eq!(reply[0].value, Value::Integer(1));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::ops::Bound;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::conditions::{evaluate, DetectorInputs, Expr};
+use crate::converters::gen_scn_from_chars;
+use crate::snmp::error::SnmpError;
+use crate::snmp::oid::Oid;
+use crate::snmp::pdu::{ErrorStatus, Message, Pdu, PduType, Value, VarBind, Version, MAX_DATAGRAM};
+
+/// Как часто поток сервера проверяет флаг остановки
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Права доступа к объекту
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    ReadOnly,
+    ReadWrite,
+}
+
+/// Объект MIB симулятора
+#[derive(Debug, Clone)]
+enum Object {
+    /// Обычное значение
+    Scalar { value: Value, access: Access },
+    /// Вход детектора с указанным номером на объекте `scn`
+    Detector { scn: String, detector: u32 },
+    /// Условие, вычисляемое по детекторам объекта `scn`
+    Condition { scn: String, expr: Expr },
+}
+
+#[derive(Debug, Default)]
+struct State {
+    objects: BTreeMap<Oid, Object>,
+    /// Состояния входов: (SCN, номер детектора) → занят
+    detectors: BTreeMap<(String, u32), bool>,
+}
+
+impl State {
+    fn read(&self, object: &Object) -> Value {
+        match object {
+            Object::Scalar { value, .. } => value.clone(),
+            Object::Detector { scn, detector } => Value::Integer(self.detector(scn, *detector) as i64),
+            Object::Condition { scn, expr } => Value::Integer(self.evaluate(scn, expr) as i64),
+        }
+    }
+
+    fn detector(&self, scn: &str, detector: u32) -> bool {
+        self.detectors.get(&(scn.to_string(), detector)).copied().unwrap_or(false)
+    }
+
+    fn evaluate(&self, scn: &str, expr: &Expr) -> bool {
+        evaluate(expr, &Site { state: self, scn })
+    }
+
+    /// Проверяет, можно ли записать `value` в объект `oid`
+    fn check_set(&self, oid: &Oid, value: &Value) -> Result<(), ErrorStatus> {
+        match self.objects.get(oid) {
+            None => Err(ErrorStatus::NoCreation),
+            Some(Object::Condition { .. }) => Err(ErrorStatus::NotWritable),
+            Some(Object::Scalar { access: Access::ReadOnly, .. }) => Err(ErrorStatus::NotWritable),
+            Some(Object::Scalar { value: current, .. }) => {
+                if std::mem::discriminant(current) == std::mem::discriminant(value) {
+                    Ok(())
+                } else {
+                    Err(ErrorStatus::WrongType)
+                }
+            }
+            Some(Object::Detector { .. }) => match value {
+                Value::Integer(0) | Value::Integer(1) => Ok(()),
+                Value::Integer(_) => Err(ErrorStatus::WrongValue),
+                _ => Err(ErrorStatus::WrongType),
+            },
+        }
+    }
+
+    fn apply_set(&mut self, oid: &Oid, new_value: Value) {
+        let detector = match self.objects.get_mut(oid) {
+            Some(Object::Scalar { value, .. }) => {
+                *value = new_value;
+                return;
+            }
+            Some(Object::Detector { scn, detector }) => (scn.clone(), *detector),
+            _ => return,
+        };
+        self.detectors
+            .insert(detector, matches!(new_value, Value::Integer(1)));
+    }
+}
+
+/// Детекторы одного объекта — входы для вычисления его условий
+struct Site<'a> {
+    state: &'a State,
+    scn: &'a str,
+}
+
+impl DetectorInputs for Site<'_> {
+    fn is_active(&self, detector: u32) -> bool {
+        self.state.detector(self.scn, detector)
+    }
+}
+
+/// Преобразует SCN в OID-индекс: "CO4554" → .1.6.67.79.52.53.53.52
+pub fn scn_index(scn: &str) -> Result<Oid, SnmpError> {
+    if scn.trim().is_empty() {
+        return Err(SnmpError::InvalidOid(format!("пустой SCN '{}'", scn)));
+    }
+    gen_scn_from_chars(scn).parse()
+}
+
+/// Симулятор контроллера.
+///
+/// Клонирование дешёвое: все копии разделяют одно состояние, поэтому
+/// тест может менять детекторы, пока сервер обслуживает запросы.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    community: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl Simulator {
+    /// Создаёт пустой симулятор, принимающий запросы с community `community`
+    pub fn new(community: &str) -> Self {
+        Self {
+            community: community.to_string(),
+            state: Arc::default(),
+        }
+    }
+
+    /// Добавляет обычный объект `column.<scn>` и возвращает его полный OID
+    pub fn add_object(
+        &self,
+        column: &Oid,
+        scn: &str,
+        value: Value,
+        access: Access,
+    ) -> Result<Oid, SnmpError> {
+        let oid = column.join(&scn_index(scn)?);
+        self.lock()
+            .objects
+            .insert(oid.clone(), Object::Scalar { value, access });
+        Ok(oid)
+    }
+
+    /// Добавляет вход детектора `column.<scn>.<detector>`. SCN берётся без
+    /// пробелов по краям, как и в OID: "CO4554 " и "CO4554" — один объект
+    pub fn add_detector(&self, column: &Oid, scn: &str, detector: u32) -> Result<Oid, SnmpError> {
+        let scn = scn.trim();
+        let oid = column.join(&scn_index(scn)?).child(detector);
+        let mut state = self.lock();
+        state.objects.insert(oid.clone(), Object::Detector { scn: scn.to_string(), detector });
+        state.detectors.entry((scn.to_string(), detector)).or_insert(false);
+        Ok(oid)
+    }
+
+    /// Добавляет условие `column.<scn>`, вычисляемое по детекторам объекта
+    /// `scn` при каждом чтении
+    pub fn add_condition(&self, column: &Oid, scn: &str, expr: Expr) -> Result<Oid, SnmpError> {
+        let scn = scn.trim();
+        let oid = column.join(&scn_index(scn)?);
+        self.lock().objects.insert(oid.clone(), Object::Condition { scn: scn.to_string(), expr });
+        Ok(oid)
+    }
+
+    /// Меняет состояние детектора объекта `scn` напрямую, минуя SNMP
+    pub fn set_detector(&self, scn: &str, detector: u32, active: bool) {
+        self.lock().detectors.insert((scn.trim().to_string(), detector), active);
+    }
+
+    /// Текущее состояние детектора объекта `scn`
+    pub fn is_detector_active(&self, scn: &str, detector: u32) -> bool {
+        self.lock().detector(scn.trim(), detector)
+    }
+
+    /// Номера занятых детекторов объекта `scn`
+    pub fn active_detectors(&self, scn: &str) -> BTreeSet<u32> {
+        self.lock()
+            .detectors
+            .iter()
+            .filter(|((site, _), active)| site == scn.trim() && **active)
+            .map(|((_, n), _)| *n)
+            .collect()
+    }
+
+    /// Вычисляет произвольное выражение по текущим детекторам объекта `scn`
+    pub fn evaluate(&self, scn: &str, expr: &Expr) -> bool {
+        self.lock().evaluate(scn.trim(), expr)
+    }
+
+    /// Текущее значение объекта (как его увидит GET)
+    pub fn value(&self, oid: &Oid) -> Option<Value> {
+        let state = self.lock();
+        state.objects.get(oid).map(|object| state.read(object))
+    }
+
+    /// Обрабатывает одну датаграмму запроса и возвращает ответ.
+    ///
+    /// `None` — запрос молча отброшен (не разобрался, чужое community
+    /// или это не запрос), как поступают настоящие агенты.
+    pub fn handle(&self, datagram: &[u8]) -> Option<Vec<u8>> {
+        let request = Message::decode(datagram).ok()?;
+        if request.community != self.community || request.pdu.kind == PduType::Response {
+            return None;
+        }
+
+        let pdu = match request.pdu.kind {
+            PduType::GetRequest => self.get(&request),
+            PduType::GetNextRequest => self.get_next(&request),
+            PduType::SetRequest => self.set(&request),
+            PduType::Response => return None,
+        };
+
+        Message {
+            version: request.version,
+            community: request.community,
+            pdu,
+        }
+        .encode()
+        .ok()
+    }
+
+    /// Обслуживает запросы на готовом сокете до ошибки ввода-вывода
+    pub fn serve(&self, socket: &UdpSocket) -> io::Result<()> {
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        loop {
+            let (len, peer) = socket.recv_from(&mut buf)?;
+            if let Some(response) = self.handle(&buf[..len]) {
+                socket.send_to(&response, peer)?;
+            }
+        }
+    }
+
+    /// Запускает сервер в фоновом потоке.
+    ///
+    /// Адрес с портом 0 выбирает свободный порт — узнать его можно
+    /// через [`RunningSimulator::local_addr`].
+    pub fn bind(&self, addr: impl ToSocketAddrs) -> io::Result<RunningSimulator> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+        let local_addr = socket.local_addr()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let simulator = self.clone();
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                let mut buf = vec![0u8; MAX_DATAGRAM];
+                while !stop.load(Ordering::Relaxed) {
+                    let (len, peer) = match socket.recv_from(&mut buf) {
+                        Ok(received) => received,
+                        Err(e)
+                            if matches!(
+                                e.kind(),
+                                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                            ) =>
+                        {
+                            continue;
+                        }
+                        Err(_) => break,
+                    };
+                    if let Some(response) = simulator.handle(&buf[..len]) {
+                        let _ = socket.send_to(&response, peer);
+                    }
+                }
+            })
+        };
+
+        Ok(RunningSimulator {
+            local_addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    fn get(&self, request: &Message) -> Pdu {
+        let state = self.lock();
+        let v1 = request.version == Version::V1;
+        let mut varbinds = Vec::with_capacity(request.pdu.varbinds.len());
+
+        for (i, varbind) in request.pdu.varbinds.iter().enumerate() {
+            match state.objects.get(&varbind.oid) {
+                Some(object) => varbinds.push(VarBind::new(varbind.oid.clone(), state.read(object))),
+                None if v1 => return error_pdu(request, ErrorStatus::NoSuchName, i),
+                None => varbinds.push(VarBind::new(varbind.oid.clone(), Value::NoSuchObject)),
+            }
+        }
+
+        response_pdu(request, varbinds)
+    }
+
+    fn get_next(&self, request: &Message) -> Pdu {
+        let state = self.lock();
+        let v1 = request.version == Version::V1;
+        let mut varbinds = Vec::with_capacity(request.pdu.varbinds.len());
+
+        for (i, varbind) in request.pdu.varbinds.iter().enumerate() {
+            let next = state
+                .objects
+                .range((Bound::Excluded(&varbind.oid), Bound::Unbounded))
+                .next();
+            match next {
+                Some((oid, object)) => varbinds.push(VarBind::new(oid.clone(), state.read(object))),
+                None if v1 => return error_pdu(request, ErrorStatus::NoSuchName, i),
+                None => varbinds.push(VarBind::new(varbind.oid.clone(), Value::EndOfMibView)),
+            }
+        }
+
+        response_pdu(request, varbinds)
+    }
+
+    fn set(&self, request: &Message) -> Pdu {
+        let mut state = self.lock();
+
+        // Сначала проверяем все переменные: SET применяется целиком или никак
+        for (i, varbind) in request.pdu.varbinds.iter().enumerate() {
+            if let Err(status) = state.check_set(&varbind.oid, &varbind.value) {
+                let status = match request.version {
+                    Version::V2c => status,
+                    Version::V1 => v1_status(status),
+                };
+                return error_pdu(request, status, i);
+            }
+        }
+
+        for varbind in &request.pdu.varbinds {
+            state.apply_set(&varbind.oid, varbind.value.clone());
+        }
+
+        let varbinds = request
+            .pdu
+            .varbinds
+            .iter()
+            .map(|vb| {
+                let value = state.objects.get(&vb.oid).map(|o| state.read(o));
+                VarBind::new(vb.oid.clone(), value.unwrap_or(Value::Null))
+            })
+            .collect();
+        response_pdu(request, varbinds)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // Состояние остаётся согласованным даже после паники в другом потоке
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Ошибки v2c, сведённые к набору SNMPv1 (RFC 3584, раздел 4.3)
+fn v1_status(status: ErrorStatus) -> ErrorStatus {
+    match status {
+        ErrorStatus::WrongValue | ErrorStatus::WrongType => ErrorStatus::BadValue,
+        ErrorStatus::NotWritable => ErrorStatus::ReadOnly,
+        _ => ErrorStatus::NoSuchName,
+    }
+}
+
+fn response_pdu(request: &Message, varbinds: Vec<VarBind>) -> Pdu {
+    Pdu {
+        kind: PduType::Response,
+        request_id: request.pdu.request_id,
+        error_status: ErrorStatus::NoError,
+        error_index: 0,
+        varbinds,
+    }
+}
+
+/// Ответ с ошибкой: переменные возвращаются в том виде, в каком пришли
+fn error_pdu(request: &Message, status: ErrorStatus, index: usize) -> Pdu {
+    Pdu {
+        kind: PduType::Response,
+        request_id: request.pdu.request_id,
+        error_status: status,
+        error_index: index as u32 + 1,
+        varbinds: request.pdu.varbinds.clone(),
+    }
+}
+
+/// Запущенный в фоне симулятор. Останавливается при `drop`.
+pub struct RunningSimulator {
+    local_addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RunningSimulator {
+    /// Адрес, на котором слушает симулятор
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Останавливает сервер и дожидается завершения потока
+    pub fn shutdown(mut self) {
+        self.stop_thread();
+    }
+
+    fn stop_thread(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for RunningSimulator {
+    fn drop(&mut self) {
+        self.stop_thread();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::parse_ddr_expression;
+    use crate::snmp::client::SnmpClient;
+
+    fn column(n: u32) -> Oid {
+        Oid::new(vec![1, 3, 6, 1, 4, 1, 99999, n])
+    }
+
+    fn simulator() -> (Simulator, Oid, Vec<Oid>, Oid) {
+        let sim = Simulator::new("private");
+        let stage = sim
+            .add_object(&column(1), "CO4554", Value::Integer(1), Access::ReadWrite)
+            .unwrap();
+        let detectors = (1..=4)
+            .map(|n| sim.add_detector(&column(2), "CO4554", n).unwrap())
+            .collect();
+        let condition = sim
+            .add_condition(
+                &column(3),
+                "CO4554",
+                parse_ddr_expression("(or 1-2) and (or 3-4)").unwrap(),
+            )
+            .unwrap();
+        (sim, stage, detectors, condition)
+    }
+
+    #[test]
+    fn test_scn_index() {
+        assert_eq!(scn_index("CO4554").unwrap().to_string(), ".1.6.67.79.52.53.53.52");
+        assert!(scn_index("  ").is_err());
+    }
+
+    #[test]
+    fn test_get_set_over_udp() {
+        let (sim, stage, detectors, condition) = simulator();
+        let running = sim.bind("127.0.0.1:0").unwrap();
+        let mut client = SnmpClient::connect(running.local_addr(), "private").unwrap();
+
+        let reply = client.get(&[stage.clone(), condition.clone()]).unwrap();
+        assert_eq!(reply[0].value, Value::Integer(1));
+        assert_eq!(reply[1].value, Value::Integer(0));
+
+        client
+            .set(vec![
+                VarBind::new(detectors[1].clone(), Value::Integer(1)),
+                VarBind::new(detectors[2].clone(), Value::Integer(1)),
+                VarBind::new(stage.clone(), Value::Integer(3)),
+            ])
+            .unwrap();
+        assert_eq!(sim.active_detectors("CO4554"), BTreeSet::from([2, 3]));
+
+        let reply = client.get(&[stage, condition]).unwrap();
+        assert_eq!(reply[0].value, Value::Integer(3));
+        assert_eq!(reply[1].value, Value::Integer(1));
+
+        running.shutdown();
+    }
+
+    #[test]
+    fn test_set_errors_are_atomic() {
+        let (sim, stage, detectors, condition) = simulator();
+        let running = sim.bind("127.0.0.1:0").unwrap();
+        let mut client = SnmpClient::connect(running.local_addr(), "private").unwrap();
+
+        let err = client
+            .set(vec![
+                VarBind::new(detectors[0].clone(), Value::Integer(1)),
+                VarBind::new(condition, Value::Integer(1)),
+            ])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SnmpError::ErrorStatus { status: ErrorStatus::NotWritable, index: 2 }
+        ));
+        assert!(!sim.is_detector_active("CO4554", 1));
+
+        let err = client
+            .set(vec![VarBind::new(stage, Value::string("3"))])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SnmpError::ErrorStatus { status: ErrorStatus::WrongType, index: 1 }
+        ));
+    }
+
+    #[test]
+    fn test_walk_and_end_of_mib() {
+        let (sim, _, detectors, condition) = simulator();
+        let running = sim.bind("127.0.0.1:0").unwrap();
+        let mut client = SnmpClient::connect(running.local_addr(), "private").unwrap();
+
+        sim.set_detector("CO4554", 4, true);
+        let walked = client.walk(&column(2)).unwrap();
+        let oids: Vec<Oid> = walked.iter().map(|vb| vb.oid.clone()).collect();
+        assert_eq!(oids, detectors);
+        assert_eq!(walked[3].value, Value::Integer(1));
+
+        let reply = client.get_next(&[condition]).unwrap();
+        assert_eq!(reply[0].value, Value::EndOfMibView);
+    }
+
+    #[test]
+    fn test_sites_are_independent() {
+        let (sim, _, detectors, condition) = simulator();
+        let other = sim.add_detector(&column(2), "CO9999", 1).unwrap();
+        let other_condition = sim
+            .add_condition(&column(3), "CO9999", parse_ddr_expression("1").unwrap())
+            .unwrap();
+        let running = sim.bind("127.0.0.1:0").unwrap();
+        let mut client = SnmpClient::connect(running.local_addr(), "private").unwrap();
+
+        client.set(vec![VarBind::new(other.clone(), Value::Integer(1))]).unwrap();
+        assert!(sim.is_detector_active("CO9999", 1));
+        assert!(!sim.is_detector_active("CO4554", 1));
+        assert_eq!(sim.value(&detectors[0]), Some(Value::Integer(0)));
+        assert_eq!(sim.value(&other_condition), Some(Value::Integer(1)));
+
+        sim.set_detector("CO9999", 3, true);
+        assert_eq!(sim.value(&condition), Some(Value::Integer(0)));
+        assert!(!sim.evaluate("CO9999", &parse_ddr_expression("and 1-3").unwrap()));
+        assert!(sim.evaluate("CO9999", &parse_ddr_expression("1 and 3").unwrap()));
+
+        // Пробелы по краям SCN не делают из него другой объект
+        assert_eq!(sim.add_detector(&column(2), " CO9999 ", 1).unwrap(), other);
+        assert!(sim.is_detector_active("CO9999", 1));
+        sim.set_detector("CO9999 ", 1, false);
+        assert_eq!(sim.value(&other), Some(Value::Integer(0)));
+        assert_eq!(sim.active_detectors(" CO9999"), BTreeSet::from([3]));
+    }
+
+    #[test]
+    fn test_v1_and_foreign_community() {
+        let (sim, _, _, _) = simulator();
+        let running = sim.bind("127.0.0.1:0").unwrap();
+
+        let mut client = SnmpClient::connect(running.local_addr(), "private").unwrap();
+        client.set_version(Version::V1);
+        let err = client.get(&[column(9)]).unwrap_err();
+        assert!(matches!(
+            err,
+            SnmpError::ErrorStatus { status: ErrorStatus::NoSuchName, index: 1 }
+        ));
+
+        let mut stranger = SnmpClient::connect(running.local_addr(), "public").unwrap();
+        stranger.set_timeout(Duration::from_millis(200)).unwrap();
+        assert!(matches!(stranger.get(&[column(1)]), Err(SnmpError::Timeout)));
+    }
+}
@@ -0,0 +1,424 @@
+//! Разбор текстового вывода `snmpwalk` (формат net-snmp)
+//!
+//! Понимает строки вида
+//! ```text
+//! .1.3.6.1.4.1.99999.1.1.6.67.79.52.53.53.52 = INTEGER: 5
+//! iso.3.6.1.2.1.1.5.0 = STRING: "CO4554"
+//! .1.3.6.1.4.1.99999.7.0 = Hex-STRING: 43 4F 34 35
+//! .1.3.6.1.2.1.1.3.0 = Timeticks: (123456) 0:20:34.56
+//! ```
+//! включая многострочные STRING и Hex-STRING, и группирует строки
+//! по объектам (SCN), найденным в OID.
+//!
+//! # Пример
+//! ```
+//! use traffic_core::snmp::{group_by_site, parse_walk, Value};
+//!
+//! let dump = "\
+//! .1.3.6.1.4.1.99999.1.1.6.67.79.52.53.53.52 = INTEGER: 3
+//! .1.3.6.1.4.1.99999.1.1.5.67.49.49.49.49 = INTEGER: 1
+//! .1.3.6.1.4.1.99999.2.1.6.67.79.52.53.53.52 = STRING: \"Lenina\"";
+//!
+//! let sites = group_by_site(parse_walk(dump).unwrap());
+//! assert_eq!(sites[0].scn.as_deref(), Some("CO4554"));
+//! assert_eq!(sites[0].rows.len(), 2);
+//! assert_eq!(sites[1].rows[0].varbind.value, Value::Integer(1));
+//! ```
+
+use crate::converters::{find_scn, ScnMatch};
+use crate::snmp::error::SnmpError;
+use crate::snmp::oid::Oid;
+use crate::snmp::pdu::{Value, VarBind};
+
+/// Символьные префиксы, которые net-snmp печатает без загруженных MIB
+const KNOWN_PREFIXES: [(&str, &[u32]); 5] = [
+    ("SNMPv2-SMI::enterprises", &[1, 3, 6, 1, 4, 1]),
+    ("SNMPv2-SMI::mib-2", &[1, 3, 6, 1, 2, 1]),
+    ("SNMPv2-SMI::experimental", &[1, 3, 6, 1, 3]),
+    ("SNMPv2-SMI::private", &[1, 3, 6, 1, 4]),
+    ("iso", &[1]),
+];
+
+/// Строка дампа
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalkRow {
+    /// Номер строки в исходном тексте, начиная с 1
+    pub line: usize,
+    pub varbind: VarBind,
+}
+
+impl WalkRow {
+    /// SCN, закодированный в OID строки
+    pub fn scn(&self) -> Option<ScnMatch> {
+        find_scn(self.varbind.oid.components())
+    }
+}
+
+/// Строки дампа, относящиеся к одному объекту
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteRows {
+    /// SCN объекта; `None` — строки без SCN в OID (sysDescr и т.п.)
+    pub scn: Option<String>,
+    pub rows: Vec<WalkRow>,
+}
+
+/// Разбирает весь дамп. Пустые строки и строки с `#` пропускаются.
+/// Строка, которая не продолжает значение и не начинает запись, — ошибка
+/// с её номером.
+pub fn parse_walk(text: &str) -> Result<Vec<WalkRow>, SnmpError> {
+    // Сначала склеиваем продолжения многострочных значений: незакрытую
+    // кавычку STRING и байты Hex-STRING
+    let mut pending: Vec<(usize, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let continues = match pending.last() {
+            Some((_, current)) => unterminated_string(current) || (hex_value(current) && hex_bytes(line)),
+            None => false,
+        };
+
+        if continues {
+            let (_, current) = pending.last_mut().expect("есть незавершённая строка");
+            current.push('\n');
+            current.push_str(line);
+        } else if !line.trim().is_empty() && !line.trim_start().starts_with('#') {
+            pending.push((i + 1, line.to_string()));
+        }
+    }
+
+    pending
+        .into_iter()
+        .map(|(line, text)| {
+            parse_walk_line(&text)
+                .map(|varbind| WalkRow { line, varbind })
+                .map_err(|e| match e {
+                    SnmpError::WalkSyntax { message, .. } => SnmpError::WalkSyntax { line, message },
+                    other => SnmpError::WalkSyntax { line, message: other.to_string() },
+                })
+        })
+        .collect()
+}
+
+/// Разбирает одну (возможно, склеенную из нескольких) строку дампа
+pub fn parse_walk_line(text: &str) -> Result<VarBind, SnmpError> {
+    let (name, value) = text
+        .split_once(" = ")
+        .or_else(|| text.trim_end().strip_suffix(" =").map(|name| (name, "")))
+        .ok_or_else(|| syntax("нет разделителя ' = '"))?;
+
+    let oid = parse_oid(name.trim())?;
+    let value = parse_value(value.trim())?;
+    Ok(VarBind::new(oid, value))
+}
+
+/// Группирует строки по SCN в порядке первого появления объекта
+pub fn group_by_site(rows: Vec<WalkRow>) -> Vec<SiteRows> {
+    let mut sites: Vec<SiteRows> = Vec::new();
+    for row in rows {
+        let scn = row.scn().map(|found| found.scn);
+        match sites.iter_mut().find(|site| site.scn == scn) {
+            Some(site) => site.rows.push(row),
+            None => sites.push(SiteRows { scn, rows: vec![row] }),
+        }
+    }
+    sites
+}
+
+fn syntax(message: &str) -> SnmpError {
+    SnmpError::WalkSyntax { line: 0, message: message.to_string() }
+}
+
+/// Значение записи — Hex-STRING или BITS, байты которых переносятся на
+/// следующие строки
+fn hex_value(text: &str) -> bool {
+    text.split_once(" = ")
+        .is_some_and(|(_, value)| matches!(value.trim_start().split_once(':'), Some(("Hex-STRING" | "BITS", _))))
+}
+
+/// Строка из пар шестнадцатеричных цифр: "00 01 4F"
+fn hex_bytes(line: &str) -> bool {
+    let mut bytes = line.split_whitespace().peekable();
+    bytes.peek().is_some() && bytes.all(|byte| byte.len() == 2 && byte.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Открыта ли кавычка STRING, которую закроет одна из следующих строк
+fn unterminated_string(text: &str) -> bool {
+    let Some((_, value)) = text.split_once(" = ") else {
+        return false;
+    };
+    let Some(quoted) = value.trim_start().strip_prefix("STRING: \"") else {
+        return false;
+    };
+
+    let mut escaped = false;
+    for c in quoted.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+fn parse_oid(name: &str) -> Result<Oid, SnmpError> {
+    if name.starts_with('.') || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return name.parse();
+    }
+
+    for (prefix, components) in KNOWN_PREFIXES {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            let base = Oid::from(components);
+            return match rest {
+                "" => Ok(base),
+                _ if rest.starts_with('.') => Ok(base.join(&rest.parse()?)),
+                _ => continue,
+            };
+        }
+    }
+
+    Err(syntax(&format!(
+        "символьный OID '{}' не поддерживается, снимите дамп с ключом -On",
+        name
+    )))
+}
+
+fn parse_value(text: &str) -> Result<Value, SnmpError> {
+    match text {
+        "" | "\"\"" => return Ok(Value::OctetString(Vec::new())),
+        "NULL" => return Ok(Value::Null),
+        _ if text.starts_with("No Such Object") => return Ok(Value::NoSuchObject),
+        _ if text.starts_with("No Such Instance") => return Ok(Value::NoSuchInstance),
+        _ if text.starts_with("No more variables") => return Ok(Value::EndOfMibView),
+        _ => {}
+    }
+
+    let (kind, body) = text
+        .split_once(':')
+        .ok_or_else(|| syntax(&format!("не указан тип значения в '{}'", text)))?;
+    let body = body.trim();
+
+    match kind {
+        "INTEGER" => parse_integer(body).map(Value::Integer),
+        "STRING" => Ok(Value::OctetString(parse_string(body)?.into_bytes())),
+        "Hex-STRING" => parse_hex(body).map(Value::OctetString),
+        "BITS" => parse_bits(body).map(Value::OctetString),
+        "OID" => parse_oid(body).map(Value::ObjectId),
+        "IpAddress" => parse_ip(body).map(Value::IpAddress),
+        "Counter32" => parse_unsigned(body).map(Value::Counter32),
+        "Gauge32" | "Unsigned32" => parse_unsigned(body).map(Value::Gauge32),
+        "Timeticks" => parse_timeticks(body).map(Value::TimeTicks),
+        "Counter64" => first_word(body)
+            .parse()
+            .map(Value::Counter64)
+            .map_err(|_| syntax(&format!("ожидалось число, получено '{}'", body))),
+        "Opaque" => parse_hex(body).map(Value::Opaque),
+        other => Err(syntax(&format!("неизвестный тип значения '{}'", other))),
+    }
+}
+
+fn first_word(text: &str) -> &str {
+    text.split_whitespace().next().unwrap_or("")
+}
+
+/// INTEGER: 5, INTEGER: -1, INTEGER: enabled(1), INTEGER: 30 seconds
+fn parse_integer(body: &str) -> Result<i64, SnmpError> {
+    let word = first_word(body);
+    let number = match (word.find('('), word.strip_suffix(')')) {
+        (Some(open), Some(inner)) => &inner[open + 1..],
+        _ => word,
+    };
+    number
+        .parse()
+        .map_err(|_| syntax(&format!("ожидалось число, получено '{}'", body)))
+}
+
+fn parse_unsigned(body: &str) -> Result<u32, SnmpError> {
+    first_word(body)
+        .parse()
+        .map_err(|_| syntax(&format!("ожидалось число, получено '{}'", body)))
+}
+
+/// Timeticks: (123456) 0:20:34.56
+fn parse_timeticks(body: &str) -> Result<u32, SnmpError> {
+    let ticks = body
+        .strip_prefix('(')
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(ticks, _)| ticks)
+        .unwrap_or(first_word(body));
+    ticks
+        .parse()
+        .map_err(|_| syntax(&format!("некорректные Timeticks '{}'", body)))
+}
+
+/// STRING: "текст" (с экранированием \" и \\) или STRING: текст
+fn parse_string(body: &str) -> Result<String, SnmpError> {
+    let Some(quoted) = body.strip_prefix('"') else {
+        return Ok(body.to_string());
+    };
+
+    let mut out = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            '"' => return Ok(out),
+            _ => out.push(c),
+        }
+    }
+    Err(syntax("незакрытая кавычка в STRING"))
+}
+
+/// Hex-STRING: 43 4F 34 35 (пары могут переноситься на следующие строки)
+fn parse_hex(body: &str) -> Result<Vec<u8>, SnmpError> {
+    parse_hex_tokens(body.split_whitespace())
+}
+
+/// BITS: 40 00 lineLocked(1) — после байтов идут имена установленных битов
+fn parse_bits(body: &str) -> Result<Vec<u8>, SnmpError> {
+    let is_label = |token: &str| token.ends_with(')') && token.contains('(');
+    let labels = body.split_whitespace().skip_while(|token| !is_label(token));
+    if let Some(token) = labels.clone().find(|token| !is_label(token)) {
+        return Err(syntax(&format!("некорректный байт '{}'", token)));
+    }
+    parse_hex_tokens(body.split_whitespace().take_while(|token| !is_label(token)))
+}
+
+/// Каждый токен — ровно две шестнадцатеричные цифры
+fn parse_hex_tokens<'a>(tokens: impl Iterator<Item = &'a str>) -> Result<Vec<u8>, SnmpError> {
+    tokens
+        .map(|pair| {
+            let digits = pair.len() == 2 && pair.bytes().all(|b| b.is_ascii_hexdigit());
+            digits
+                .then(|| u8::from_str_radix(pair, 16).ok())
+                .flatten()
+                .ok_or_else(|| syntax(&format!("некорректный байт '{}'", pair)))
+        })
+        .collect()
+}
+
+fn parse_ip(body: &str) -> Result<[u8; 4], SnmpError> {
+    let octets = body
+        .split('.')
+        .map(|part| part.trim().parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()
+        .and_then(|octets| <[u8; 4]>::try_from(octets).ok());
+    octets.ok_or_else(|| syntax(&format!("некорректный IpAddress '{}'", body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(line: &str) -> Value {
+        parse_walk_line(line).unwrap().value
+    }
+
+    #[test]
+    fn test_value_types() {
+        assert_eq!(value(".1.3.6.1.2.1.2.1.0 = INTEGER: 5"), Value::Integer(5));
+        assert_eq!(value(".1.3.6.1.2.1.2.1.0 = INTEGER: up(1)"), Value::Integer(1));
+        assert_eq!(value(".1.3.6.1.2.1.2.1.0 = INTEGER: -3"), Value::Integer(-3));
+        assert_eq!(value(".1.3.6.1.2.1.1.5.0 = STRING: \"CO \\\"4554\\\"\""), Value::string("CO \"4554\""));
+        assert_eq!(value(".1.3.6.1.2.1.1.5.0 = \"\""), Value::OctetString(vec![]));
+        assert_eq!(
+            value(".1.3.6.1.4.1.99999.7.0 = Hex-STRING: 43 4F 34 35 "),
+            Value::OctetString(b"CO45".to_vec())
+        );
+        assert_eq!(
+            value(".1.3.6.1.4.1.99999.8.0 = BITS: 40 80 lineLocked(1) fault(8)"),
+            Value::OctetString(vec![0x40, 0x80])
+        );
+        for bad in ["Hex-STRING: 43 4 F", "Hex-STRING: 43 +F", "Opaque: 43 4G", "BITS: 40 up(1) 80"] {
+            let line = format!(".1.3.6.1.4.1.99999.7.0 = {}", bad);
+            assert!(parse_walk_line(&line).is_err(), "{}", bad);
+        }
+        assert_eq!(
+            value(".1.3.6.1.2.1.1.3.0 = Timeticks: (123456) 0:20:34.56"),
+            Value::TimeTicks(123456)
+        );
+        assert_eq!(value(".1.3.6.1.2.1.2.2.1.10.1 = Counter32: 77"), Value::Counter32(77));
+        assert_eq!(value(".1.3.6.1.2.1.2.2.1.5.1 = Gauge32: 1000000"), Value::Gauge32(1000000));
+        assert_eq!(
+            value(".1.3.6.1.2.1.4.20.1.1.10.0.0.1 = IpAddress: 10.0.0.1"),
+            Value::IpAddress([10, 0, 0, 1])
+        );
+        assert_eq!(
+            value(".1.3.6.1.2.1.1.2.0 = OID: .1.3.6.1.4.1.99999"),
+            Value::ObjectId("1.3.6.1.4.1.99999".parse().unwrap())
+        );
+        assert_eq!(
+            value(".1.3.6.1.2.1.1.9 = No more variables left in this MIB View (It is past the end of the MIB tree)"),
+            Value::EndOfMibView
+        );
+    }
+
+    #[test]
+    fn test_symbolic_prefixes() {
+        let varbind = parse_walk_line("iso.3.6.1.2.1.1.5.0 = STRING: \"x\"").unwrap();
+        assert_eq!(varbind.oid.to_string(), ".1.3.6.1.2.1.1.5.0");
+
+        let varbind = parse_walk_line("SNMPv2-SMI::enterprises.99999.1.0 = INTEGER: 1").unwrap();
+        assert_eq!(varbind.oid.to_string(), ".1.3.6.1.4.1.99999.1.0");
+
+        assert!(parse_walk_line("SNMPv2-MIB::sysName.0 = STRING: x").is_err());
+    }
+
+    #[test]
+    fn test_multiline_values() {
+        let dump = "\
+.1.3.6.1.2.1.1.1.0 = STRING: \"line one
+line = two\"
+.1.3.6.1.4.1.99999.7.0 = Hex-STRING: 43 4F 34 35 35 34 00 00 00 00 00 00 00 00 00 00
+00 01
+.1.3.6.1.2.1.1.5.0 = STRING: \"CO4554\"";
+
+        let rows = parse_walk(dump).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].varbind.value, Value::string("line one\nline = two"));
+        match &rows[1].varbind.value {
+            Value::OctetString(bytes) => assert_eq!(bytes.len(), 18),
+            other => panic!("Expected octet string, got {:?}", other),
+        }
+        assert_eq!(rows[2].line, 5);
+    }
+
+    #[test]
+    fn test_error_has_line_number() {
+        let dump = ".1.3.6.1.2.1.1.5.0 = INTEGER: 1\n\n.1.3.6.1.2.1.1.6.0 = FOO: 1";
+        match parse_walk(dump) {
+            Err(SnmpError::WalkSyntax { line, .. }) => assert_eq!(line, 3),
+            other => panic!("Expected syntax error, got {:?}", other),
+        }
+
+        // Пустые строки и комментарии после значения не приклеиваются к нему,
+        // а мусор не считается продолжением
+        let dump = "\
+.1.3.6.1.4.1.99999.7.0 = Hex-STRING: 43 4F
+
+# конец таблицы
+.1.3.6.1.2.1.1.5.0 = STRING: \"CO4554\"
+Timeout: No Response from 10.0.0.1";
+        match parse_walk(dump) {
+            Err(SnmpError::WalkSyntax { line, .. }) => assert_eq!(line, 5),
+            other => panic!("Expected syntax error, got {:?}", other),
+        }
+        let rows = parse_walk(&dump[..dump.rfind('\n').unwrap()]).unwrap();
+        assert_eq!(rows[0].varbind.value, Value::OctetString(b"CO".to_vec()));
+        assert_eq!(rows[1].line, 4);
+    }
+
+    #[test]
+    fn test_group_by_site() {
+        let dump = "\
+.1.3.6.1.2.1.1.5.0 = STRING: \"agent\"
+.1.3.6.1.4.1.99999.3.1.6.67.79.52.53.53.52.1 = INTEGER: 0
+.1.3.6.1.4.1.99999.3.1.5.67.49.49.49.49.1 = INTEGER: 1
+.1.3.6.1.4.1.99999.3.1.6.67.79.52.53.53.52.2 = INTEGER: 1";
+
+        let sites = group_by_site(parse_walk(dump).unwrap());
+        let names: Vec<Option<&str>> = sites.iter().map(|s| s.scn.as_deref()).collect();
+        assert_eq!(names, vec![None, Some("CO4554"), Some("C1111")]);
+        assert_eq!(sites[1].rows.len(), 2);
+        assert_eq!(sites[1].rows[1].line, 4);
+    }
+}
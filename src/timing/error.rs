@@ -0,0 +1,28 @@
+//! Типы ошибок расчёта плана
+
+use thiserror::Error;
+
+/// Ошибки расчёта жёсткого плана
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TimingError {
+    /// Ошибка: не задано ни одной фазы
+    #[error("Не задано ни одной фазы")]
+    NoStages,
+
+    /// Ошибка: некорректная интенсивность или поток насыщения
+    #[error("Фаза {stage}: интенсивность должна быть ≥ 0, поток насыщения — > 0")]
+    InvalidFlow { stage: u32 },
+
+    /// Ошибка: минимум больше максимума
+    #[error("Некорректные ограничения: {0}")]
+    InvalidLimits(String),
+
+    /// Ошибка: суммарная загрузка не меньше 1 — перекрёсток перегружен
+    #[error("Суммарный фазовый коэффициент Y = {0:.3} ≥ 1: перекрёсток перегружен")]
+    Oversaturated(f64),
+
+    /// Ошибка: минимальные зелёные не помещаются в максимальный цикл
+    /// или максимальные не заполняют минимальный
+    #[error("Нужен цикл не меньше {required} с, а максимум {max_cycle} с")]
+    Infeasible { required: u32, max_cycle: u32 },
+}
@@ -0,0 +1,32 @@
+//! Расчёт жёсткого плана по Вебстеру
+//!
+//! По интенсивностям, потокам насыщения, потерянному времени и промтактам
+//! фаз вычисляет оптимальный цикл Вебстера и распределяет зелёное время
+//! пропорционально фазовым коэффициентам с учётом минимальных и
+//! максимальных зелёных и ограничений цикла.
+//!
+//! ```text
+//! y  = q / s                 фазовый коэффициент
+//! L  = Σ (l + I)             потерянное время цикла
+//! C0 = (1.5 L + 5) / (1 − Y) оптимальный цикл
+//! g  = (C − L) · y / Y       зелёное время фазы
+//! ```
+//!
+//! # Пример
+//! ```
+//! use traffic_core::timing::{webster, PlanLimits, StageDemand};
+//!
+//! let stages = [
+//!     StageDemand::new(1, 600.0, 1800.0).with_intergreen(5),
+//!     StageDemand::new(2, 450.0, 1800.0).with_intergreen(5),
+//! ];
+//! let plan = webster(&stages, &PlanLimits::default()).unwrap();
+//! assert_eq!(plan.cycle, 63);  // C0 = (1.5 · 14 + 5) / (1 − 0.583) = 62.4
+//! assert_eq!(plan.greens[0].green + plan.greens[1].green, 63 - plan.lost_time);
+//! ```
+
+mod error;    // error.rs — типы ошибок
+mod webster;  // webster.rs — цикл Вебстера и распределение зелёного
+
+pub use error::TimingError;
+pub use webster::{apply_intergreens, webster, PlanLimits, StageDemand, StageTiming, TimingPlan};
@@ -0,0 +1,350 @@
+//! Цикл Вебстера и распределение зелёного времени
+
+use std::collections::BTreeMap;
+
+use crate::timing::error::TimingError;
+
+/// Исходные данные фазы
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageDemand {
+    /// Номер фазы
+    pub stage: u32,
+
+    /// Интенсивность расчётного (критического) направления, авт/ч
+    pub flow: f64,
+
+    /// Поток насыщения этого направления, авт/ч
+    pub saturation_flow: f64,
+
+    /// Потерянное время в начале и конце зелёного, с
+    pub lost_time: u32,
+
+    /// Промтакт после фазы (до следующей по циклу), с
+    pub intergreen: u32,
+
+    /// Минимальное и максимальное зелёное, с
+    pub min_green: u32,
+    pub max_green: u32,
+}
+
+impl StageDemand {
+    /// Фаза с потерянным временем 2 с, без промтакта, зелёное 5…90 с
+    pub fn new(stage: u32, flow: f64, saturation_flow: f64) -> Self {
+        Self {
+            stage,
+            flow,
+            saturation_flow,
+            lost_time: 2,
+            intergreen: 0,
+            min_green: 5,
+            max_green: 90,
+        }
+    }
+
+    pub fn with_lost_time(mut self, lost_time: u32) -> Self {
+        self.lost_time = lost_time;
+        self
+    }
+
+    pub fn with_intergreen(mut self, intergreen: u32) -> Self {
+        self.intergreen = intergreen;
+        self
+    }
+
+    pub fn with_greens(mut self, min_green: u32, max_green: u32) -> Self {
+        self.min_green = min_green;
+        self.max_green = max_green;
+        self
+    }
+
+    /// Фазовый коэффициент y = q / s
+    pub fn flow_ratio(&self) -> f64 {
+        self.flow / self.saturation_flow
+    }
+}
+
+/// Ограничения цикла, с
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanLimits {
+    pub min_cycle: u32,
+    pub max_cycle: u32,
+}
+
+impl Default for PlanLimits {
+    fn default() -> Self {
+        Self {
+            min_cycle: 30,
+            max_cycle: 120,
+        }
+    }
+}
+
+/// Результат для одной фазы
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageTiming {
+    pub stage: u32,
+    /// Зелёное время, с
+    pub green: u32,
+    /// Фазовый коэффициент y
+    pub flow_ratio: f64,
+    /// Степень насыщения x = y · C / g
+    pub saturation: f64,
+}
+
+/// Рассчитанный план
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingPlan {
+    /// Итоговый цикл, с
+    pub cycle: u32,
+    /// Оптимальный цикл Вебстера до округления и ограничений, с
+    pub webster_cycle: f64,
+    /// Суммарный фазовый коэффициент Y
+    pub flow_ratio: f64,
+    /// Потерянное время цикла L, с
+    pub lost_time: u32,
+    pub greens: Vec<StageTiming>,
+}
+
+impl TimingPlan {
+    /// Зелёное время фазы
+    pub fn green(&self, stage: u32) -> Option<u32> {
+        self.greens.iter().find(|g| g.stage == stage).map(|g| g.green)
+    }
+}
+
+/// Проставляет промтакты фаз по порядку цикла из промтактов между фазами
+/// (например, `IntergreenMatrix::stage_intergreens`): после последней фазы
+/// идёт первая. Недостающие пары не меняют промтакт.
+pub fn apply_intergreens(stages: &mut [StageDemand], intergreens: &BTreeMap<(u32, u32), u32>) {
+    let order: Vec<u32> = stages.iter().map(|stage| stage.stage).collect();
+    for (i, stage) in stages.iter_mut().enumerate() {
+        let next = order[(i + 1) % order.len()];
+        if let Some(&intergreen) = intergreens.get(&(stage.stage, next)) {
+            stage.intergreen = intergreen;
+        }
+    }
+}
+
+/// Рассчитывает план: цикл Вебстера в пределах `limits`, зелёное
+/// пропорционально фазовым коэффициентам в пределах min/max каждой фазы.
+///
+/// Если минимальные зелёные не помещаются в цикл, цикл увеличивается;
+/// если максимальные не заполняют его — уменьшается.
+pub fn webster(stages: &[StageDemand], limits: &PlanLimits) -> Result<TimingPlan, TimingError> {
+    if stages.is_empty() {
+        return Err(TimingError::NoStages);
+    }
+    if limits.min_cycle > limits.max_cycle {
+        return Err(TimingError::InvalidLimits(format!(
+            "минимальный цикл {} с больше максимального {} с",
+            limits.min_cycle, limits.max_cycle
+        )));
+    }
+    for stage in stages {
+        if !(stage.flow >= 0.0 && stage.saturation_flow > 0.0) {
+            return Err(TimingError::InvalidFlow { stage: stage.stage });
+        }
+        if stage.min_green > stage.max_green {
+            return Err(TimingError::InvalidLimits(format!(
+                "фаза {}: минимальное зелёное {} с больше максимального {} с",
+                stage.stage, stage.min_green, stage.max_green
+            )));
+        }
+    }
+
+    let ratios: Vec<f64> = stages.iter().map(StageDemand::flow_ratio).collect();
+    let total_ratio: f64 = ratios.iter().sum();
+    if total_ratio >= 1.0 {
+        return Err(TimingError::Oversaturated(total_ratio));
+    }
+
+    let lost_time: u32 = stages.iter().map(|s| s.lost_time + s.intergreen).sum();
+    let webster_cycle = (1.5 * lost_time as f64 + 5.0) / (1.0 - total_ratio);
+
+    let required = lost_time + stages.iter().map(|s| s.min_green).sum::<u32>();
+    if required > limits.max_cycle {
+        return Err(TimingError::Infeasible { required, max_cycle: limits.max_cycle });
+    }
+    // Цикл складывается из округлённых зелёных, а они не больше максимумов
+    let longest = stages.iter().fold(lost_time, |sum, s| sum.saturating_add(s.max_green));
+    if longest < limits.min_cycle {
+        return Err(TimingError::Infeasible { required: limits.min_cycle, max_cycle: longest });
+    }
+    let cycle = (webster_cycle.ceil() as u32)
+        .clamp(limits.min_cycle, limits.max_cycle)
+        .max(required);
+
+    let greens = distribute((cycle - lost_time) as f64, stages, &ratios);
+    let greens = round_greens(&greens, stages);
+    let cycle = lost_time + greens.iter().sum::<u32>();
+
+    let greens = stages
+        .iter()
+        .zip(&ratios)
+        .zip(greens)
+        .map(|((stage, &ratio), green)| StageTiming {
+            stage: stage.stage,
+            green,
+            flow_ratio: ratio,
+            saturation: if green > 0 { ratio * cycle as f64 / green as f64 } else { 0.0 },
+        })
+        .collect();
+
+    Ok(TimingPlan {
+        cycle,
+        webster_cycle,
+        flow_ratio: total_ratio,
+        lost_time,
+        greens,
+    })
+}
+
+/// Делит доступное зелёное пропорционально `ratios`; фазы, вышедшие за свои
+/// пределы, фиксируются на границе, остаток делится между остальными
+fn distribute(available: f64, stages: &[StageDemand], ratios: &[f64]) -> Vec<f64> {
+    let mut fixed: Vec<Option<f64>> = vec![None; stages.len()];
+
+    loop {
+        let remaining = available - fixed.iter().flatten().sum::<f64>();
+        let free: Vec<usize> = (0..stages.len()).filter(|&i| fixed[i].is_none()).collect();
+        if free.is_empty() {
+            break;
+        }
+        let free_ratio: f64 = free.iter().map(|&i| ratios[i]).sum();
+        let share = |i: usize| {
+            if free_ratio > 0.0 {
+                remaining * ratios[i] / free_ratio
+            } else {
+                remaining / free.len() as f64
+            }
+        };
+
+        let mut changed = false;
+        for &i in &free {
+            let green = share(i);
+            let (min, max) = (stages[i].min_green as f64, stages[i].max_green as f64);
+            if green < min {
+                fixed[i] = Some(min);
+                changed = true;
+            } else if green > max {
+                fixed[i] = Some(max);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            for &i in &free {
+                fixed[i] = Some(share(i));
+            }
+            break;
+        }
+    }
+
+    fixed.into_iter().map(|green| green.unwrap_or_default()).collect()
+}
+
+/// Округляет до целых секунд, сохраняя сумму (метод наибольших остатков)
+fn round_greens(greens: &[f64], stages: &[StageDemand]) -> Vec<u32> {
+    let total = greens.iter().sum::<f64>().round() as u32;
+    let mut rounded: Vec<u32> = greens.iter().map(|green| green.floor() as u32).collect();
+
+    let mut order: Vec<usize> = (0..greens.len()).collect();
+    order.sort_by(|&a, &b| {
+        let fraction = |i: usize| greens[i] - greens[i].floor();
+        fraction(b).total_cmp(&fraction(a))
+    });
+
+    let mut missing = total.saturating_sub(rounded.iter().sum());
+    for i in order.into_iter().cycle().take(greens.len() * 2) {
+        if missing == 0 {
+            break;
+        }
+        if rounded[i] < stages[i].max_green {
+            rounded[i] += 1;
+            missing -= 1;
+        }
+    }
+    rounded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demands() -> Vec<StageDemand> {
+        vec![
+            StageDemand::new(1, 900.0, 1800.0).with_intergreen(5),
+            StageDemand::new(2, 300.0, 1800.0).with_intergreen(5),
+            StageDemand::new(3, 180.0, 1800.0).with_intergreen(4),
+        ]
+    }
+
+    #[test]
+    fn test_webster_cycle() {
+        let plan = webster(&demands(), &PlanLimits::default()).unwrap();
+
+        // Y = 0.5 + 0.1667 + 0.1 = 0.7667, L = 3 · 2 + 14 = 20
+        assert!((plan.flow_ratio - 0.7667).abs() < 1e-3);
+        assert_eq!(plan.lost_time, 20);
+        // C0 = (1.5 · 20 + 5) / (1 − 0.7667) = 150 → ограничен 120
+        assert!((plan.webster_cycle - 150.0).abs() < 0.1);
+        assert_eq!(plan.cycle, 120);
+
+        // 100 с зелёного делятся 0.5 : 0.1667 : 0.1, минимумы соблюдены
+        assert_eq!(plan.green(1), Some(65));
+        assert_eq!(plan.green(2), Some(22));
+        assert_eq!(plan.green(3), Some(13));
+    }
+
+    #[test]
+    fn test_clamping() {
+        let mut stages = demands();
+        stages[0] = stages[0].clone().with_greens(5, 40);
+        stages[2] = stages[2].clone().with_greens(20, 90);
+        let plan = webster(&stages, &PlanLimits { min_cycle: 30, max_cycle: 100 }).unwrap();
+
+        // 80 с зелёного: фаза 3 поднята до минимума, фаза 1 упирается в максимум
+        assert_eq!(plan.green(1), Some(40));
+        assert_eq!(plan.green(3), Some(20));
+        assert_eq!(plan.green(2), Some(20));
+        assert_eq!(plan.cycle, 100);
+
+        // Максимумы не заполняют цикл — цикл сокращается
+        let short: Vec<StageDemand> = demands().into_iter().map(|s| s.with_greens(5, 10)).collect();
+        assert_eq!(webster(&short, &PlanLimits::default()).unwrap().cycle, 50);
+    }
+
+    #[test]
+    fn test_errors() {
+        let limits = PlanLimits::default();
+        assert_eq!(webster(&[], &limits), Err(TimingError::NoStages));
+        assert_eq!(
+            webster(&[StageDemand::new(1, 2000.0, 1800.0)], &limits),
+            Err(TimingError::Oversaturated(2000.0 / 1800.0))
+        );
+        assert_eq!(
+            webster(&[StageDemand::new(7, 100.0, 0.0)], &limits),
+            Err(TimingError::InvalidFlow { stage: 7 })
+        );
+        let greedy: Vec<StageDemand> = demands().into_iter().map(|s| s.with_greens(40, 90)).collect();
+        assert_eq!(
+            webster(&greedy, &limits),
+            Err(TimingError::Infeasible { required: 140, max_cycle: 120 })
+        );
+        // Максимумы с потерянным временем не дотягивают до минимального цикла
+        let short: Vec<StageDemand> = demands().into_iter().map(|s| s.with_greens(5, 10)).collect();
+        assert_eq!(
+            webster(&short, &PlanLimits { min_cycle: 60, max_cycle: 120 }),
+            Err(TimingError::Infeasible { required: 60, max_cycle: 50 })
+        );
+    }
+
+    #[test]
+    fn test_apply_intergreens() {
+        let mut stages = demands();
+        let intergreens = BTreeMap::from([((1, 2), 6), ((2, 3), 7), ((3, 1), 8), ((1, 3), 9)]);
+        apply_intergreens(&mut stages, &intergreens);
+        let applied: Vec<u32> = stages.iter().map(|s| s.intergreen).collect();
+        assert_eq!(applied, [6, 7, 8]);
+    }
+}
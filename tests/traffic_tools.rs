@@ -0,0 +1,58 @@
+//! Проверки командной строки traffic-tools: код выхода, stdout и stderr
+
+use std::process::Command;
+
+/// Запускает traffic-tools; возвращает код выхода, stdout и stderr
+fn run(args: &[&str]) -> (i32, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_traffic-tools"))
+        .args(args)
+        .output()
+        .expect("запуск traffic-tools");
+    (
+        output.status.code().unwrap_or(-1),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn test_reversed_range() {
+    let (code, stdout, stderr) = run(&["cond", "expand", "5-1"]);
+    assert_eq!(code, 1);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("начало больше конца"), "{}", stderr);
+
+    let (code, _, stderr) = run(&["cond", "eval", "and 5-1"]);
+    assert_eq!(code, 1);
+    assert!(stderr.contains("начало больше конца"), "{}", stderr);
+}
+
+#[test]
+fn test_range_too_large() {
+    let (code, stdout, stderr) = run(&["cond", "expand", "or 1-4000000000"]);
+    assert_eq!(code, 1);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("слишком большой"), "{}", stderr);
+
+    let (code, stdout, _) = run(&["--json", "cond", "expand", "1-3"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("ddr(D1) or ddr(D2) or ddr(D3)"));
+}
+
+#[test]
+fn test_eval_measurements() {
+    let (code, _, stderr) = run(&["cond", "eval", "occ(D3) >= 60"]);
+    assert_eq!(code, 1);
+    assert!(stderr.contains("Нет измерения occ(D3)"), "{}", stderr);
+
+    let (code, stdout, _) = run(&["cond", "eval", "--measure", "occ:3=75", "occ(D3) >= 60"]);
+    assert_eq!((code, stdout.trim()), (0, "true"));
+
+    let args = ["cond", "eval", "-a", "1", "-m", "occ:3=40", "-m", "cnt(D1)=4", "1 and occ(D3) < 50 and cnt(D1) = 4"];
+    let (code, stdout, _) = run(&args);
+    assert_eq!((code, stdout.trim()), (0, "true"));
+
+    let (code, _, stderr) = run(&["cond", "eval", "--measure", "occ:3", "occ(D3) >= 60"]);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("ожидалось измерение вида occ:3=75"), "{}", stderr);
+}